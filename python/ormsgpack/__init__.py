@@ -1,10 +1,17 @@
 # SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 from .ormsgpack import (
+    OPT_BIG_INT_AS_BIN,
+    OPT_BIG_INT_AS_EXT,
     OPT_DATETIME_AS_TIMESTAMP_EXT,
+    OPT_DECIMAL_AS_EXT,
+    OPT_DECIMAL_AS_STR,
+    OPT_KEEP_UNKNOWN_EXT,
     OPT_NAIVE_UTC,
+    OPT_NATIVE_TIMESTAMP,
     OPT_NON_STR_KEYS,
     OPT_OMIT_MICROSECONDS,
+    OPT_PARSE_DATETIME,
     OPT_PASSTHROUGH_BIG_INT,
     OPT_PASSTHROUGH_DATACLASS,
     OPT_PASSTHROUGH_DATETIME,
@@ -12,30 +19,86 @@ from .ormsgpack import (
     OPT_PASSTHROUGH_SUBCLASS,
     OPT_PASSTHROUGH_TUPLE,
     OPT_PASSTHROUGH_UUID,
+    OPT_REJECT_TRAILING_DATA,
     OPT_REPLACE_SURROGATES,
+    OPT_SERIALIZE_COLLECTIONS,
+    OPT_SERIALIZE_NAMEDTUPLE,
     OPT_SERIALIZE_NUMPY,
     OPT_SERIALIZE_PYDANTIC,
+    OPT_SERIALIZE_SET,
+    OPT_SET_AS_EXT,
     OPT_SORT_KEYS,
+    OPT_TIMEDELTA_AS_EXT,
+    OPT_TIMEDELTA_AS_ISO8601,
+    OPT_TIMEDELTA_AS_MICROSECONDS,
+    OPT_TIMEDELTA_AS_SECONDS,
+    OPT_TIMESTAMP_AS_NANOS,
+    OPT_TIMESTAMP_AS_SECONDS,
     OPT_UTC_Z,
     Ext,
+    ExtRegistry,
+    MsgpackBufferTooSmallError,
     MsgpackDecodeError,
+    MsgpackDecodeErrorKind,
+    MsgpackDepthError,
     MsgpackEncodeError,
+    MsgpackExtError,
+    MsgpackIntegerOverflowError,
+    MsgpackInvalidUtf8Error,
+    MsgpackLimitError,
+    MsgpackTruncatedError,
+    MsgpackUnsupportedTypeError,
+    Packer,
+    Timestamp,
+    Unpacker,
     __version__,
+    dump,
+    load,
+    load_iter,
     packb,
+    packb_into,
+    register_encoder,
     unpackb,
+    unpackb_partial,
 )
 
 __all__ = (
     "__version__",
+    "dump",
+    "load",
+    "load_iter",
     "packb",
+    "packb_into",
+    "register_encoder",
     "unpackb",
+    "unpackb_partial",
     "Ext",
+    "ExtRegistry",
+    "MsgpackBufferTooSmallError",
     "MsgpackDecodeError",
+    "MsgpackDecodeErrorKind",
+    "MsgpackDepthError",
     "MsgpackEncodeError",
+    "MsgpackExtError",
+    "MsgpackIntegerOverflowError",
+    "MsgpackInvalidUtf8Error",
+    "MsgpackLimitError",
+    "MsgpackTruncatedError",
+    "MsgpackUnsupportedTypeError",
+    "Packer",
+    "Timestamp",
+    "Unpacker",
+    "OPT_BIG_INT_AS_BIN",
+    "OPT_BIG_INT_AS_EXT",
     "OPT_DATETIME_AS_TIMESTAMP_EXT",
+    "OPT_DECIMAL_AS_EXT",
+    "OPT_DECIMAL_AS_STR",
+    "OPT_KEEP_UNKNOWN_EXT",
     "OPT_NAIVE_UTC",
+    "OPT_NATIVE_TIMESTAMP",
     "OPT_NON_STR_KEYS",
     "OPT_OMIT_MICROSECONDS",
+    "OPT_PARSE_DATETIME",
     "OPT_PASSTHROUGH_BIG_INT",
     "OPT_PASSTHROUGH_DATACLASS",
     "OPT_PASSTHROUGH_DATETIME",
@@ -43,9 +106,20 @@ __all__ = (
     "OPT_PASSTHROUGH_SUBCLASS",
     "OPT_PASSTHROUGH_TUPLE",
     "OPT_PASSTHROUGH_UUID",
+    "OPT_REJECT_TRAILING_DATA",
     "OPT_REPLACE_SURROGATES",
+    "OPT_SERIALIZE_COLLECTIONS",
+    "OPT_SERIALIZE_NAMEDTUPLE",
     "OPT_SERIALIZE_NUMPY",
     "OPT_SERIALIZE_PYDANTIC",
+    "OPT_SERIALIZE_SET",
+    "OPT_SET_AS_EXT",
     "OPT_SORT_KEYS",
+    "OPT_TIMEDELTA_AS_EXT",
+    "OPT_TIMEDELTA_AS_ISO8601",
+    "OPT_TIMEDELTA_AS_MICROSECONDS",
+    "OPT_TIMEDELTA_AS_SECONDS",
+    "OPT_TIMESTAMP_AS_NANOS",
+    "OPT_TIMESTAMP_AS_SECONDS",
     "OPT_UTC_Z",
 )
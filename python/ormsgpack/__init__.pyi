@@ -1,31 +1,270 @@
-from collections.abc import Callable
-from typing import Any
+import datetime
+import enum
+from collections.abc import Callable, Iterable, Iterator
+from typing import Any, Protocol, TypeVar, overload
+
+_T = TypeVar("_T")
 
 __version__: str
 
+class _SupportsRead(Protocol):
+    def read(self, size: int, /) -> bytes | bytearray | memoryview: ...
+
+class _SupportsWrite(Protocol):
+    def write(self, data: bytes, /) -> Any: ...
+
+def dump(
+    obj: Any,
+    fp: _SupportsWrite,
+    /,
+    default: Callable[[Any], Any] | None = ...,
+    option: int | None = None,
+    *,
+    ext_registry: ExtRegistry | None = None,
+) -> None: ...
+def load(
+    fp: _SupportsRead,
+    /,
+    *,
+    ext_hook: Callable[[int, bytes], Any] | None = ...,
+    ext_registry: ExtRegistry | None = ...,
+    option: int | None = ...,
+    max_depth: int | None = ...,
+    max_str_len: int | None = ...,
+    max_bin_len: int | None = ...,
+    max_array_len: int | None = ...,
+    max_map_len: int | None = ...,
+    max_ext_len: int | None = ...,
+    max_alloc_size: int | None = ...,
+) -> Any: ...
+def load_iter(
+    fp: _SupportsRead,
+    /,
+    *,
+    ext_hook: Callable[[int, bytes], Any] | None = ...,
+    ext_registry: ExtRegistry | None = ...,
+    option: int | None = ...,
+    max_depth: int | None = ...,
+    max_str_len: int | None = ...,
+    max_bin_len: int | None = ...,
+    max_array_len: int | None = ...,
+    max_map_len: int | None = ...,
+    max_ext_len: int | None = ...,
+    max_alloc_size: int | None = ...,
+    max_buffer_size: int | None = ...,
+) -> Unpacker: ...
+
 def packb(
     obj: Any,
     /,
     default: Callable[[Any], Any] | None = ...,
     option: int | None = None,
+    *,
+    ext_registry: ExtRegistry | None = None,
 ) -> bytes: ...
+def packb_into(
+    obj: Any,
+    buffer: Any,
+    /,
+    offset: int = 0,
+    *,
+    default: Callable[[Any], Any] | None = ...,
+    option: int | None = None,
+    ext_registry: ExtRegistry | None = None,
+) -> int: ...
+def register_encoder(
+    cls: type[_T],
+    encoder: Callable[[_T], Any] | None,
+    /,
+    *,
+    subclasses: bool = False,
+) -> None: ...
+@overload
+def unpackb(
+    obj: bytes | bytearray | memoryview,
+    /,
+    *,
+    type: type[_T],
+    ext_hook: Callable[[int, bytes], Any] | None = ...,
+    ext_registry: ExtRegistry | None = ...,
+    tzinfo: datetime.tzinfo | None = ...,
+    datetime_keys: Iterable[str] | None = ...,
+    option: int | None = ...,
+    max_depth: int | None = ...,
+    max_str_len: int | None = ...,
+    max_bin_len: int | None = ...,
+    max_array_len: int | None = ...,
+    max_map_len: int | None = ...,
+    max_ext_len: int | None = ...,
+    max_alloc_size: int | None = ...,
+) -> _T: ...
+@overload
+def unpackb(
+    obj: bytes | bytearray | memoryview,
+    /,
+    *,
+    model: type[_T],
+    ext_hook: Callable[[int, bytes], Any] | None = ...,
+    ext_registry: ExtRegistry | None = ...,
+    tzinfo: datetime.tzinfo | None = ...,
+    datetime_keys: Iterable[str] | None = ...,
+    option: int | None = ...,
+    max_depth: int | None = ...,
+    max_str_len: int | None = ...,
+    max_bin_len: int | None = ...,
+    max_array_len: int | None = ...,
+    max_map_len: int | None = ...,
+    max_ext_len: int | None = ...,
+    max_alloc_size: int | None = ...,
+) -> _T: ...
+@overload
 def unpackb(
     obj: bytes | bytearray | memoryview,
     /,
     *,
+    type: Any = ...,
+    model: None = ...,
     ext_hook: Callable[[int, bytes], Any] | None = ...,
+    ext_registry: ExtRegistry | None = ...,
+    tzinfo: datetime.tzinfo | None = ...,
+    datetime_keys: Iterable[str] | None = ...,
     option: int | None = ...,
+    max_depth: int | None = ...,
+    max_str_len: int | None = ...,
+    max_bin_len: int | None = ...,
+    max_array_len: int | None = ...,
+    max_map_len: int | None = ...,
+    max_ext_len: int | None = ...,
+    max_alloc_size: int | None = ...,
 ) -> Any: ...
+def unpackb_partial(
+    obj: bytes | bytearray | memoryview,
+    /,
+    offset: int = 0,
+    *,
+    ext_hook: Callable[[int, bytes], Any] | None = ...,
+    ext_registry: ExtRegistry | None = ...,
+    option: int | None = ...,
+    max_depth: int | None = ...,
+    max_str_len: int | None = ...,
+    max_bin_len: int | None = ...,
+    max_array_len: int | None = ...,
+    max_map_len: int | None = ...,
+    max_ext_len: int | None = ...,
+    max_alloc_size: int | None = ...,
+) -> tuple[Any, int]: ...
+
+class MsgpackDecodeError(ValueError):
+    kind: MsgpackDecodeErrorKind | None
+    pos: int | None
+    path: str | None
+    marker: int | None
 
-class MsgpackDecodeError(ValueError): ...
-class MsgpackEncodeError(TypeError): ...
+class MsgpackDecodeErrorKind(enum.Enum):
+    EXT_HOOK_FAILED = 1
+    EXT_HOOK_MISSING = 2
+    INTERNAL = 3
+    INVALID_STR = 4
+    INVALID_TYPE = 5
+    INVALID_VALUE = 6
+    LIMIT_EXCEEDED = 7
+    RECURSION_LIMIT_REACHED = 8
+    TRAILING_DATA = 9
+    UNEXPECTED_EOF = 10
+
+class MsgpackEncodeError(TypeError):
+    path: str | None
+
+class MsgpackBufferTooSmallError(MsgpackEncodeError): ...
+class MsgpackUnsupportedTypeError(MsgpackEncodeError): ...
+class MsgpackIntegerOverflowError(MsgpackEncodeError): ...
+class MsgpackTruncatedError(MsgpackDecodeError): ...
+class MsgpackInvalidUtf8Error(MsgpackDecodeError): ...
+class MsgpackExtError(MsgpackDecodeError): ...
+class MsgpackLimitError(MsgpackDecodeError): ...
+class MsgpackDepthError(MsgpackLimitError): ...
 
 class Ext:
-    def __init__(self, tag: int, data: bytes) -> None: ...
+    __match_args__ = ("tag", "data")
+    @property
+    def tag(self) -> int: ...
+    @property
+    def data(self) -> bytes: ...
+    def __init__(self, tag: int, data: bytes | bytearray | memoryview) -> None: ...
+
+class ExtRegistry:
+    def __init__(self) -> None: ...
+    def register(
+        self,
+        cls: type[_T],
+        tag: int,
+        to_payload: Callable[[_T], Any],
+        from_payload: Callable[[Any], _T],
+        /,
+        *,
+        recursive: bool = False,
+    ) -> None: ...
+
+class Packer:
+    def __init__(
+        self,
+        default: Callable[[Any], Any] | None = ...,
+        option: int | None = None,
+        *,
+        encoders: dict[type, Callable[[Any], Any]] | None = None,
+        ext_registry: ExtRegistry | None = None,
+    ) -> None: ...
+    def pack(self, obj: Any, /) -> bytes: ...
+
+class Timestamp:
+    __match_args__ = ("seconds", "nanoseconds")
+    @property
+    def seconds(self) -> int: ...
+    @property
+    def nanoseconds(self) -> int: ...
+    def __init__(self, seconds: int, nanoseconds: int = 0, /) -> None: ...
+    def __lt__(self, other: Timestamp) -> bool: ...
+    def __le__(self, other: Timestamp) -> bool: ...
+    def __gt__(self, other: Timestamp) -> bool: ...
+    def __ge__(self, other: Timestamp) -> bool: ...
+    @classmethod
+    def from_datetime(cls, datetime: datetime.datetime, /) -> Timestamp: ...
+    @classmethod
+    def from_unix_nanos(cls, nanos: int, /) -> Timestamp: ...
+    def to_datetime(self) -> datetime.datetime: ...
+    def to_datetime64(self) -> Any: ...
+    def to_unix_nanos(self) -> int: ...
+
+class Unpacker:
+    def __init__(
+        self,
+        *,
+        ext_hook: Callable[[int, bytes], Any] | None = ...,
+        ext_registry: ExtRegistry | None = ...,
+        option: int | None = ...,
+        max_depth: int | None = ...,
+        max_str_len: int | None = ...,
+        max_bin_len: int | None = ...,
+        max_array_len: int | None = ...,
+        max_map_len: int | None = ...,
+        max_ext_len: int | None = ...,
+        max_alloc_size: int | None = ...,
+        max_buffer_size: int | None = ...,
+    ) -> None: ...
+    def feed(self, data: bytes | bytearray | memoryview, /) -> None: ...
+    def __iter__(self) -> Iterator[Any]: ...
+    def __next__(self) -> Any: ...
 
+OPT_BIG_INT_AS_BIN: int
+OPT_BIG_INT_AS_EXT: int
 OPT_DATETIME_AS_TIMESTAMP_EXT: int
+OPT_DECIMAL_AS_EXT: int
+OPT_DECIMAL_AS_STR: int
+OPT_KEEP_UNKNOWN_EXT: int
 OPT_NAIVE_UTC: int
+OPT_NATIVE_TIMESTAMP: int
 OPT_OMIT_MICROSECONDS: int
+OPT_PARSE_DATETIME: int
 OPT_PASSTHROUGH_BIG_INT: int
 OPT_PASSTHROUGH_DATACLASS: int
 OPT_PASSTHROUGH_DATETIME: int
@@ -33,9 +272,20 @@ OPT_PASSTHROUGH_ENUM: int
 OPT_PASSTHROUGH_SUBCLASS: int
 OPT_PASSTHROUGH_TUPLE: int
 OPT_PASSTHROUGH_UUID: int
+OPT_REJECT_TRAILING_DATA: int
 OPT_REPLACE_SURROGATES: int
+OPT_SERIALIZE_COLLECTIONS: int
+OPT_SERIALIZE_NAMEDTUPLE: int
 OPT_SERIALIZE_NUMPY: int
 OPT_SERIALIZE_PYDANTIC: int
+OPT_SERIALIZE_SET: int
+OPT_SET_AS_EXT: int
 OPT_NON_STR_KEYS: int
 OPT_SORT_KEYS: int
+OPT_TIMEDELTA_AS_EXT: int
+OPT_TIMEDELTA_AS_ISO8601: int
+OPT_TIMEDELTA_AS_MICROSECONDS: int
+OPT_TIMEDELTA_AS_SECONDS: int
+OPT_TIMESTAMP_AS_NANOS: int
+OPT_TIMESTAMP_AS_SECONDS: int
 OPT_UTC_Z: int
@@ -1,11 +1,18 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use crate::deserialize::error::ErrorKind;
+use crate::deserialize::limits::{Limits, ITEM_SIZE};
+use crate::deserialize::rfc3339::{self, Rfc3339};
+use crate::deserialize::schema::*;
 use crate::deserialize::DeserializeError;
 use crate::exc::*;
+use crate::ext::new_ext;
+use crate::ext_registry::{lookup_tag, ExtEntry};
 use crate::ffi::*;
-use crate::msgpack::Marker;
+use crate::msgpack::*;
 use crate::opt::*;
 use crate::state::State;
+use crate::timestamp::{new_timestamp, new_unix_nanos};
 use chrono::{Datelike, Timelike};
 use simdutf8::basic::{from_utf8, Utf8Error};
 use std::borrow::Cow;
@@ -13,67 +20,198 @@ use std::io::Read;
 use std::os::raw::c_char;
 use std::ptr::NonNull;
 
-const RECURSION_LIMIT: u16 = 1024;
-
-pub fn deserialize(
+pub fn input_as_bytes(
     ptr: *mut pyo3::ffi::PyObject,
-    state: *mut State,
-    ext_hook: Option<NonNull<pyo3::ffi::PyObject>>,
-    opts: Opt,
-) -> Result<NonNull<pyo3::ffi::PyObject>, DeserializeError<'static>> {
+) -> Result<&'static [u8], DeserializeError<'static>> {
     let obj_type_ptr = ob_type!(ptr);
-    let contents: &[u8];
-
     if obj_type_ptr == &raw mut pyo3::ffi::PyBytes_Type {
-        contents = unsafe { pybytes_as_bytes(ptr) };
+        Ok(unsafe { pybytes_as_bytes(ptr) })
     } else if obj_type_ptr == &raw mut pyo3::ffi::PyMemoryView_Type {
-        if let Some(buffer) = unsafe { pymemoryview_as_bytes(ptr) } {
-            contents = buffer;
-        } else {
-            return Err(DeserializeError::new(Cow::Borrowed(
+        match unsafe { pymemoryview_as_bytes(ptr) } {
+            Some(buffer) => Ok(buffer),
+            None => Err(DeserializeError::new(Cow::Borrowed(
                 "Input type memoryview must be a C contiguous buffer",
-            )));
+            ))),
         }
     } else if obj_type_ptr == &raw mut pyo3::ffi::PyByteArray_Type {
-        contents = unsafe { pybytearray_as_bytes(ptr) };
+        Ok(unsafe { pybytearray_as_bytes(ptr) })
     } else {
-        return Err(DeserializeError::new(Cow::Borrowed(
+        Err(DeserializeError::new(Cow::Borrowed(
             "Input must be bytes, bytearray, memoryview",
-        )));
+        )))
+    }
+}
+
+/// The objects customizing the deserialization, as borrowed references.
+#[derive(Clone, Copy, Default)]
+pub struct Config {
+    pub hook: Option<NonNull<pyo3::ffi::PyObject>>,
+    pub registry: Option<NonNull<pyo3::ffi::PyObject>>,
+    /// The timezone to convert the datetimes decoded from timestamp
+    /// extension objects to.
+    pub tzinfo: Option<NonNull<pyo3::ffi::PyObject>>,
+    /// The frozenset of the map keys whose values are parsed with
+    /// `OPT_PARSE_DATETIME`. All strings are parsed if it is not set.
+    pub datetime_keys: Option<NonNull<pyo3::ffi::PyObject>>,
+}
+
+pub fn deserialize(
+    ptr: *mut pyo3::ffi::PyObject,
+    state: *mut State,
+    config: Config,
+    opts: Opt,
+    limits: &Limits,
+    schema: Option<&Schema>,
+) -> Result<NonNull<pyo3::ffi::PyObject>, DeserializeError<'static>> {
+    let contents = input_as_bytes(ptr)?;
+    deserialize_bytes(contents, state, config, opts, limits, schema)
+}
+
+pub fn deserialize_bytes(
+    contents: &[u8],
+    state: *mut State,
+    config: Config,
+    opts: Opt,
+    limits: &Limits,
+    schema: Option<&Schema>,
+) -> Result<NonNull<pyo3::ffi::PyObject>, DeserializeError<'static>> {
+    let (obj, end) = deserialize_partial(contents, state, config, opts, limits, schema)?;
+    if opts & REJECT_TRAILING_DATA != 0 && end != contents.len() {
+        unsafe { pyo3::ffi::Py_DECREF(obj.as_ptr()) };
+        let err = Error::TrailingData(end);
+        return Err(DeserializeError {
+            message: Cow::Owned(err.to_string()),
+            kind: Some(err.kind()),
+            pos: Some(end),
+            path: None,
+            marker: Some(contents[end]),
+        });
     }
+    Ok(obj)
+}
 
-    let mut deserializer = Deserializer::new(contents, state, ext_hook, opts);
-    deserializer
-        .deserialize()
-        .map_err(|e| DeserializeError::new(Cow::Owned(e.to_string())))
+/// Deserialize the object at the beginning of `contents` and return it with
+/// the offset of the first byte after it. If `schema` is set, the object
+/// must be of the type it describes.
+pub fn deserialize_partial(
+    contents: &[u8],
+    state: *mut State,
+    config: Config,
+    opts: Opt,
+    limits: &Limits,
+    schema: Option<&Schema>,
+) -> Result<(NonNull<pyo3::ffi::PyObject>, usize), DeserializeError<'static>> {
+    let mut deserializer = Deserializer::new(contents, state, config, opts, limits);
+    let res = match schema {
+        Some(schema) => deserializer.deserialize_typed(schema),
+        None => deserializer.deserialize(),
+    };
+    match res {
+        Ok(obj) => Ok((obj, contents.len() - deserializer.data.len())),
+        Err(err) => Err(deserializer.into_error(err, contents)),
+    }
 }
 
 #[derive(Debug)]
 enum Error {
-    ExtHookFailed,
+    /// The ext_hook raised an exception, which is described in the message.
+    ExtHookFailed(String),
     ExtHookMissing,
+    /// The from_payload of a registered extension type raised an exception,
+    /// which is described in the message.
+    ExtDecoderFailed(i8, String),
     Internal,
     InvalidStr,
     InvalidType(Marker),
     InvalidValue,
+    /// The conversion of a datetime to the `tzinfo` passed to `unpackb`
+    /// raised an exception, which is described in the message.
+    TimezoneFailed(String),
+    LimitExceeded(&'static str),
     RecursionLimitReached,
+    TrailingData(usize),
     UnexpectedEof,
+    /// An object does not match the type passed to `unpackb`.
+    TypeMismatch(Marker, String),
+    LiteralMismatch(String),
+    MissingField(String),
+    TooManyItems(String),
+    /// A class of the type passed to `unpackb` raised an exception when
+    /// creating an instance, which is described in the message.
+    ClassFailed(String, String),
+    /// The validator of a pydantic model raised an exception, which is
+    /// described in the message.
+    ValidationFailed(String, String),
+    Schema(String),
 }
 
 impl std::fmt::Display for Error {
     #[cold]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            Error::ExtHookFailed => f.write_str("ext_hook failed"),
+            Error::ExtHookFailed(ref description) => {
+                write!(f, "ext_hook failed: {description}")
+            }
             Error::ExtHookMissing => f.write_str("ext_hook missing"),
+            Error::ExtDecoderFailed(tag, ref description) => {
+                write!(
+                    f,
+                    "from_payload of extension type {tag} failed: {description}"
+                )
+            }
             Error::Internal => f.write_str("internal error"),
             Error::InvalidStr => f.write_str("invalid UTF-8 string"),
             Error::InvalidType(ref marker) => {
                 write!(f, "invalid type {marker:?}")
             }
             Error::InvalidValue => f.write_str("invalid value"),
+            Error::TimezoneFailed(ref description) => {
+                write!(f, "failed to convert datetime to tzinfo: {description}")
+            }
+            Error::LimitExceeded(name) => write!(f, "{name} exceeded"),
             Error::RecursionLimitReached => f.write_str(RECURSION_LIMIT_REACHED),
+            Error::TrailingData(pos) => write!(f, "trailing data at position {pos}"),
             Error::UnexpectedEof => write!(f, "unexpected end of file"),
+            Error::TypeMismatch(ref marker, ref expected) => {
+                write!(f, "invalid type {marker:?}, expected {expected}")
+            }
+            Error::LiteralMismatch(ref expected) => {
+                write!(f, "invalid value, expected {expected}")
+            }
+            Error::MissingField(ref name) => write!(f, "missing required field '{name}'"),
+            Error::TooManyItems(ref name) => write!(f, "too many items for {name}"),
+            Error::ClassFailed(ref name, ref description) => {
+                write!(f, "failed to create {name}: {description}")
+            }
+            Error::ValidationFailed(ref name, ref description) => {
+                write!(f, "failed to validate {name}: {description}")
+            }
+            Error::Schema(ref msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl Error {
+    fn kind(&self) -> ErrorKind {
+        match *self {
+            Error::ExtHookFailed(_) | Error::ExtDecoderFailed(_, _) => ErrorKind::ExtHookFailed,
+            Error::ExtHookMissing => ErrorKind::ExtHookMissing,
+            Error::Internal => ErrorKind::Internal,
+            Error::InvalidStr => ErrorKind::InvalidStr,
+            Error::InvalidType(_) => ErrorKind::InvalidType,
+            Error::InvalidValue => ErrorKind::InvalidValue,
+            Error::LimitExceeded(_) => ErrorKind::LimitExceeded,
+            Error::RecursionLimitReached => ErrorKind::RecursionLimitReached,
+            Error::TrailingData(_) => ErrorKind::TrailingData,
+            Error::UnexpectedEof => ErrorKind::UnexpectedEof,
+            Error::TypeMismatch(_, _) => ErrorKind::InvalidType,
+            Error::LiteralMismatch(_)
+            | Error::MissingField(_)
+            | Error::TooManyItems(_)
+            | Error::ClassFailed(_, _)
+            | Error::TimezoneFailed(_)
+            | Error::ValidationFailed(_, _) => ErrorKind::InvalidValue,
+            Error::Schema(_) => ErrorKind::Internal,
         }
     }
 }
@@ -87,28 +225,87 @@ impl From<Utf8Error> for Error {
 
 struct Deserializer<'de> {
     data: &'de [u8],
+    /// The length of the remaining data at the start of the innermost item
+    /// that failed to decode.
+    error_remaining: Option<usize>,
+    /// The path segments of the failed item, from the innermost.
+    error_path: Vec<String>,
     state: *mut State,
-    ext_hook: Option<NonNull<pyo3::ffi::PyObject>>,
+    config: Config,
     opts: Opt,
+    limits: Limits,
+    /// Whether the strings of the current map value are parsed with
+    /// `OPT_PARSE_DATETIME`.
+    parse_datetime: bool,
+    alloc_size: u64,
     recursion: u16,
 }
 
 impl<'de> Deserializer<'de> {
-    fn new(
-        data: &'de [u8],
-        state: *mut State,
-        ext_hook: Option<NonNull<pyo3::ffi::PyObject>>,
-        opts: Opt,
-    ) -> Self {
+    fn new(data: &'de [u8], state: *mut State, config: Config, opts: Opt, limits: &Limits) -> Self {
         Deserializer {
             data: data,
+            error_remaining: None,
+            error_path: Vec::new(),
             state: state,
-            ext_hook: ext_hook,
+            config: config,
             opts: opts,
+            limits: *limits,
+            parse_datetime: config.datetime_keys.is_none(),
+            alloc_size: 0,
             recursion: 0,
         }
     }
 
+    /// Record the start of the item that failed to decode, unless an item
+    /// nested in it already failed.
+    #[cold]
+    fn set_error_start(&mut self, remaining: usize) {
+        if self.error_remaining.is_none() {
+            self.error_remaining = Some(remaining);
+        }
+    }
+
+    #[cold]
+    fn into_error(self, err: Error, contents: &[u8]) -> DeserializeError<'static> {
+        let pos = contents.len() - self.error_remaining.unwrap_or(self.data.len());
+        let path = format_error_path(&self.error_path);
+        DeserializeError {
+            message: Cow::Owned(err.to_string()),
+            kind: Some(err.kind()),
+            pos: Some(pos),
+            path: Some(path),
+            marker: contents.get(pos).copied(),
+        }
+    }
+
+    #[inline(always)]
+    fn check_len(&mut self, len: u32, limit: u64, name: &'static str) -> Result<(), Error> {
+        if unlikely!(u64::from(len) > limit) {
+            return Err(Error::LimitExceeded(name));
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn allocate(&mut self, size: u64) -> Result<(), Error> {
+        self.alloc_size += size;
+        if unlikely!(self.alloc_size > self.limits.max_alloc_size) {
+            return Err(Error::LimitExceeded("max_alloc_size"));
+        }
+        Ok(())
+    }
+
+    /// Check that the remaining data may contain `count` items of at least
+    /// one byte, before allocating a container for them.
+    #[inline(always)]
+    fn check_items(&self, count: u64) -> Result<(), Error> {
+        if unlikely!(count > self.data.len() as u64) {
+            return Err(Error::UnexpectedEof);
+        }
+        Ok(())
+    }
+
     fn read_slice(&mut self, len: usize) -> Result<&'de [u8], Error> {
         if len > self.data.len() {
             return Err(Error::UnexpectedEof);
@@ -214,10 +411,8 @@ impl<'de> Deserializer<'de> {
         Ok(Marker::from_u8(n))
     }
 
-    fn deserialize_timestamp_ext(
-        &mut self,
-        len: u32,
-    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+    /// Read the data of a timestamp extension object.
+    fn read_timestamp(&mut self, len: u32) -> Result<(i64, u32), Error> {
         let (seconds, nanoseconds): (i64, u32) = match len {
             4 => {
                 let seconds = self.read_u32()?;
@@ -234,38 +429,303 @@ impl<'de> Deserializer<'de> {
             }
             _ => return Err(Error::InvalidValue),
         };
+        Ok((seconds, nanoseconds))
+    }
+
+    /// Decode a timestamp extension object as the type selected by the
+    /// options.
+    fn deserialize_timestamp(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let (seconds, nanoseconds) = self.read_timestamp(len)?;
+        if nanoseconds >= 1_000_000_000 {
+            return Err(Error::InvalidValue);
+        }
+        let obj = unsafe {
+            if self.opts & NATIVE_TIMESTAMP != 0 {
+                new_timestamp((*self.state).timestamp_type, seconds, nanoseconds)
+            } else if self.opts & TIMESTAMP_AS_NANOS != 0 {
+                new_unix_nanos(seconds, nanoseconds)
+            } else if self.opts & TIMESTAMP_AS_SECONDS != 0 {
+                pyo3::ffi::PyFloat_FromDouble(seconds as f64 + f64::from(nanoseconds) / 1e9)
+            } else {
+                self.timestamp_as_datetime(seconds, nanoseconds)?
+            }
+        };
+        // The year may be out of the range of datetime, in which case the
+        // raised ValueError is chained to the decode error.
+        NonNull::new(obj).ok_or(Error::InvalidValue)
+    }
+
+    unsafe fn timestamp_as_datetime(
+        &self,
+        seconds: i64,
+        nanoseconds: u32,
+    ) -> Result<*mut pyo3::ffi::PyObject, Error> {
         let datetime = match chrono::DateTime::<chrono::Utc>::from_timestamp(seconds, nanoseconds) {
             Some(value) => value,
             None => return Err(Error::InvalidValue),
         };
+        let datetime_api = *pyo3::ffi::PyDateTimeAPI();
+        let tzinfo = if self.opts & NAIVE_UTC != 0 {
+            pyo3::ffi::Py_None()
+        } else {
+            datetime_api.TimeZone_UTC
+        };
+        let obj = (datetime_api.DateTime_FromDateAndTime)(
+            datetime.year(),
+            datetime.month() as i32,
+            datetime.day() as i32,
+            datetime.hour() as i32,
+            datetime.minute() as i32,
+            datetime.second() as i32,
+            (datetime.nanosecond() / 1000) as i32,
+            tzinfo,
+            datetime_api.DateTimeType,
+        );
+        match self.config.tzinfo {
+            // The year may be out of the range of datetime, in which case the
+            // raised ValueError is chained to the decode error.
+            Some(_) if unlikely!(obj.is_null()) => Err(Error::InvalidValue),
+            Some(tzinfo) => {
+                let converted = pyobject_call_method_one_arg(
+                    obj,
+                    (*self.state).astimezone_str,
+                    tzinfo.as_ptr(),
+                );
+                pyo3::ffi::Py_DECREF(obj);
+                if unlikely!(converted.is_null()) {
+                    Err(Error::TimezoneFailed(describe_raised_exception()))
+                } else {
+                    Ok(converted)
+                }
+            }
+            None => Ok(obj),
+        }
+    }
+
+    /// Decode a duration serialized with `OPT_TIMEDELTA_AS_EXT` as a
+    /// `datetime.timedelta`, truncated to microseconds.
+    fn deserialize_timedelta(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        if len != 12 {
+            return Err(Error::InvalidValue);
+        }
+        let nanoseconds = self.read_u32()?;
+        let seconds = self.read_i64()?;
+        if nanoseconds >= 1_000_000_000 {
+            return Err(Error::InvalidValue);
+        }
+        let days = seconds.div_euclid(86400);
+        if !(-999_999_999..=999_999_999).contains(&days) {
+            return Err(Error::InvalidValue);
+        }
         unsafe {
-            let obj = {
-                let datetime_api = *pyo3::ffi::PyDateTimeAPI();
-                (datetime_api.DateTime_FromDateAndTime)(
-                    datetime.year(),
-                    datetime.month() as i32,
-                    datetime.day() as i32,
-                    datetime.hour() as i32,
-                    datetime.minute() as i32,
-                    datetime.second() as i32,
-                    (datetime.nanosecond() / 1000) as i32,
-                    datetime_api.TimeZone_UTC,
-                    datetime_api.DateTimeType,
-                )
+            let datetime_api = *pyo3::ffi::PyDateTimeAPI();
+            let obj = (datetime_api.Delta_FromDelta)(
+                days as i32,
+                seconds.rem_euclid(86400) as i32,
+                (nanoseconds / 1000) as i32,
+                1,
+                datetime_api.DeltaType,
+            );
+            Ok(NonNull::new_unchecked(obj))
+        }
+    }
+
+    /// Decode a set or frozenset serialized with `OPT_SET_AS_EXT`. The
+    /// elements are decoded as map keys, so that they are hashable.
+    fn deserialize_set(
+        &mut self,
+        len: u32,
+        frozen: bool,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let data = self.read_slice(len as usize)?;
+        self.deserialize_payload(data, |deserializer| {
+            deserializer.deserialize_set_elements(frozen)
+        })
+    }
+
+    /// Decode `data`, the payload of an extension object that was just read,
+    /// with `decode`, which must consume all of it. The length of the payload
+    /// is already checked, so an end of data in it is an invalid value.
+    fn deserialize_payload<F>(
+        &mut self,
+        data: &'de [u8],
+        decode: F,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error>
+    where
+        F: FnOnce(&mut Deserializer<'de>) -> Result<NonNull<pyo3::ffi::PyObject>, Error>,
+    {
+        let mut deserializer =
+            Deserializer::new(data, self.state, self.config, self.opts, &self.limits);
+        deserializer.alloc_size = self.alloc_size;
+        deserializer.recursion = self.recursion;
+        let res = decode(&mut deserializer);
+        self.alloc_size = deserializer.alloc_size;
+        match res {
+            Ok(obj) if deserializer.data.is_empty() => Ok(obj),
+            Ok(obj) => {
+                unsafe { pyo3::ffi::Py_DECREF(obj.as_ptr()) };
+                Err(Error::InvalidValue)
+            }
+            Err(err) => {
+                // The positions in the payload are relative to its end, which
+                // is the start of the remaining data.
+                let remaining = deserializer.error_remaining.unwrap_or(data.len());
+                self.set_error_start(remaining + self.data.len());
+                self.error_path.extend(deserializer.error_path);
+                match err {
+                    Error::UnexpectedEof => Err(Error::InvalidValue),
+                    err => Err(err),
+                }
+            }
+        }
+    }
+
+    fn deserialize_set_elements(
+        &mut self,
+        frozen: bool,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let len = match self.read_marker()? {
+            Marker::FixArray(len) => len.into(),
+            Marker::Array16 => self.read_u16()?.into(),
+            Marker::Array32 => self.read_u32()?,
+            marker => return Err(Error::InvalidType(marker)),
+        };
+        self.check_len(len, self.limits.max_array_len, "max_array_len")?;
+        self.check_items(len.into())?;
+        self.allocate(u64::from(len) * ITEM_SIZE)?;
+        let ptr = unsafe {
+            if frozen {
+                pyo3::ffi::PyFrozenSet_New(std::ptr::null_mut())
+            } else {
+                pyo3::ffi::PySet_New(std::ptr::null_mut())
+            }
+        };
+        for i in 0..len {
+            let elem = match self.deserialize_map_key() {
+                Ok(elem) => elem,
+                Err(err) => {
+                    self.error_path.push(format!("[{i}]"));
+                    unsafe { pyo3::ffi::Py_DECREF(ptr) };
+                    return Err(err);
+                }
             };
+            unsafe {
+                let ret = pyo3::ffi::PySet_Add(ptr, elem.as_ptr());
+                pyo3::ffi::Py_DECREF(elem.as_ptr());
+                if unlikely!(ret == -1) {
+                    pyo3::ffi::Py_DECREF(ptr);
+                    return Err(Error::Internal);
+                }
+            }
+        }
+        unsafe { Ok(NonNull::new_unchecked(ptr)) }
+    }
+
+    /// Decode an integer serialized with `OPT_BIG_INT_AS_EXT` as an `int`.
+    fn deserialize_big_int(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let data = self.read_slice(len as usize)?;
+        let (negative, magnitude) = match data.split_first() {
+            Some((0, magnitude)) => (false, magnitude),
+            Some((1, magnitude)) => (true, magnitude),
+            _ => return Err(Error::InvalidValue),
+        };
+        unsafe {
+            let mut obj =
+                pyo3::ffi::_PyLong_FromByteArray(magnitude.as_ptr(), magnitude.len(), 0, 0);
+            if negative {
+                let positive = obj;
+                obj = pyo3::ffi::PyNumber_Negative(positive);
+                pyo3::ffi::Py_DECREF(positive);
+            }
             Ok(NonNull::new_unchecked(obj))
         }
     }
 
-    fn deserialize_ext(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+    /// Decode a decimal serialized with `OPT_DECIMAL_AS_EXT` as a
+    /// `decimal.Decimal`.
+    fn deserialize_decimal(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let data = self.read_slice(len as usize)?;
+        if data.len() < 5 {
+            return Err(Error::InvalidValue);
+        }
+        let exponent = i32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+        let packed = &data[5..];
+        let mut value = String::with_capacity(16 + 2 * packed.len());
+        if data[0] & 1 != 0 {
+            value.push('-');
+        }
+        match data[0] >> 1 {
+            DECIMAL_FINITE if !packed.is_empty() => {}
+            DECIMAL_INFINITY if exponent == 0 && packed.is_empty() => value.push_str("Infinity"),
+            DECIMAL_NAN if exponent == 0 => value.push_str("NaN"),
+            DECIMAL_SNAN if exponent == 0 => value.push_str("sNaN"),
+            _ => return Err(Error::InvalidValue),
+        }
+        for byte in packed {
+            let (high, low) = (byte >> 4, byte & 0x0f);
+            if high > 9 || low > 9 {
+                return Err(Error::InvalidValue);
+            }
+            value.push((b'0' + high) as char);
+            value.push((b'0' + low) as char);
+        }
+        if data[0] >> 1 == DECIMAL_FINITE {
+            value.push('E');
+            value.push_str(itoa::Buffer::new().format(exponent));
+        }
+        unsafe {
+            let arg = unicode_from_str(&value);
+            let obj = pyo3::ffi::PyObject_CallOneArg(
+                (*self.state).decimal_type.cast::<pyo3::ffi::PyObject>(),
+                arg,
+            );
+            pyo3::ffi::Py_DECREF(arg);
+            if unlikely!(obj.is_null()) {
+                pyo3::ffi::PyErr_Clear();
+                return Err(Error::InvalidValue);
+            }
+            Ok(NonNull::new_unchecked(obj))
+        }
+    }
+
+    /// Decode an extension object, which is a map key if `key` is set.
+    fn deserialize_ext(
+        &mut self,
+        len: u32,
+        key: bool,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        self.check_len(len, self.limits.max_ext_len, "max_ext_len")?;
+        self.allocate(len.into())?;
         let tag = self.read_i8()?;
-        if tag == -1 && self.opts & DATETIME_AS_TIMESTAMP_EXT != 0 {
-            return self.deserialize_timestamp_ext(len);
+        // The registrations take precedence over the tags of the options.
+        if let Some(registry) = self.config.registry {
+            if let Some(entry) = unsafe { lookup_tag(registry.as_ptr(), tag) } {
+                let data = self.read_slice(len as usize)?;
+                return self.deserialize_registered_ext(tag, &entry, data);
+            }
+        }
+        if tag == -1 && self.opts & TIMESTAMP_OPTS != 0 {
+            return self.deserialize_timestamp(len);
+        }
+        if tag == TIMEDELTA_EXT_TAG && self.opts & TIMEDELTA_AS_EXT != 0 {
+            return self.deserialize_timedelta(len);
+        }
+        if tag == DECIMAL_EXT_TAG && self.opts & DECIMAL_AS_EXT != 0 {
+            return self.deserialize_decimal(len);
+        }
+        if tag == BIG_INT_EXT_TAG && self.opts & BIG_INT_AS_EXT != 0 {
+            return self.deserialize_big_int(len);
+        }
+        if (tag == SET_EXT_TAG || tag == FROZENSET_EXT_TAG) && self.opts & SET_AS_EXT != 0 {
+            // A set is not hashable.
+            if key && tag == SET_EXT_TAG {
+                return Err(Error::InvalidValue);
+            }
+            return self.deserialize_set(len, tag == FROZENSET_EXT_TAG);
         }
 
         let data = self.read_slice(len as usize)?;
-
-        match self.ext_hook {
+        match self.config.hook {
             Some(callable) => unsafe {
                 let tag_obj = pyo3::ffi::PyLong_FromLongLong(tag as i64);
                 let data_ptr = data.as_ptr().cast::<c_char>();
@@ -280,15 +740,57 @@ impl<'de> Deserializer<'de> {
                 pyo3::ffi::Py_DECREF(tag_obj);
                 pyo3::ffi::Py_DECREF(data_obj);
                 if unlikely!(obj.is_null()) {
-                    Err(Error::ExtHookFailed)
+                    Err(Error::ExtHookFailed(describe_raised_exception()))
                 } else {
                     Ok(NonNull::new_unchecked(obj))
                 }
             },
+            None if self.opts & KEEP_UNKNOWN_EXT != 0 => unsafe {
+                let tag_obj = pyo3::ffi::PyLong_FromLongLong(tag as i64);
+                let data_ptr = data.as_ptr().cast::<c_char>();
+                let data_len = data.len() as pyo3::ffi::Py_ssize_t;
+                let data_obj = pyo3::ffi::PyBytes_FromStringAndSize(data_ptr, data_len);
+                let obj = new_ext((*self.state).ext_type, tag_obj, data_obj);
+                Ok(NonNull::new_unchecked(obj))
+            },
             None => Err(Error::ExtHookMissing),
         }
     }
 
+    /// Decode the payload of an extension type registered in the
+    /// `ExtRegistry` and pass it to its `from_payload`.
+    fn deserialize_registered_ext(
+        &mut self,
+        tag: i8,
+        entry: &ExtEntry,
+        data: &'de [u8],
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let payload = if entry.recursive {
+            self.deserialize_payload(data, |deserializer| deserializer.deserialize())?
+                .as_ptr()
+        } else {
+            unsafe {
+                pyo3::ffi::PyBytes_FromStringAndSize(
+                    data.as_ptr().cast::<c_char>(),
+                    data.len() as pyo3::ffi::Py_ssize_t,
+                )
+            }
+        };
+        unsafe {
+            // from_payload may register the tag again, which releases the
+            // registration it is borrowed from.
+            let from_payload = pyo3::ffi::Py_NewRef(entry.from_payload);
+            let obj = pyobject_call_one_arg(from_payload, payload);
+            pyo3::ffi::Py_DECREF(from_payload);
+            pyo3::ffi::Py_DECREF(payload);
+            if unlikely!(obj.is_null()) {
+                Err(Error::ExtDecoderFailed(tag, describe_raised_exception()))
+            } else {
+                Ok(NonNull::new_unchecked(obj))
+            }
+        }
+    }
+
     fn deserialize_null(&self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
         unsafe {
             let py_none = pyo3::ffi::Py_None();
@@ -335,13 +837,77 @@ impl<'de> Deserializer<'de> {
     }
 
     fn deserialize_str(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        self.check_len(len, self.limits.max_str_len, "max_str_len")?;
+        self.allocate(len.into())?;
         let data = self.read_slice(len as usize)?;
         let value = from_utf8(data)?;
+        if unlikely!(self.opts & PARSE_DATETIME != 0 && self.parse_datetime) {
+            if let Some(value) = rfc3339::parse(data) {
+                return Ok(unsafe { self.rfc3339_as_datetime(value) });
+            }
+        }
         let ptr = unicode_from_str(value);
         unsafe { Ok(NonNull::new_unchecked(ptr)) }
     }
 
+    /// Create a `datetime.date`, `datetime.time` or `datetime.datetime`
+    /// from a string parsed with `OPT_PARSE_DATETIME`, preserving its UTC
+    /// offset.
+    #[cold]
+    unsafe fn rfc3339_as_datetime(&self, value: Rfc3339) -> NonNull<pyo3::ffi::PyObject> {
+        let datetime_api = *pyo3::ffi::PyDateTimeAPI();
+        let ptr = match value {
+            Rfc3339::Date(date) => {
+                (datetime_api.Date_FromDate)(date.year, date.month, date.day, datetime_api.DateType)
+            }
+            Rfc3339::Time(time) => (datetime_api.Time_FromTime)(
+                time.hour,
+                time.minute,
+                time.second,
+                time.microsecond,
+                pyo3::ffi::Py_None(),
+                datetime_api.TimeType,
+            ),
+            Rfc3339::DateTime(date, time, offset) => {
+                let tzinfo = match offset {
+                    None => {
+                        pyo3::ffi::Py_INCREF(pyo3::ffi::Py_None());
+                        pyo3::ffi::Py_None()
+                    }
+                    Some(0) => {
+                        pyo3::ffi::Py_INCREF(datetime_api.TimeZone_UTC);
+                        datetime_api.TimeZone_UTC
+                    }
+                    Some(offset) => {
+                        let delta =
+                            (datetime_api.Delta_FromDelta)(0, offset, 0, 1, datetime_api.DeltaType);
+                        let tzinfo =
+                            (datetime_api.TimeZone_FromTimeZone)(delta, std::ptr::null_mut());
+                        pyo3::ffi::Py_DECREF(delta);
+                        tzinfo
+                    }
+                };
+                let ptr = (datetime_api.DateTime_FromDateAndTime)(
+                    date.year,
+                    date.month,
+                    date.day,
+                    time.hour,
+                    time.minute,
+                    time.second,
+                    time.microsecond,
+                    tzinfo,
+                    datetime_api.DateTimeType,
+                );
+                pyo3::ffi::Py_DECREF(tzinfo);
+                ptr
+            }
+        };
+        NonNull::new_unchecked(ptr)
+    }
+
     fn deserialize_bin(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        self.check_len(len, self.limits.max_bin_len, "max_bin_len")?;
+        self.allocate(len.into())?;
         let v = self.read_slice(len as usize)?;
         let ptr = v.as_ptr().cast::<c_char>();
         let len = v.len() as pyo3::ffi::Py_ssize_t;
@@ -352,38 +918,44 @@ impl<'de> Deserializer<'de> {
     }
 
     fn deserialize_array(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        self.check_len(len, self.limits.max_array_len, "max_array_len")?;
+        self.check_items(len.into())?;
+        self.allocate(u64::from(len) * ITEM_SIZE)?;
         let ptr = unsafe { pyo3::ffi::PyList_New(len as pyo3::ffi::Py_ssize_t) };
         for i in 0..len {
-            let elem = self.deserialize()?;
+            let elem = match self.deserialize() {
+                Ok(elem) => elem,
+                Err(err) => {
+                    self.error_path.push(format!("[{i}]"));
+                    unsafe { pyo3::ffi::Py_DECREF(ptr) };
+                    return Err(err);
+                }
+            };
             unsafe { pyo3::ffi::PyList_SET_ITEM(ptr, i as pyo3::ffi::Py_ssize_t, elem.as_ptr()) };
         }
         unsafe { Ok(NonNull::new_unchecked(ptr)) }
     }
 
+    fn check_map(&mut self, len: u32) -> Result<(), Error> {
+        self.check_len(len, self.limits.max_map_len, "max_map_len")?;
+        self.check_items(2 * u64::from(len))?;
+        self.allocate(2 * u64::from(len) * ITEM_SIZE)
+    }
+
     fn deserialize_map_with_str_keys(
         &mut self,
         len: u32,
     ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        self.check_map(len)?;
         let dict_ptr = unsafe { pydict_new_presized(len as pyo3::ffi::Py_ssize_t) };
         for _ in 0..len {
-            let marker = self.read_marker()?;
-            let key = match marker {
-                Marker::FixStr(len) => self.deserialize_map_str_key(len.into()),
-                Marker::Str8 => {
-                    let len = self.read_u8()?;
-                    self.deserialize_map_str_key(len.into())
+            let (key, value) = match self.deserialize_str_key_value() {
+                Ok(item) => item,
+                Err(err) => {
+                    unsafe { pyo3::ffi::Py_DECREF(dict_ptr) };
+                    return Err(err);
                 }
-                Marker::Str16 => {
-                    let len = self.read_u16()?;
-                    self.deserialize_map_str_key(len.into())
-                }
-                Marker::Str32 => {
-                    let len = self.read_u32()?;
-                    self.deserialize_map_str_key(len)
-                }
-                marker => Err(Error::InvalidType(marker)),
-            }?;
-            let value = self.deserialize()?;
+            };
             unsafe {
                 let pyhash = (*key.as_ptr().cast::<pyo3::ffi::PyASCIIObject>()).hash;
                 let _ = pydict_set_item_known_hash(dict_ptr, key.as_ptr(), value.as_ptr(), pyhash);
@@ -395,19 +967,69 @@ impl<'de> Deserializer<'de> {
         unsafe { Ok(NonNull::new_unchecked(dict_ptr)) }
     }
 
+    #[inline(always)]
+    fn deserialize_str_key_value(
+        &mut self,
+    ) -> Result<(NonNull<pyo3::ffi::PyObject>, NonNull<pyo3::ffi::PyObject>), Error> {
+        let remaining = self.data.len();
+        let key = match self.deserialize_str_key() {
+            Ok(key) => key,
+            Err(err) => {
+                self.set_error_start(remaining);
+                return Err(err);
+            }
+        };
+        match self.deserialize_map_value(key) {
+            Ok(value) => Ok((key, value)),
+            Err(err) => {
+                self.error_path.push(path_key_segment(key.as_ptr()));
+                unsafe { pyo3::ffi::Py_DECREF(key.as_ptr()) };
+                Err(err)
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn deserialize_str_key(&mut self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let marker = self.read_marker()?;
+        match marker {
+            Marker::FixStr(len) => self.deserialize_map_str_key(len.into()),
+            Marker::Str8 => {
+                let len = self.read_u8()?;
+                self.deserialize_map_str_key(len.into())
+            }
+            Marker::Str16 => {
+                let len = self.read_u16()?;
+                self.deserialize_map_str_key(len.into())
+            }
+            Marker::Str32 => {
+                let len = self.read_u32()?;
+                self.deserialize_map_str_key(len)
+            }
+            marker => Err(Error::InvalidType(marker)),
+        }
+    }
+
     fn deserialize_map_with_non_str_keys(
         &mut self,
         len: u32,
     ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        self.check_map(len)?;
         let dict_ptr = unsafe { pydict_new_presized(len as pyo3::ffi::Py_ssize_t) };
         for _ in 0..len {
-            let key = self.deserialize_map_key()?;
-            let value = self.deserialize()?;
+            let (key, value) = match self.deserialize_non_str_key_value() {
+                Ok(item) => item,
+                Err(err) => {
+                    unsafe { pyo3::ffi::Py_DECREF(dict_ptr) };
+                    return Err(err);
+                }
+            };
             unsafe {
                 let ret = pyo3::ffi::PyDict_SetItem(dict_ptr, key.as_ptr(), value.as_ptr());
                 pyo3::ffi::Py_DECREF(key.as_ptr());
                 pyo3::ffi::Py_DECREF(value.as_ptr());
                 if unlikely!(ret == -1) {
+                    pyo3::ffi::Py_DECREF(dict_ptr);
                     return Err(Error::Internal);
                 }
             }
@@ -415,6 +1037,48 @@ impl<'de> Deserializer<'de> {
         unsafe { Ok(NonNull::new_unchecked(dict_ptr)) }
     }
 
+    #[inline(always)]
+    fn deserialize_non_str_key_value(
+        &mut self,
+    ) -> Result<(NonNull<pyo3::ffi::PyObject>, NonNull<pyo3::ffi::PyObject>), Error> {
+        let key = self.deserialize_map_key()?;
+        match self.deserialize_map_value(key) {
+            Ok(value) => Ok((key, value)),
+            Err(err) => {
+                self.error_path.push(path_key_segment(key.as_ptr()));
+                unsafe { pyo3::ffi::Py_DECREF(key.as_ptr()) };
+                Err(err)
+            }
+        }
+    }
+
+    /// Deserialize the value of a map item, parsing its strings with
+    /// `OPT_PARSE_DATETIME` if `datetime_keys` contains the key.
+    #[inline(always)]
+    fn deserialize_map_value(
+        &mut self,
+        key: NonNull<pyo3::ffi::PyObject>,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        match self.config.datetime_keys {
+            Some(keys) => {
+                let parent = self.parse_datetime;
+                self.parse_datetime =
+                    match unsafe { pyo3::ffi::PySet_Contains(keys.as_ptr(), key.as_ptr()) } {
+                        1 => true,
+                        0 => false,
+                        _ => {
+                            unsafe { pyo3::ffi::PyErr_Clear() };
+                            false
+                        }
+                    };
+                let value = self.deserialize();
+                self.parse_datetime = parent;
+                value
+            }
+            None => self.deserialize(),
+        }
+    }
+
     fn deserialize_map(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
         if self.opts & NON_STR_KEYS != 0 {
             self.deserialize_map_with_non_str_keys(len)
@@ -424,8 +1088,18 @@ impl<'de> Deserializer<'de> {
     }
 
     fn deserialize(&mut self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let remaining = self.data.len();
+        let value = self.deserialize_value();
+        if unlikely!(value.is_err()) {
+            self.set_error_start(remaining);
+        }
+        value
+    }
+
+    #[inline(always)]
+    fn deserialize_value(&mut self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
         self.recursion += 1;
-        if unlikely!(self.recursion == RECURSION_LIMIT) {
+        if unlikely!(u64::from(self.recursion) > self.limits.max_depth) {
             return Err(Error::RecursionLimitReached);
         }
 
@@ -519,22 +1193,22 @@ impl<'de> Deserializer<'de> {
                 let len = self.read_u32()?;
                 self.deserialize_map(len)
             }
-            Marker::FixExt1 => self.deserialize_ext(1),
-            Marker::FixExt2 => self.deserialize_ext(2),
-            Marker::FixExt4 => self.deserialize_ext(4),
-            Marker::FixExt8 => self.deserialize_ext(8),
-            Marker::FixExt16 => self.deserialize_ext(16),
+            Marker::FixExt1 => self.deserialize_ext(1, false),
+            Marker::FixExt2 => self.deserialize_ext(2, false),
+            Marker::FixExt4 => self.deserialize_ext(4, false),
+            Marker::FixExt8 => self.deserialize_ext(8, false),
+            Marker::FixExt16 => self.deserialize_ext(16, false),
             Marker::Ext8 => {
                 let len = self.read_u8()?;
-                self.deserialize_ext(len.into())
+                self.deserialize_ext(len.into(), false)
             }
             Marker::Ext16 => {
                 let len = self.read_u16()?;
-                self.deserialize_ext(len.into())
+                self.deserialize_ext(len.into(), false)
             }
             Marker::Ext32 => {
                 let len = self.read_u32()?;
-                self.deserialize_ext(len)
+                self.deserialize_ext(len, false)
             }
             Marker::Reserved => Err(Error::InvalidType(Marker::Reserved)),
         };
@@ -543,12 +1217,443 @@ impl<'de> Deserializer<'de> {
         value
     }
 
+    /// Deserialize an object of the type described by `schema`.
+    fn deserialize_typed(
+        &mut self,
+        schema: &Schema,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let remaining = self.data.len();
+        let value = self.deserialize_typed_value(schema);
+        if unlikely!(value.is_err()) {
+            self.set_error_start(remaining);
+        }
+        value
+    }
+
+    fn peek_marker(&self) -> Result<Marker, Error> {
+        match self.data.first() {
+            Some(&n) => Ok(Marker::from_u8(n)),
+            None => Err(Error::UnexpectedEof),
+        }
+    }
+
+    fn deserialize_typed_value(
+        &mut self,
+        schema: &Schema,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        match schema {
+            Schema::Any => self.deserialize_value(),
+            Schema::List(item) => {
+                self.enter_typed()?;
+                let value = self.deserialize_typed_array(schema, item)?;
+                self.recursion -= 1;
+                Ok(value)
+            }
+            Schema::Dict(value_schema) => {
+                self.enter_typed()?;
+                let value = self.deserialize_typed_map(schema, value_schema)?;
+                self.recursion -= 1;
+                Ok(value)
+            }
+            Schema::Class(class_ref, kind) => {
+                let cls = get_class(*class_ref).map_err(Error::Schema)?;
+                let value = if matches!(kind, ClassKind::PydanticModel) {
+                    self.deserialize_model(schema, cls)
+                } else {
+                    self.enter_typed()
+                        .and_then(|()| self.deserialize_class(schema, cls, *kind))
+                        .inspect(|_| self.recursion -= 1)
+                };
+                unsafe { pyo3::ffi::Py_DECREF(cls) };
+                value
+            }
+            Schema::Union(members) => self.deserialize_union(schema, members),
+            Schema::Literal(values) => {
+                let marker = self.peek_marker()?;
+                if !schema.accepts(marker) {
+                    return Err(Error::TypeMismatch(marker, schema.describe()));
+                }
+                let value = self.deserialize_str_value()?;
+                for &literal in values.iter() {
+                    unsafe {
+                        if ob_type!(literal) == ob_type!(value.as_ptr())
+                            && pyo3::ffi::PyObject_RichCompareBool(
+                                value.as_ptr(),
+                                literal,
+                                pyo3::ffi::Py_EQ,
+                            ) == 1
+                        {
+                            return Ok(value);
+                        }
+                    }
+                }
+                unsafe { pyo3::ffi::Py_DECREF(value.as_ptr()) };
+                Err(Error::LiteralMismatch(schema.describe()))
+            }
+            _ => {
+                let marker = self.peek_marker()?;
+                if !schema.accepts(marker) {
+                    return Err(Error::TypeMismatch(marker, schema.describe()));
+                }
+                let value = if matches!(schema, Schema::Str) {
+                    self.deserialize_str_value()?
+                } else {
+                    self.deserialize_value()?
+                };
+                if matches!(schema, Schema::Float)
+                    && unsafe { pyo3::ffi::PyLong_Check(value.as_ptr()) } != 0
+                {
+                    unsafe {
+                        let float = pyo3::ffi::PyLong_AsDouble(value.as_ptr());
+                        pyo3::ffi::Py_DECREF(value.as_ptr());
+                        return self.deserialize_f64(float);
+                    }
+                }
+                Ok(value)
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn enter_typed(&mut self) -> Result<(), Error> {
+        self.recursion += 1;
+        if unlikely!(u64::from(self.recursion) > self.limits.max_depth) {
+            return Err(Error::RecursionLimitReached);
+        }
+        Ok(())
+    }
+
+    fn read_array_len(&mut self, schema: &Schema) -> Result<u32, Error> {
+        match self.read_marker()? {
+            Marker::FixArray(len) => Ok(len.into()),
+            Marker::Array16 => Ok(self.read_u16()?.into()),
+            Marker::Array32 => self.read_u32(),
+            marker => Err(Error::TypeMismatch(marker, schema.describe())),
+        }
+    }
+
+    fn read_map_len(&mut self, schema: &Schema) -> Result<u32, Error> {
+        match self.read_marker()? {
+            Marker::FixMap(len) => Ok(len.into()),
+            Marker::Map16 => Ok(self.read_u16()?.into()),
+            Marker::Map32 => self.read_u32(),
+            marker => Err(Error::TypeMismatch(marker, schema.describe())),
+        }
+    }
+
+    fn deserialize_typed_array(
+        &mut self,
+        schema: &Schema,
+        item: &Schema,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let len = self.read_array_len(schema)?;
+        self.check_len(len, self.limits.max_array_len, "max_array_len")?;
+        self.check_items(len.into())?;
+        self.allocate(u64::from(len) * ITEM_SIZE)?;
+        let ptr = unsafe { pyo3::ffi::PyList_New(len as pyo3::ffi::Py_ssize_t) };
+        for i in 0..len {
+            let elem = match self.deserialize_typed(item) {
+                Ok(elem) => elem,
+                Err(err) => {
+                    self.error_path.push(format!("[{i}]"));
+                    unsafe { pyo3::ffi::Py_DECREF(ptr) };
+                    return Err(err);
+                }
+            };
+            unsafe { pyo3::ffi::PyList_SET_ITEM(ptr, i as pyo3::ffi::Py_ssize_t, elem.as_ptr()) };
+        }
+        unsafe { Ok(NonNull::new_unchecked(ptr)) }
+    }
+
+    fn deserialize_typed_map(
+        &mut self,
+        schema: &Schema,
+        value_schema: &Schema,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let len = self.read_map_len(schema)?;
+        self.check_map(len)?;
+        let dict_ptr = unsafe { pydict_new_presized(len as pyo3::ffi::Py_ssize_t) };
+        for _ in 0..len {
+            let remaining = self.data.len();
+            let key = match self.deserialize_str_key() {
+                Ok(key) => key,
+                Err(err) => {
+                    self.set_error_start(remaining);
+                    unsafe { pyo3::ffi::Py_DECREF(dict_ptr) };
+                    return Err(err);
+                }
+            };
+            let value = match self.deserialize_typed(value_schema) {
+                Ok(value) => value,
+                Err(err) => {
+                    self.error_path.push(path_key_segment(key.as_ptr()));
+                    unsafe {
+                        pyo3::ffi::Py_DECREF(key.as_ptr());
+                        pyo3::ffi::Py_DECREF(dict_ptr);
+                    }
+                    return Err(err);
+                }
+            };
+            unsafe {
+                let pyhash = (*key.as_ptr().cast::<pyo3::ffi::PyASCIIObject>()).hash;
+                let _ = pydict_set_item_known_hash(dict_ptr, key.as_ptr(), value.as_ptr(), pyhash);
+                pyo3::ffi::Py_DECREF(key.as_ptr());
+                pyo3::ffi::Py_DECREF(value.as_ptr());
+            }
+        }
+        unsafe { Ok(NonNull::new_unchecked(dict_ptr)) }
+    }
+
+    /// Deserialize a value of a `str` or `Literal` type, whose strings are
+    /// not parsed with `OPT_PARSE_DATETIME`.
+    fn deserialize_str_value(&mut self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let parent = self.parse_datetime;
+        self.parse_datetime = false;
+        let value = self.deserialize_value();
+        self.parse_datetime = parent;
+        value
+    }
+
+    fn deserialize_class(
+        &mut self,
+        schema: &Schema,
+        cls: *mut pyo3::ffi::PyObject,
+        kind: ClassKind,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let marker = self.peek_marker()?;
+        let capsule = get_class_schema(self.state, cls, kind).map_err(Error::Schema)?;
+        let class = unsafe { class_schema_from_capsule(capsule) };
+        let res = if matches!(kind, ClassKind::NamedTuple)
+            && !matches!(marker, Marker::FixMap(_) | Marker::Map16 | Marker::Map32)
+        {
+            self.deserialize_class_args(schema, cls, class)
+        } else {
+            self.deserialize_class_kwargs(schema, cls, class)
+        };
+        unsafe { pyo3::ffi::Py_DECREF(capsule) };
+        res
+    }
+
+    /// Deserialize the fields of a class from an array, for a `NamedTuple`.
+    fn deserialize_class_args(
+        &mut self,
+        schema: &Schema,
+        cls: *mut pyo3::ffi::PyObject,
+        class: &ClassSchema,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let len = self.read_array_len(schema)?;
+        if len as usize > class.fields.len() {
+            return Err(Error::TooManyItems(schema.describe()));
+        }
+        if let Some(field) = class.fields[len as usize..].iter().find(|f| f.required) {
+            return Err(Error::MissingField(field.name.clone()));
+        }
+        let args = unsafe { pyo3::ffi::PyTuple_New(len as pyo3::ffi::Py_ssize_t) };
+        for (i, field) in class.fields[..len as usize].iter().enumerate() {
+            let elem = match self.deserialize_typed(&field.schema) {
+                Ok(elem) => elem,
+                Err(err) => {
+                    self.error_path.push(format!("[{i}]"));
+                    unsafe { pyo3::ffi::Py_DECREF(args) };
+                    return Err(err);
+                }
+            };
+            unsafe { pyo3::ffi::PyTuple_SET_ITEM(args, i as pyo3::ffi::Py_ssize_t, elem.as_ptr()) };
+        }
+        let obj = unsafe { pyo3::ffi::PyObject_Call(cls, args, std::ptr::null_mut()) };
+        unsafe { pyo3::ffi::Py_DECREF(args) };
+        self.class_instance(cls, obj)
+    }
+
+    /// Deserialize the fields of a class from a map, ignoring unknown keys.
+    fn deserialize_class_kwargs(
+        &mut self,
+        schema: &Schema,
+        cls: *mut pyo3::ffi::PyObject,
+        class: &ClassSchema,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let len = self.read_map_len(schema)?;
+        self.check_map(len)?;
+        let dict_ptr = unsafe { pydict_new_presized(class.fields.len() as pyo3::ffi::Py_ssize_t) };
+        let mut found = vec![false; class.fields.len()];
+        for _ in 0..len {
+            if let Err(err) = self.deserialize_field(class, dict_ptr, &mut found) {
+                unsafe { pyo3::ffi::Py_DECREF(dict_ptr) };
+                return Err(err);
+            }
+        }
+        if let Some(field) = class
+            .fields
+            .iter()
+            .zip(found.iter())
+            .find(|(field, found)| field.required && !**found)
+            .map(|(field, _)| field)
+        {
+            unsafe { pyo3::ffi::Py_DECREF(dict_ptr) };
+            return Err(Error::MissingField(field.name.clone()));
+        }
+        if matches!(class.kind, ClassKind::TypedDict | ClassKind::PydanticModel) {
+            return unsafe { Ok(NonNull::new_unchecked(dict_ptr)) };
+        }
+        let obj = unsafe {
+            let args = pyo3::ffi::PyTuple_New(0);
+            let obj = pyo3::ffi::PyObject_Call(cls, args, dict_ptr);
+            pyo3::ffi::Py_DECREF(args);
+            pyo3::ffi::Py_DECREF(dict_ptr);
+            obj
+        };
+        self.class_instance(cls, obj)
+    }
+
+    #[inline(always)]
+    fn deserialize_field(
+        &mut self,
+        class: &ClassSchema,
+        dict_ptr: *mut pyo3::ffi::PyObject,
+        found: &mut [bool],
+    ) -> Result<(), Error> {
+        let remaining = self.data.len();
+        let key = match self.deserialize_str_key() {
+            Ok(key) => key,
+            Err(err) => {
+                self.set_error_start(remaining);
+                return Err(err);
+            }
+        };
+        let index = match unicode_to_str(key.as_ptr()) {
+            Ok(name) => class.fields.iter().position(|field| field.name == name),
+            Err(_) => None,
+        };
+        let value = match index {
+            Some(i) => self.deserialize_typed(&class.fields[i].schema),
+            None => self.deserialize(),
+        };
+        let value = match value {
+            Ok(value) => value,
+            Err(err) => {
+                self.error_path.push(path_key_segment(key.as_ptr()));
+                unsafe { pyo3::ffi::Py_DECREF(key.as_ptr()) };
+                return Err(err);
+            }
+        };
+        if let Some(i) = index {
+            found[i] = true;
+            unsafe {
+                pyo3::ffi::PyDict_SetItem(dict_ptr, class.fields[i].key, value.as_ptr());
+            }
+        }
+        unsafe {
+            pyo3::ffi::Py_DECREF(key.as_ptr());
+            pyo3::ffi::Py_DECREF(value.as_ptr());
+        }
+        Ok(())
+    }
+
+    /// Deserialize an object and validate it with the validator of a pydantic
+    /// model. The fields of a map are deserialized into the validated dict
+    /// and its unknown keys are skipped, unless the model may use them.
+    fn deserialize_model(
+        &mut self,
+        schema: &Schema,
+        cls: *mut pyo3::ffi::PyObject,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let marker = self.peek_marker()?;
+        let capsule =
+            get_class_schema(self.state, cls, ClassKind::PydanticModel).map_err(Error::Schema)?;
+        let class = unsafe { class_schema_from_capsule(capsule) };
+        let obj = if class.keep_unknown
+            || !matches!(marker, Marker::FixMap(_) | Marker::Map16 | Marker::Map32)
+        {
+            self.deserialize_value()
+        } else {
+            self.enter_typed()
+                .and_then(|()| self.deserialize_class_kwargs(schema, cls, class))
+                .inspect(|_| self.recursion -= 1)
+        };
+        let res = obj.and_then(|obj| unsafe {
+            let validator = pyo3::ffi::PyObject_GetAttr(cls, (*self.state).pydantic_validator_str);
+            let model = if validator.is_null() {
+                validator
+            } else {
+                let model = pyobject_call_method_one_arg(
+                    validator,
+                    (*self.state).validate_python_str,
+                    obj.as_ptr(),
+                );
+                pyo3::ffi::Py_DECREF(validator);
+                model
+            };
+            pyo3::ffi::Py_DECREF(obj.as_ptr());
+            match NonNull::new(model) {
+                Some(model) => Ok(model),
+                None => Err(Error::ValidationFailed(
+                    type_name(cls),
+                    describe_raised_exception(),
+                )),
+            }
+        });
+        unsafe { pyo3::ffi::Py_DECREF(capsule) };
+        res
+    }
+
+    #[inline(always)]
+    fn class_instance(
+        &mut self,
+        cls: *mut pyo3::ffi::PyObject,
+        obj: *mut pyo3::ffi::PyObject,
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        match NonNull::new(obj) {
+            Some(obj) => Ok(obj),
+            None => Err(Error::ClassFailed(
+                type_name(cls),
+                describe_raised_exception(),
+            )),
+        }
+    }
+
+    /// Deserialize an object with the members of a union that accept its
+    /// marker, in order, until one succeeds.
+    fn deserialize_union(
+        &mut self,
+        schema: &Schema,
+        members: &[Schema],
+    ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let marker = self.peek_marker()?;
+        let mut candidates = members
+            .iter()
+            .filter(|member| member.accepts(marker))
+            .peekable();
+        let Some(mut member) = candidates.next() else {
+            return Err(Error::TypeMismatch(marker, schema.describe()));
+        };
+        let data = self.data;
+        let alloc_size = self.alloc_size;
+        let recursion = self.recursion;
+        loop {
+            let value = self.deserialize_typed_value(member);
+            match candidates.next() {
+                Some(next) if value.is_err() => {
+                    self.data = data;
+                    self.alloc_size = alloc_size;
+                    self.recursion = recursion;
+                    self.error_remaining = None;
+                    self.error_path.clear();
+                    unsafe { pyo3::ffi::PyErr_Clear() };
+                    member = next;
+                }
+                _ => return value,
+            }
+        }
+    }
+
     fn deserialize_map_str_key(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
         if unlikely!(len > 64) {
             let value = self.deserialize_str(len)?;
             hash_str(value.as_ptr());
             Ok(value)
         } else {
+            self.check_len(len, self.limits.max_str_len, "max_str_len")?;
+            self.allocate(len.into())?;
             let data = self.read_slice(len as usize)?;
             Ok(unsafe { (*self.state).key_map.get(data)? })
         }
@@ -558,9 +1663,18 @@ impl<'de> Deserializer<'de> {
         &mut self,
         len: u32,
     ) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        self.check_len(len, self.limits.max_array_len, "max_array_len")?;
+        self.check_items(len.into())?;
+        self.allocate(u64::from(len) * ITEM_SIZE)?;
         let ptr = unsafe { pyo3::ffi::PyTuple_New(len as pyo3::ffi::Py_ssize_t) };
         for i in 0..len {
-            let elem = self.deserialize_map_key()?;
+            let elem = match self.deserialize_map_key() {
+                Ok(elem) => elem,
+                Err(err) => {
+                    unsafe { pyo3::ffi::Py_DECREF(ptr) };
+                    return Err(err);
+                }
+            };
             unsafe {
                 pytuple_set_item(ptr, i as pyo3::ffi::Py_ssize_t, elem.as_ptr());
             }
@@ -568,18 +1682,19 @@ impl<'de> Deserializer<'de> {
         unsafe { Ok(NonNull::new_unchecked(ptr)) }
     }
 
-    fn deserialize_map_ext_key(&mut self, len: u32) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
-        let tag = self.read_i8()?;
-        if tag == -1 && self.opts & DATETIME_AS_TIMESTAMP_EXT != 0 {
-            self.deserialize_timestamp_ext(len)
-        } else {
-            Err(Error::InvalidValue)
+    fn deserialize_map_key(&mut self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+        let remaining = self.data.len();
+        let value = self.deserialize_map_key_value();
+        if unlikely!(value.is_err()) {
+            self.set_error_start(remaining);
         }
+        value
     }
 
-    fn deserialize_map_key(&mut self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
+    #[inline(always)]
+    fn deserialize_map_key_value(&mut self) -> Result<NonNull<pyo3::ffi::PyObject>, Error> {
         self.recursion += 1;
-        if unlikely!(self.recursion == RECURSION_LIMIT) {
+        if unlikely!(u64::from(self.recursion) > self.limits.max_depth) {
             return Err(Error::RecursionLimitReached);
         }
 
@@ -664,11 +1779,22 @@ impl<'de> Deserializer<'de> {
                 let len = self.read_u32()?;
                 self.deserialize_map_array_key(len)
             }
-            Marker::FixExt4 => self.deserialize_map_ext_key(4),
-            Marker::FixExt8 => self.deserialize_map_ext_key(8),
+            Marker::FixExt1 => self.deserialize_ext(1, true),
+            Marker::FixExt2 => self.deserialize_ext(2, true),
+            Marker::FixExt4 => self.deserialize_ext(4, true),
+            Marker::FixExt8 => self.deserialize_ext(8, true),
+            Marker::FixExt16 => self.deserialize_ext(16, true),
             Marker::Ext8 => {
                 let len = self.read_u8()?;
-                self.deserialize_map_ext_key(len.into())
+                self.deserialize_ext(len.into(), true)
+            }
+            Marker::Ext16 => {
+                let len = self.read_u16()?;
+                self.deserialize_ext(len.into(), true)
+            }
+            Marker::Ext32 => {
+                let len = self.read_u32()?;
+                self.deserialize_ext(len, true)
             }
             marker => Err(Error::InvalidType(marker)),
         };
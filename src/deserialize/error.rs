@@ -1,15 +1,119 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use crate::state::State;
+use pyo3::ffi::*;
 use std::borrow::Cow;
+use std::ffi::CStr;
+
+/// The kind of a decode error, exposed as a member of the
+/// `MsgpackDecodeErrorKind` enum.
+#[derive(Debug, Clone, Copy)]
+pub enum ErrorKind {
+    ExtHookFailed,
+    ExtHookMissing,
+    Internal,
+    InvalidStr,
+    InvalidType,
+    InvalidValue,
+    LimitExceeded,
+    RecursionLimitReached,
+    TrailingData,
+    UnexpectedEof,
+}
+
+/// The member names of `MsgpackDecodeErrorKind`, in the order of `ErrorKind`.
+pub const ERROR_KIND_NAMES: &CStr = c"EXT_HOOK_FAILED EXT_HOOK_MISSING INTERNAL INVALID_STR INVALID_TYPE INVALID_VALUE LIMIT_EXCEEDED RECURSION_LIMIT_REACHED TRAILING_DATA UNEXPECTED_EOF";
+
+/// Create the `MsgpackDecodeErrorKind` enum with the functional API of
+/// `enum.Enum`.
+#[cold]
+pub unsafe fn create_decode_error_kind() -> *mut PyObject {
+    let module = PyImport_ImportModule(c"enum".as_ptr());
+    let enum_type = PyObject_GetAttrString(module, c"Enum".as_ptr());
+    Py_DECREF(module);
+    let args = PyTuple_New(2);
+    PyTuple_SET_ITEM(
+        args,
+        0,
+        PyUnicode_FromString(c"MsgpackDecodeErrorKind".as_ptr()),
+    );
+    PyTuple_SET_ITEM(args, 1, PyUnicode_FromString(ERROR_KIND_NAMES.as_ptr()));
+    let kwargs = PyDict_New();
+    let module_name = PyUnicode_FromString(c"ormsgpack".as_ptr());
+    PyDict_SetItemString(kwargs, c"module".as_ptr(), module_name);
+    Py_DECREF(module_name);
+    let kind = PyObject_Call(enum_type, args, kwargs);
+    Py_DECREF(kwargs);
+    Py_DECREF(args);
+    Py_DECREF(enum_type);
+    kind
+}
+
+impl ErrorKind {
+    pub fn name(self) -> &'static CStr {
+        match self {
+            ErrorKind::ExtHookFailed => c"EXT_HOOK_FAILED",
+            ErrorKind::ExtHookMissing => c"EXT_HOOK_MISSING",
+            ErrorKind::Internal => c"INTERNAL",
+            ErrorKind::InvalidStr => c"INVALID_STR",
+            ErrorKind::InvalidType => c"INVALID_TYPE",
+            ErrorKind::InvalidValue => c"INVALID_VALUE",
+            ErrorKind::LimitExceeded => c"LIMIT_EXCEEDED",
+            ErrorKind::RecursionLimitReached => c"RECURSION_LIMIT_REACHED",
+            ErrorKind::TrailingData => c"TRAILING_DATA",
+            ErrorKind::UnexpectedEof => c"UNEXPECTED_EOF",
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct DeserializeError<'a> {
     pub message: Cow<'a, str>,
+    pub kind: Option<ErrorKind>,
+    /// The offset of the item being decoded when the error occurred.
+    pub pos: Option<usize>,
+    /// The location of the item in the decoded object, such as `$.a[0]`.
+    pub path: Option<String>,
+    /// The first byte of the item, if the input does not end before it.
+    pub marker: Option<u8>,
 }
 
 impl<'a> DeserializeError<'a> {
     #[cold]
     pub fn new(message: Cow<'a, str>) -> Self {
-        DeserializeError { message }
+        DeserializeError {
+            message,
+            kind: None,
+            pos: None,
+            path: None,
+            marker: None,
+        }
+    }
+
+    /// Return the exception type to raise for the error.
+    #[cold]
+    pub fn exception(&self, state: *mut State) -> *mut PyObject {
+        unsafe {
+            match self.kind {
+                Some(ErrorKind::ExtHookFailed | ErrorKind::ExtHookMissing) => {
+                    (*state).MsgpackExtError
+                }
+                Some(ErrorKind::InvalidStr) => (*state).MsgpackInvalidUtf8Error,
+                Some(ErrorKind::LimitExceeded) => (*state).MsgpackLimitError,
+                Some(ErrorKind::RecursionLimitReached) => (*state).MsgpackDepthError,
+                Some(ErrorKind::UnexpectedEof) => (*state).MsgpackTruncatedError,
+                _ => (*state).MsgpackDecodeError,
+            }
+        }
+    }
+
+    /// Shift the position of the error by the offset of the decoded data in
+    /// a larger buffer or stream.
+    #[cold]
+    pub fn shift(mut self, offset: usize) -> Self {
+        if let Some(pos) = self.pos.as_mut() {
+            *pos += offset;
+        }
+        self
     }
 }
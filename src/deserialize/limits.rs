@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::msgpack::Marker;
+use crate::state::State;
+use pyo3::ffi::*;
+
+/// The maximum nesting depth, which bounds the stack usage of the
+/// deserializer.
+pub const MAX_DEPTH: u64 = 1023;
+
+/// Size accounted for each array element and map key or value in the
+/// allocation budget.
+pub const ITEM_SIZE: u64 = 8;
+
+#[derive(Clone, Copy)]
+pub struct Limits {
+    pub max_depth: u64,
+    pub max_str_len: u64,
+    pub max_bin_len: u64,
+    pub max_array_len: u64,
+    pub max_map_len: u64,
+    pub max_ext_len: u64,
+    pub max_alloc_size: u64,
+}
+
+impl Limits {
+    pub const fn new() -> Self {
+        Limits {
+            max_depth: MAX_DEPTH,
+            max_str_len: u64::MAX,
+            max_bin_len: u64::MAX,
+            max_array_len: u64::MAX,
+            max_map_len: u64::MAX,
+            max_ext_len: u64::MAX,
+            max_alloc_size: u64::MAX,
+        }
+    }
+
+    /// Set a limit from a keyword argument. Return `Ok(false)` if `key` is
+    /// not the name of a limit, and the name otherwise if `value` is invalid.
+    pub unsafe fn parse_arg(
+        &mut self,
+        state: *mut State,
+        key: *mut PyObject,
+        value: *mut PyObject,
+    ) -> Result<bool, &'static str> {
+        let (limit, name, max) = if PyUnicode_Compare(key, (*state).max_depth_str) == 0 {
+            (&mut self.max_depth, "max_depth", MAX_DEPTH)
+        } else if PyUnicode_Compare(key, (*state).max_str_len_str) == 0 {
+            (&mut self.max_str_len, "max_str_len", u64::MAX)
+        } else if PyUnicode_Compare(key, (*state).max_bin_len_str) == 0 {
+            (&mut self.max_bin_len, "max_bin_len", u64::MAX)
+        } else if PyUnicode_Compare(key, (*state).max_array_len_str) == 0 {
+            (&mut self.max_array_len, "max_array_len", u64::MAX)
+        } else if PyUnicode_Compare(key, (*state).max_map_len_str) == 0 {
+            (&mut self.max_map_len, "max_map_len", u64::MAX)
+        } else if PyUnicode_Compare(key, (*state).max_ext_len_str) == 0 {
+            (&mut self.max_ext_len, "max_ext_len", u64::MAX)
+        } else if PyUnicode_Compare(key, (*state).max_alloc_size_str) == 0 {
+            (&mut self.max_alloc_size, "max_alloc_size", u64::MAX)
+        } else {
+            return Ok(false);
+        };
+        if value == Py_None() {
+            *limit = max;
+            return Ok(true);
+        }
+        if Py_TYPE(value) != &raw mut PyLong_Type {
+            return Err(name);
+        }
+        let val = PyLong_AsUnsignedLongLong(value);
+        if val == u64::MAX && !PyErr_Occurred().is_null() {
+            PyErr_Clear();
+            return Err(name);
+        }
+        if val > max {
+            return Err(name);
+        }
+        *limit = val;
+        Ok(true)
+    }
+
+    /// Check the length of an item read by the framing scan of an unpacker,
+    /// so that a stream exceeding the limits is rejected before it is
+    /// buffered.
+    pub fn check_marker_len(&self, marker: Marker, len: u64) -> Result<(), &'static str> {
+        let (limit, name) = match marker {
+            Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+                (self.max_str_len, "max_str_len")
+            }
+            Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => (self.max_bin_len, "max_bin_len"),
+            Marker::FixArray(_) | Marker::Array16 | Marker::Array32 => {
+                (self.max_array_len, "max_array_len")
+            }
+            Marker::FixMap(_) | Marker::Map16 | Marker::Map32 => (self.max_map_len, "max_map_len"),
+            Marker::FixExt1
+            | Marker::FixExt2
+            | Marker::FixExt4
+            | Marker::FixExt8
+            | Marker::FixExt16
+            | Marker::Ext8
+            | Marker::Ext16
+            | Marker::Ext32 => (self.max_ext_len, "max_ext_len"),
+            _ => return Ok(()),
+        };
+        if len > limit {
+            Err(name)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -3,7 +3,16 @@
 mod cache;
 mod deserializer;
 mod error;
+mod limits;
+mod rfc3339;
+mod schema;
+mod unpacker;
 
 pub use cache::KeyMap;
-pub use deserializer::deserialize;
-pub use error::DeserializeError;
+pub use deserializer::{deserialize, deserialize_partial, input_as_bytes, Config};
+pub use error::{create_decode_error_kind, DeserializeError};
+pub use limits::Limits;
+pub use schema::{get_schema, is_pydantic_model_class, schema_from_capsule};
+pub use unpacker::{
+    create_unpacker_type, deserialize_file, parse_size_arg, unpacker_from_file, MAX_BUFFER_SIZE,
+};
@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+/// A date, time or datetime in one of the RFC 3339 formats written by
+/// `packb`.
+pub enum Rfc3339 {
+    Date(Date),
+    Time(Time),
+    /// A datetime with its UTC offset in seconds, if any.
+    DateTime(Date, Time, Option<i32>),
+}
+
+pub struct Date {
+    pub year: i32,
+    pub month: i32,
+    pub day: i32,
+}
+
+pub struct Time {
+    pub hour: i32,
+    pub minute: i32,
+    pub second: i32,
+    pub microsecond: i32,
+}
+
+#[inline(always)]
+fn parse_digits(data: &[u8]) -> Option<i32> {
+    let mut value = 0;
+    for &c in data {
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        value = value * 10 + i32::from(c - b'0');
+    }
+    Some(value)
+}
+
+/// Parse `YYYY-MM-DD`.
+fn parse_date(data: &[u8]) -> Option<Date> {
+    if data[4] != b'-' || data[7] != b'-' {
+        return None;
+    }
+    let date = Date {
+        year: parse_digits(&data[0..4])?,
+        month: parse_digits(&data[5..7])?,
+        day: parse_digits(&data[8..10])?,
+    };
+    if date.year == 0 {
+        return None;
+    }
+    chrono::NaiveDate::from_ymd_opt(date.year, date.month as u32, date.day as u32)?;
+    Some(date)
+}
+
+/// Parse `HH:MM:SS` or `HH:MM:SS.ffffff` and return the time with the length
+/// of its representation.
+fn parse_time(data: &[u8]) -> Option<(Time, usize)> {
+    if data.len() < 8 || data[2] != b':' || data[5] != b':' {
+        return None;
+    }
+    let mut time = Time {
+        hour: parse_digits(&data[0..2])?,
+        minute: parse_digits(&data[3..5])?,
+        second: parse_digits(&data[6..8])?,
+        microsecond: 0,
+    };
+    if time.hour > 23 || time.minute > 59 || time.second > 59 {
+        return None;
+    }
+    let mut len = 8;
+    if data.len() >= 15 && data[8] == b'.' {
+        time.microsecond = parse_digits(&data[9..15])?;
+        len = 15;
+    }
+    Some((time, len))
+}
+
+/// Parse `Z`, `+HH:MM` or `-HH:MM` as a number of seconds.
+fn parse_offset(data: &[u8]) -> Option<i32> {
+    if data == b"Z" {
+        return Some(0);
+    }
+    if data.len() != 6 || data[3] != b':' {
+        return None;
+    }
+    let sign = match data[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let hour = parse_digits(&data[1..3])?;
+    let minute = parse_digits(&data[4..6])?;
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+    Some(sign * (hour * 3600 + minute * 60))
+}
+
+/// Parse a string in one of the formats written by `packb` for dates, times
+/// and datetimes, or return `None` if it is not in any of them.
+pub fn parse(data: &[u8]) -> Option<Rfc3339> {
+    match data.len() {
+        10 => Some(Rfc3339::Date(parse_date(data)?)),
+        8 | 15 => match parse_time(data)? {
+            (time, len) if len == data.len() => Some(Rfc3339::Time(time)),
+            _ => None,
+        },
+        19..=32 => {
+            if data[10] != b'T' {
+                return None;
+            }
+            let date = parse_date(&data[..10])?;
+            let (time, len) = parse_time(&data[11..])?;
+            let offset = match &data[11 + len..] {
+                b"" => None,
+                rest => Some(parse_offset(rest)?),
+            };
+            Some(Rfc3339::DateTime(date, time, offset))
+        }
+        _ => None,
+    }
+}
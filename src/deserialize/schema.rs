@@ -0,0 +1,853 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::exc::describe_raised_exception;
+use crate::ffi::*;
+use crate::msgpack::Marker;
+use crate::state::State;
+use pyo3::ffi::*;
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+const SCHEMA_CAPSULE_NAME: &CStr = c"ormsgpack.Schema";
+const CLASS_SCHEMA_CAPSULE_NAME: &CStr = c"ormsgpack.ClassSchema";
+
+/// The maximum number of entries of the generic schema cache.
+const MAX_GENERIC_SCHEMAS: isize = 1024;
+
+/// The compiled form of a type annotation passed as `type` to `unpackb`.
+pub enum Schema {
+    Any,
+    None,
+    Bool,
+    Int,
+    Float,
+    Str,
+    Bytes,
+    List(Box<Schema>),
+    /// A `dict` with `str` keys and values of the given type.
+    Dict(Box<Schema>),
+    Union(Vec<Schema>),
+    Literal(Vec<*mut PyObject>),
+    /// A dataclass, `TypedDict`, `NamedTuple` or pydantic model, whose schema
+    /// is compiled separately and cached by class so that it may refer to
+    /// itself. The class is weakly referenced, so that the cached schemas do
+    /// not keep it alive.
+    Class(*mut PyObject, ClassKind),
+}
+
+#[derive(Clone, Copy)]
+pub enum ClassKind {
+    Dataclass,
+    TypedDict,
+    NamedTuple,
+    PydanticModel,
+}
+
+pub struct Field {
+    pub name: String,
+    pub key: *mut PyObject,
+    pub schema: Schema,
+    pub required: bool,
+}
+
+pub struct ClassSchema {
+    pub kind: ClassKind,
+    pub fields: Vec<Field>,
+    /// Whether the unknown keys of a map must be kept, for a pydantic model
+    /// whose validation may depend on them.
+    pub keep_unknown: bool,
+}
+
+impl Drop for Schema {
+    fn drop(&mut self) {
+        match self {
+            Schema::Literal(values) => {
+                for value in values.iter() {
+                    unsafe { Py_DECREF(*value) };
+                }
+            }
+            Schema::Class(cls, _) => unsafe { Py_DECREF(*cls) },
+            _ => {}
+        }
+    }
+}
+
+impl Drop for ClassSchema {
+    fn drop(&mut self) {
+        unsafe {
+            for field in self.fields.iter() {
+                Py_DECREF(field.key);
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn is_int_marker(marker: Marker) -> bool {
+    matches!(
+        marker,
+        Marker::FixPos(_)
+            | Marker::FixNeg(_)
+            | Marker::U8
+            | Marker::U16
+            | Marker::U32
+            | Marker::U64
+            | Marker::I8
+            | Marker::I16
+            | Marker::I32
+            | Marker::I64
+    )
+}
+
+#[inline(always)]
+fn is_str_marker(marker: Marker) -> bool {
+    matches!(
+        marker,
+        Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32
+    )
+}
+
+#[inline(always)]
+fn is_bin_marker(marker: Marker) -> bool {
+    matches!(marker, Marker::Bin8 | Marker::Bin16 | Marker::Bin32)
+}
+
+#[inline(always)]
+fn is_array_marker(marker: Marker) -> bool {
+    matches!(
+        marker,
+        Marker::FixArray(_) | Marker::Array16 | Marker::Array32
+    )
+}
+
+#[inline(always)]
+fn is_map_marker(marker: Marker) -> bool {
+    matches!(marker, Marker::FixMap(_) | Marker::Map16 | Marker::Map32)
+}
+
+impl Schema {
+    /// Return whether an object starting with `marker` may be of this type.
+    pub fn accepts(&self, marker: Marker) -> bool {
+        match self {
+            Schema::Any => true,
+            Schema::None => matches!(marker, Marker::Null),
+            Schema::Bool => matches!(marker, Marker::True | Marker::False),
+            Schema::Int => is_int_marker(marker),
+            Schema::Float => matches!(marker, Marker::F32 | Marker::F64) || is_int_marker(marker),
+            Schema::Str => is_str_marker(marker),
+            Schema::Bytes => is_bin_marker(marker),
+            Schema::List(_) => is_array_marker(marker),
+            Schema::Dict(_) => is_map_marker(marker),
+            Schema::Union(members) => members.iter().any(|member| member.accepts(marker)),
+            Schema::Literal(values) => values.iter().any(|&value| unsafe {
+                if value == Py_None() {
+                    matches!(marker, Marker::Null)
+                } else if PyBool_Check(value) != 0 {
+                    matches!(marker, Marker::True | Marker::False)
+                } else if PyLong_Check(value) != 0 {
+                    is_int_marker(marker)
+                } else if PyUnicode_Check(value) != 0 {
+                    is_str_marker(marker)
+                } else {
+                    is_bin_marker(marker)
+                }
+            }),
+            Schema::Class(_, ClassKind::NamedTuple) => {
+                is_array_marker(marker) || is_map_marker(marker)
+            }
+            Schema::Class(_, ClassKind::PydanticModel) => true,
+            Schema::Class(_, _) => is_map_marker(marker),
+        }
+    }
+
+    /// Describe the type for error messages.
+    #[cold]
+    pub fn describe(&self) -> String {
+        match self {
+            Schema::Any => String::from("Any"),
+            Schema::None => String::from("None"),
+            Schema::Bool => String::from("bool"),
+            Schema::Int => String::from("int"),
+            Schema::Float => String::from("float"),
+            Schema::Str => String::from("str"),
+            Schema::Bytes => String::from("bytes"),
+            Schema::List(item) => format!("list[{}]", item.describe()),
+            Schema::Dict(value) => format!("dict[str, {}]", value.describe()),
+            Schema::Union(members) => members
+                .iter()
+                .map(Schema::describe)
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Schema::Literal(values) => {
+                let values = values
+                    .iter()
+                    .map(|&value| repr(value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("Literal[{values}]")
+            }
+            Schema::Class(class_ref, _) => unsafe {
+                let cls = weakref_get(*class_ref);
+                if cls.is_null() {
+                    return String::from("?");
+                }
+                let name = type_name(cls);
+                Py_DECREF(cls);
+                name
+            },
+        }
+    }
+}
+
+/// Return a new reference to the class of a `Schema::Class`.
+pub fn get_class(class_ref: *mut PyObject) -> Result<*mut PyObject, String> {
+    let cls = unsafe { weakref_get(class_ref) };
+    if cls.is_null() {
+        return Err(String::from("The class of a type no longer exists"));
+    }
+    Ok(cls)
+}
+
+#[cold]
+fn repr(obj: *mut PyObject) -> String {
+    unsafe {
+        let repr = PyObject_Repr(obj);
+        if repr.is_null() {
+            PyErr_Clear();
+            return String::from("?");
+        }
+        let value = match unicode_to_str(repr) {
+            Ok(value) => value.to_string(),
+            Err(_) => String::from("?"),
+        };
+        Py_DECREF(repr);
+        value
+    }
+}
+
+/// Return whether `cls` is a pydantic model class. Pydantic dataclasses are
+/// excluded, as they are created by calling the class like other dataclasses.
+#[cold]
+pub fn is_pydantic_model_class(state: *mut State, cls: *mut PyObject) -> bool {
+    unsafe {
+        PyType_Check(cls) != 0
+            && PyObject_HasAttr(cls, (*state).pydantic_validator_str) == 1
+            && PyObject_HasAttr(cls, (*state).pydantic_fields_str) == 1
+            && PyObject_HasAttr(cls, (*state).dataclass_fields_str) == 0
+    }
+}
+
+#[cold]
+pub fn type_name(cls: *mut PyObject) -> String {
+    unsafe {
+        CStr::from_ptr((*cls.cast::<PyTypeObject>()).tp_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+unsafe extern "C" fn schema_capsule_destructor(capsule: *mut PyObject) {
+    let ptr = PyCapsule_GetPointer(capsule, SCHEMA_CAPSULE_NAME.as_ptr());
+    drop(Box::from_raw(ptr.cast::<Schema>()));
+}
+
+unsafe extern "C" fn class_schema_capsule_destructor(capsule: *mut PyObject) {
+    let ptr = PyCapsule_GetPointer(capsule, CLASS_SCHEMA_CAPSULE_NAME.as_ptr());
+    drop(Box::from_raw(ptr.cast::<ClassSchema>()));
+}
+
+/// Return the `Schema` stored in a capsule returned by `get_schema`.
+pub unsafe fn schema_from_capsule<'a>(capsule: *mut PyObject) -> &'a Schema {
+    &*PyCapsule_GetPointer(capsule, SCHEMA_CAPSULE_NAME.as_ptr()).cast::<Schema>()
+}
+
+/// Return the `ClassSchema` stored in a capsule returned by
+/// `get_class_schema`.
+pub unsafe fn class_schema_from_capsule<'a>(capsule: *mut PyObject) -> &'a ClassSchema {
+    &*PyCapsule_GetPointer(capsule, CLASS_SCHEMA_CAPSULE_NAME.as_ptr()).cast::<ClassSchema>()
+}
+
+/// Return a new reference to a capsule containing the schema of
+/// `annotation`, compiling it and caching it in `State` on first use.
+#[cold]
+pub fn get_schema(state: *mut State, annotation: *mut PyObject) -> Result<*mut PyObject, String> {
+    unsafe {
+        let is_type = PyType_Check(annotation) != 0;
+        let mut cached: *mut PyObject = null_mut();
+        if is_type {
+            cached = (*state).schema_cache.get(annotation);
+        } else if compat::PyDict_GetItemRef((*state).generic_schema_cache, annotation, &mut cached)
+            < 0
+        {
+            return Err(format!("Invalid type {}", repr(annotation)));
+        }
+        if !cached.is_null() {
+            return Ok(cached);
+        }
+        let mut compiler = Compiler::new(state);
+        let schema = compiler.compile(annotation);
+        compiler.release();
+        let capsule = PyCapsule_New(
+            Box::into_raw(Box::new(schema?)).cast(),
+            SCHEMA_CAPSULE_NAME.as_ptr(),
+            Some(schema_capsule_destructor),
+        );
+        if is_type {
+            (*state).schema_cache.set(annotation, capsule);
+        } else {
+            if PyDict_Size((*state).generic_schema_cache) >= MAX_GENERIC_SCHEMAS {
+                PyDict_Clear((*state).generic_schema_cache);
+            }
+            PyDict_SetItem((*state).generic_schema_cache, annotation, capsule);
+        }
+        Ok(capsule)
+    }
+}
+
+/// Return a new reference to a capsule containing the schema of `cls`,
+/// compiling it if it is not cached.
+#[cold]
+pub fn get_class_schema(
+    state: *mut State,
+    cls: *mut PyObject,
+    kind: ClassKind,
+) -> Result<*mut PyObject, String> {
+    unsafe {
+        let cached = (*state).class_schema_cache.get(cls);
+        if !cached.is_null() {
+            return Ok(cached);
+        }
+        let mut compiler = Compiler::new(state);
+        let res = compiler.compile_class(cls, kind);
+        compiler.release();
+        res?;
+        let cached = (*state).class_schema_cache.get(cls);
+        if cached.is_null() {
+            return Err(format!("Unsupported type {}", type_name(cls)));
+        }
+        Ok(cached)
+    }
+}
+
+struct Compiler {
+    state: *mut State,
+    typing: *mut PyObject,
+    types: *mut PyObject,
+    dataclasses: *mut PyObject,
+    /// The classes being compiled, which are referred to by their class
+    /// without compiling them again.
+    stack: Vec<*mut PyObject>,
+}
+
+impl Compiler {
+    #[cold]
+    unsafe fn new(state: *mut State) -> Self {
+        Compiler {
+            state: state,
+            typing: PyImport_ImportModule(c"typing".as_ptr()),
+            types: PyImport_ImportModule(c"types".as_ptr()),
+            dataclasses: PyImport_ImportModule(c"dataclasses".as_ptr()),
+            stack: Vec::new(),
+        }
+    }
+
+    #[cold]
+    unsafe fn release(&mut self) {
+        Py_XDECREF(self.typing);
+        Py_XDECREF(self.types);
+        Py_XDECREF(self.dataclasses);
+    }
+
+    /// Call the function `name` of `module` with one argument.
+    #[cold]
+    unsafe fn call(&self, module: *mut PyObject, name: &CStr, arg: *mut PyObject) -> *mut PyObject {
+        let func = PyObject_GetAttrString(module, name.as_ptr());
+        if func.is_null() {
+            return null_mut();
+        }
+        let res = pyobject_call_one_arg(func, arg);
+        Py_DECREF(func);
+        res
+    }
+
+    /// Return whether `obj` is the attribute `name` of `module`.
+    #[cold]
+    unsafe fn is_attr(&self, obj: *mut PyObject, module: *mut PyObject, name: &CStr) -> bool {
+        let attr = PyObject_GetAttrString(module, name.as_ptr());
+        if attr.is_null() {
+            PyErr_Clear();
+            return false;
+        }
+        Py_DECREF(attr);
+        obj == attr
+    }
+
+    #[cold]
+    unsafe fn is_true(&self, obj: *mut PyObject) -> bool {
+        if obj.is_null() {
+            PyErr_Clear();
+            return false;
+        }
+        let res = PyObject_IsTrue(obj) == 1;
+        Py_DECREF(obj);
+        res
+    }
+
+    #[cold]
+    unsafe fn compile(&mut self, annotation: *mut PyObject) -> Result<Schema, String> {
+        if self.typing.is_null() || self.types.is_null() || self.dataclasses.is_null() {
+            return Err(String::from("Failed to import the typing modules"));
+        }
+        if annotation == Py_None() || annotation == Py_TYPE(Py_None()).cast::<PyObject>() {
+            return Ok(Schema::None);
+        }
+        if annotation == (&raw mut PyBaseObject_Type).cast::<PyObject>()
+            || self.is_attr(annotation, self.typing, c"Any")
+        {
+            return Ok(Schema::Any);
+        }
+        if annotation == (&raw mut PyBool_Type).cast::<PyObject>() {
+            return Ok(Schema::Bool);
+        }
+        if annotation == (&raw mut PyLong_Type).cast::<PyObject>() {
+            return Ok(Schema::Int);
+        }
+        if annotation == (&raw mut PyFloat_Type).cast::<PyObject>() {
+            return Ok(Schema::Float);
+        }
+        if annotation == (&raw mut PyUnicode_Type).cast::<PyObject>() {
+            return Ok(Schema::Str);
+        }
+        if annotation == (&raw mut PyBytes_Type).cast::<PyObject>() {
+            return Ok(Schema::Bytes);
+        }
+        if annotation == (&raw mut PyList_Type).cast::<PyObject>() {
+            return Ok(Schema::List(Box::new(Schema::Any)));
+        }
+        if annotation == (&raw mut PyDict_Type).cast::<PyObject>() {
+            return Ok(Schema::Dict(Box::new(Schema::Any)));
+        }
+
+        let origin = self.call(self.typing, c"get_origin", annotation);
+        if origin.is_null() {
+            return Err(format!("Invalid type {}", repr(annotation)));
+        }
+        if origin != Py_None() {
+            let res = self.compile_generic(annotation, origin);
+            Py_DECREF(origin);
+            return res;
+        }
+        Py_DECREF(origin);
+
+        if PyType_Check(annotation) != 0 {
+            let kind = if is_pydantic_model_class(self.state, annotation) {
+                Some(ClassKind::PydanticModel)
+            } else if self.is_true(self.call(self.dataclasses, c"is_dataclass", annotation)) {
+                Some(ClassKind::Dataclass)
+            } else if self.is_true(self.call(self.typing, c"is_typeddict", annotation)) {
+                Some(ClassKind::TypedDict)
+            } else if PyType_IsSubtype(annotation.cast::<PyTypeObject>(), &raw mut PyTuple_Type)
+                != 0
+                && PyObject_HasAttrString(annotation, c"_fields".as_ptr()) == 1
+            {
+                Some(ClassKind::NamedTuple)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                self.compile_class(annotation, kind)?;
+                let class_ref = PyWeakref_NewRef(annotation, null_mut());
+                if class_ref.is_null() {
+                    return Err(describe_raised_exception());
+                }
+                return Ok(Schema::Class(class_ref, kind));
+            }
+        }
+        Err(format!("Unsupported type {}", repr(annotation)))
+    }
+
+    #[cold]
+    unsafe fn compile_generic(
+        &mut self,
+        annotation: *mut PyObject,
+        origin: *mut PyObject,
+    ) -> Result<Schema, String> {
+        let args = self.call(self.typing, c"get_args", annotation);
+        if args.is_null() {
+            return Err(format!("Invalid type {}", repr(annotation)));
+        }
+        let res = self.compile_args(annotation, origin, args);
+        Py_DECREF(args);
+        res
+    }
+
+    #[cold]
+    unsafe fn compile_args(
+        &mut self,
+        annotation: *mut PyObject,
+        origin: *mut PyObject,
+        args: *mut PyObject,
+    ) -> Result<Schema, String> {
+        let nargs = PyTuple_GET_SIZE(args);
+        if origin == (&raw mut PyList_Type).cast::<PyObject>() {
+            let item = if nargs == 0 {
+                Schema::Any
+            } else {
+                self.compile(PyTuple_GET_ITEM(args, 0))?
+            };
+            return Ok(Schema::List(Box::new(item)));
+        }
+        if origin == (&raw mut PyDict_Type).cast::<PyObject>() {
+            if nargs == 0 {
+                return Ok(Schema::Dict(Box::new(Schema::Any)));
+            }
+            if PyTuple_GET_ITEM(args, 0) != (&raw mut PyUnicode_Type).cast::<PyObject>() {
+                return Err(format!(
+                    "Unsupported type {}: dict keys must be str",
+                    repr(annotation)
+                ));
+            }
+            let value = self.compile(PyTuple_GET_ITEM(args, 1))?;
+            return Ok(Schema::Dict(Box::new(value)));
+        }
+        if self.is_attr(origin, self.typing, c"Union")
+            || self.is_attr(origin, self.types, c"UnionType")
+        {
+            let mut members = Vec::with_capacity(nargs as usize);
+            for i in 0..nargs {
+                let mut member = self.compile(PyTuple_GET_ITEM(args, i))?;
+                match member {
+                    Schema::Union(ref mut nested) => members.append(nested),
+                    _ => members.push(member),
+                }
+            }
+            return Ok(Schema::Union(members));
+        }
+        if self.is_attr(origin, self.typing, c"Literal") {
+            let mut values = Vec::with_capacity(nargs as usize);
+            for i in 0..nargs {
+                let value = PyTuple_GET_ITEM(args, i);
+                if value != Py_None()
+                    && PyLong_Check(value) == 0
+                    && PyUnicode_Check(value) == 0
+                    && PyBytes_Check(value) == 0
+                {
+                    return Err(format!(
+                        "Unsupported type {}: Literal values must be None, bool, int, str or bytes",
+                        repr(annotation)
+                    ));
+                }
+                values.push(Py_NewRef(value));
+            }
+            return Ok(Schema::Literal(values));
+        }
+        Err(format!("Unsupported type {}", repr(annotation)))
+    }
+
+    /// Compile the schema of `cls` and store it in the class schema cache,
+    /// unless it is already cached or being compiled.
+    #[cold]
+    unsafe fn compile_class(&mut self, cls: *mut PyObject, kind: ClassKind) -> Result<(), String> {
+        if self.stack.contains(&cls) {
+            return Ok(());
+        }
+        let cached = (*self.state).class_schema_cache.get(cls);
+        if !cached.is_null() {
+            Py_DECREF(cached);
+            return Ok(());
+        }
+        if self.typing.is_null() || self.dataclasses.is_null() {
+            return Err(String::from("Failed to import the typing modules"));
+        }
+        self.stack.push(cls);
+        let res = if matches!(kind, ClassKind::PydanticModel) {
+            self.compile_pydantic_fields(cls)
+        } else {
+            self.compile_fields(cls, kind).map(|fields| (fields, false))
+        };
+        self.stack.pop();
+        let (fields, keep_unknown) = res?;
+        let schema = ClassSchema {
+            kind: kind,
+            fields: fields,
+            keep_unknown: keep_unknown,
+        };
+        let capsule = PyCapsule_New(
+            Box::into_raw(Box::new(schema)).cast(),
+            CLASS_SCHEMA_CAPSULE_NAME.as_ptr(),
+            Some(class_schema_capsule_destructor),
+        );
+        (*self.state).class_schema_cache.set(cls, capsule);
+        Py_DECREF(capsule);
+        Ok(())
+    }
+
+    #[cold]
+    unsafe fn compile_fields(
+        &mut self,
+        cls: *mut PyObject,
+        kind: ClassKind,
+    ) -> Result<Vec<Field>, String> {
+        let hints = self.call(self.typing, c"get_type_hints", cls);
+        if hints.is_null() {
+            return Err(format!(
+                "Failed to get the type hints of {}: {}",
+                type_name(cls),
+                describe_raised_exception()
+            ));
+        }
+        let res = match kind {
+            ClassKind::Dataclass => self.compile_dataclass_fields(cls, hints),
+            ClassKind::TypedDict => self.compile_typeddict_fields(cls, hints),
+            ClassKind::NamedTuple => self.compile_namedtuple_fields(cls, hints),
+            ClassKind::PydanticModel => unreachable!(),
+        };
+        Py_DECREF(hints);
+        res
+    }
+
+    #[cold]
+    unsafe fn new_field(
+        &mut self,
+        name: *mut PyObject,
+        annotation: *mut PyObject,
+        required: bool,
+    ) -> Result<Field, String> {
+        let schema = if annotation.is_null() {
+            Schema::Any
+        } else {
+            self.compile(annotation)?
+        };
+        let name_str = match unicode_to_str(name) {
+            Ok(value) => value.to_string(),
+            Err(_) => return Err(String::from("Invalid field name")),
+        };
+        Ok(Field {
+            name: name_str,
+            key: Py_NewRef(name),
+            schema: schema,
+            required: required,
+        })
+    }
+
+    #[cold]
+    unsafe fn compile_dataclass_fields(
+        &mut self,
+        cls: *mut PyObject,
+        hints: *mut PyObject,
+    ) -> Result<Vec<Field>, String> {
+        let fields = self.call(self.dataclasses, c"fields", cls);
+        if fields.is_null() {
+            return Err(describe_raised_exception());
+        }
+        let missing = PyObject_GetAttrString(self.dataclasses, c"MISSING".as_ptr());
+        let mut res = Vec::new();
+        for i in 0..PyTuple_GET_SIZE(fields) {
+            let field = PyTuple_GET_ITEM(fields, i);
+            let init = PyObject_GetAttrString(field, c"init".as_ptr());
+            if !self.is_true(init) {
+                continue;
+            }
+            let name = PyObject_GetAttrString(field, c"name".as_ptr());
+            let default = PyObject_GetAttrString(field, c"default".as_ptr());
+            let default_factory = PyObject_GetAttrString(field, c"default_factory".as_ptr());
+            let required = default == missing && default_factory == missing;
+            Py_XDECREF(default);
+            Py_XDECREF(default_factory);
+            let field = self.new_field(name, PyDict_GetItem(hints, name), required);
+            Py_DECREF(name);
+            match field {
+                Ok(field) => res.push(field),
+                Err(err) => {
+                    Py_XDECREF(missing);
+                    Py_DECREF(fields);
+                    return Err(err);
+                }
+            }
+        }
+        Py_XDECREF(missing);
+        Py_DECREF(fields);
+        Ok(res)
+    }
+
+    #[cold]
+    unsafe fn compile_typeddict_fields(
+        &mut self,
+        cls: *mut PyObject,
+        hints: *mut PyObject,
+    ) -> Result<Vec<Field>, String> {
+        let required_keys = PyObject_GetAttrString(cls, c"__required_keys__".as_ptr());
+        if required_keys.is_null() {
+            return Err(describe_raised_exception());
+        }
+        let mut res = Vec::new();
+        for (name, annotation) in PyDictIter::from_pyobject(hints) {
+            let required = PySequence_Contains(required_keys, name.as_ptr()) == 1;
+            match self.new_field(name.as_ptr(), annotation.as_ptr(), required) {
+                Ok(field) => res.push(field),
+                Err(err) => {
+                    Py_DECREF(required_keys);
+                    return Err(err);
+                }
+            }
+        }
+        Py_DECREF(required_keys);
+        Ok(res)
+    }
+
+    #[cold]
+    unsafe fn compile_namedtuple_fields(
+        &mut self,
+        cls: *mut PyObject,
+        hints: *mut PyObject,
+    ) -> Result<Vec<Field>, String> {
+        let fields = PyObject_GetAttrString(cls, c"_fields".as_ptr());
+        let defaults = PyObject_GetAttrString(cls, c"_field_defaults".as_ptr());
+        if fields.is_null() || defaults.is_null() {
+            Py_XDECREF(fields);
+            Py_XDECREF(defaults);
+            return Err(describe_raised_exception());
+        }
+        let mut res = Vec::new();
+        for i in 0..PyTuple_GET_SIZE(fields) {
+            let name = PyTuple_GET_ITEM(fields, i);
+            let required = PySequence_Contains(defaults, name) != 1;
+            match self.new_field(name, PyDict_GetItem(hints, name), required) {
+                Ok(field) => res.push(field),
+                Err(err) => {
+                    Py_DECREF(fields);
+                    Py_DECREF(defaults);
+                    return Err(err);
+                }
+            }
+        }
+        Py_DECREF(fields);
+        Py_DECREF(defaults);
+        Ok(res)
+    }
+
+    /// Compile the keys of the fields of a pydantic model, which are the
+    /// names and aliases that its validator may look up. The values are
+    /// validated by the model, so they are deserialized as any object. The
+    /// unknown keys must be kept if the model does not ignore them, or if a
+    /// model validator may use them.
+    #[cold]
+    unsafe fn compile_pydantic_fields(
+        &mut self,
+        cls: *mut PyObject,
+    ) -> Result<(Vec<Field>, bool), String> {
+        let fields = PyObject_GetAttr(cls, (*self.state).pydantic_fields_str);
+        let config = PyObject_GetAttrString(cls, c"model_config".as_ptr());
+        let decorators = PyObject_GetAttrString(cls, c"__pydantic_decorators__".as_ptr());
+        let validators = if decorators.is_null() {
+            null_mut()
+        } else {
+            PyObject_GetAttrString(decorators, c"model_validators".as_ptr())
+        };
+        if fields.is_null() || config.is_null() || validators.is_null() {
+            Py_XDECREF(fields);
+            Py_XDECREF(config);
+            Py_XDECREF(decorators);
+            return Err(format!(
+                "Unsupported type {}: {}",
+                type_name(cls),
+                describe_raised_exception()
+            ));
+        }
+        let extra = PyDict_GetItemString(config, c"extra".as_ptr());
+        let mut keep_unknown = !(extra.is_null()
+            || extra == Py_None()
+            || PyUnicode_Check(extra) != 0
+                && PyUnicode_CompareWithASCIIString(extra, c"ignore".as_ptr()) == 0)
+            || PyObject_Size(validators) != 0
+            || self.is_true(PyObject_GetAttrString(
+                cls,
+                c"__pydantic_root_model__".as_ptr(),
+            ));
+        let mut keys: Vec<*mut PyObject> = Vec::new();
+        for (name, info) in PyDictIter::from_pyobject(fields) {
+            keys.push(Py_NewRef(name.as_ptr()));
+            let alias = PyObject_GetAttrString(info.as_ptr(), c"alias".as_ptr());
+            let validation_alias =
+                PyObject_GetAttrString(info.as_ptr(), c"validation_alias".as_ptr());
+            if !self.push_alias_keys(alias, &mut keys)
+                || !self.push_alias_keys(validation_alias, &mut keys)
+            {
+                keep_unknown = true;
+            }
+            Py_XDECREF(alias);
+            Py_XDECREF(validation_alias);
+        }
+        PyErr_Clear();
+        Py_DECREF(fields);
+        Py_DECREF(config);
+        Py_DECREF(decorators);
+        Py_DECREF(validators);
+
+        let mut res: Vec<Field> = Vec::with_capacity(keys.len());
+        for key in keys {
+            let name = match unicode_to_str(key) {
+                Ok(value) => value.to_string(),
+                Err(_) => {
+                    PyErr_Clear();
+                    Py_DECREF(key);
+                    keep_unknown = true;
+                    continue;
+                }
+            };
+            if res.iter().any(|field| field.name == name) {
+                Py_DECREF(key);
+                continue;
+            }
+            res.push(Field {
+                name: name,
+                key: key,
+                schema: Schema::Any,
+                required: false,
+            });
+        }
+        Ok((res, keep_unknown))
+    }
+
+    /// Push the top level keys of a pydantic alias, which is a `str`, an
+    /// `AliasPath` or an `AliasChoices`, and return whether it is supported.
+    #[cold]
+    unsafe fn push_alias_keys(&self, alias: *mut PyObject, keys: &mut Vec<*mut PyObject>) -> bool {
+        if alias.is_null() || alias == Py_None() {
+            return true;
+        }
+        if PyUnicode_Check(alias) != 0 {
+            keys.push(Py_NewRef(alias));
+            return true;
+        }
+        let path = PyObject_GetAttrString(alias, c"path".as_ptr());
+        if !path.is_null() {
+            let first = PySequence_GetItem(path, 0);
+            Py_DECREF(path);
+            if first.is_null() {
+                PyErr_Clear();
+                return false;
+            }
+            if PyUnicode_Check(first) == 0 {
+                Py_DECREF(first);
+                return false;
+            }
+            keys.push(first);
+            return true;
+        }
+        PyErr_Clear();
+        let choices = PyObject_GetAttrString(alias, c"choices".as_ptr());
+        if choices.is_null() {
+            PyErr_Clear();
+            return false;
+        }
+        let mut res = true;
+        for i in 0..PyObject_Size(choices).max(0) {
+            let choice = PySequence_GetItem(choices, i);
+            res &= !choice.is_null() && self.push_alias_keys(choice, keys);
+            Py_XDECREF(choice);
+        }
+        Py_DECREF(choices);
+        res
+    }
+}
@@ -0,0 +1,619 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::deserialize::deserializer::{deserialize_bytes, input_as_bytes, Config};
+use crate::deserialize::error::ErrorKind;
+use crate::deserialize::{DeserializeError, Limits};
+use crate::exc::*;
+use crate::ffi::*;
+use crate::msgpack::Marker;
+use crate::opt::*;
+use crate::state::State;
+use pyo3::ffi::*;
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_uint, c_void};
+use std::ptr::{null_mut, NonNull};
+
+const UNPACKER_DOC: &CStr = c"Unpacker(*, ext_hook=None, ext_registry=None, option=None, max_depth=1023, max_str_len=None, max_bin_len=None, max_array_len=None, max_map_len=None, max_ext_len=None, max_alloc_size=None, max_buffer_size=104857600)\n--\n\nDeserialize a stream of concatenated MessagePack objects.";
+const FEED_DOC: &CStr = c"feed(data, /)\n--\n\nAppend data to the internal buffer.";
+
+/// Size of the reads from file objects.
+const READ_LENGTH: isize = 65536;
+
+/// The default maximum size of the data buffered by an unpacker.
+pub const MAX_BUFFER_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Read a chunk from a file object through its `read` method and append it
+/// to `buffer`. Return whether the end of the file is reached. An `Err(None)`
+/// result means that a Python exception raised by the file object is set.
+pub fn read_chunk(
+    read: *mut PyObject,
+    buffer: &mut Vec<u8>,
+) -> Result<bool, Option<DeserializeError<'static>>> {
+    unsafe {
+        let size = PyLong_FromSsize_t(READ_LENGTH);
+        let chunk = pyobject_call_one_arg(read, size);
+        Py_DECREF(size);
+        if chunk.is_null() {
+            return Err(None);
+        }
+        let res = match input_as_bytes(chunk) {
+            Ok(contents) => {
+                buffer.extend_from_slice(contents);
+                Ok(contents.is_empty())
+            }
+            Err(err) => Err(Some(err)),
+        };
+        Py_DECREF(chunk);
+        res
+    }
+}
+
+#[inline]
+fn read_len(data: &[u8], pos: usize, width: usize) -> Option<u64> {
+    let bytes = data.get(pos..pos + width)?;
+    Some(bytes.iter().fold(0, |acc, &b| (acc << 8) | u64::from(b)))
+}
+
+/// Return the marker of the item at `pos`, its length as declared in its
+/// header, its encoded length and the number of items it contains, or `None`
+/// if `data` ends before the header does.
+fn item_len(data: &[u8], pos: usize) -> Option<(Marker, u64, u64, u64)> {
+    let marker = Marker::from_u8(*data.get(pos)?);
+    let pos = pos + 1;
+    let (header, payload, items) = match marker {
+        Marker::U8 | Marker::I8 => (0, 1, 0),
+        Marker::U16 | Marker::I16 => (0, 2, 0),
+        Marker::U32 | Marker::I32 | Marker::F32 => (0, 4, 0),
+        Marker::U64 | Marker::I64 | Marker::F64 => (0, 8, 0),
+        Marker::FixStr(len) => (0, len.into(), 0),
+        Marker::Str8 | Marker::Bin8 => (1, read_len(data, pos, 1)?, 0),
+        Marker::Str16 | Marker::Bin16 => (2, read_len(data, pos, 2)?, 0),
+        Marker::Str32 | Marker::Bin32 => (4, read_len(data, pos, 4)?, 0),
+        Marker::FixArray(len) => (0, 0, len.into()),
+        Marker::Array16 => (2, 0, read_len(data, pos, 2)?),
+        Marker::Array32 => (4, 0, read_len(data, pos, 4)?),
+        Marker::FixMap(len) => (0, 0, 2 * u64::from(len)),
+        Marker::Map16 => (2, 0, 2 * read_len(data, pos, 2)?),
+        Marker::Map32 => (4, 0, 2 * read_len(data, pos, 4)?),
+        Marker::FixExt1 => (0, 2, 0),
+        Marker::FixExt2 => (0, 3, 0),
+        Marker::FixExt4 => (0, 5, 0),
+        Marker::FixExt8 => (0, 9, 0),
+        Marker::FixExt16 => (0, 17, 0),
+        Marker::Ext8 => (1, read_len(data, pos, 1)? + 1, 0),
+        Marker::Ext16 => (2, read_len(data, pos, 2)? + 1, 0),
+        Marker::Ext32 => (4, read_len(data, pos, 4)? + 1, 0),
+        _ => (0, 0, 0),
+    };
+    let declared = match marker {
+        Marker::FixArray(_) | Marker::Array16 | Marker::Array32 => items,
+        Marker::FixMap(_) | Marker::Map16 | Marker::Map32 => items / 2,
+        Marker::FixExt1
+        | Marker::FixExt2
+        | Marker::FixExt4
+        | Marker::FixExt8
+        | Marker::FixExt16
+        | Marker::Ext8
+        | Marker::Ext16
+        | Marker::Ext32 => payload - 1,
+        _ => payload,
+    };
+    Some((marker, declared, 1 + header + payload, items))
+}
+
+/// Incremental framing of a MessagePack object. `pos` is the offset of the
+/// next item to scan and `pending` is the number of items left to complete
+/// the object. Scanning never decodes values, so it can be resumed cheaply
+/// whenever more data is available.
+#[derive(Clone, Copy)]
+struct Framing {
+    pos: usize,
+    pending: u64,
+}
+
+impl Framing {
+    fn new() -> Self {
+        Framing { pos: 0, pending: 1 }
+    }
+
+    /// Scan `data` and return whether it contains a complete object, whose
+    /// length is then `self.pos`, or the name of the exceeded limit.
+    fn advance(&mut self, data: &[u8], limits: &Limits) -> Result<bool, &'static str> {
+        while self.pending > 0 {
+            let (marker, declared, len, items) = match item_len(data, self.pos) {
+                Some(value) => value,
+                None => return Ok(false),
+            };
+            limits.check_marker_len(marker, declared)?;
+            let end = (self.pos as u64).saturating_add(len);
+            if end > data.len() as u64 {
+                return Ok(false);
+            }
+            self.pos = end as usize;
+            self.pending = self.pending - 1 + items;
+        }
+        Ok(true)
+    }
+}
+
+#[repr(C)]
+pub struct Unpacker {
+    ob_base: PyObject,
+    state: *mut State,
+    ext_hook: Option<NonNull<PyObject>>,
+    ext_registry: Option<NonNull<PyObject>>,
+    read: Option<NonNull<PyObject>>,
+    opts: Opt,
+    limits: Limits,
+    /// The maximum length of the data left in the buffer.
+    max_buffer_size: u64,
+    buffer: Vec<u8>,
+    start: usize,
+    /// The position in the stream of the start of the buffer.
+    offset: usize,
+    framing: Framing,
+    busy: bool,
+}
+
+#[cold]
+#[inline(never)]
+fn raise_busy(state: *mut State) -> *mut PyObject {
+    raise_unpackb_exception(state, "Unpacker is already in use")
+}
+
+#[cold]
+#[inline(never)]
+fn buffer_size_error(pos: usize) -> DeserializeError<'static> {
+    DeserializeError {
+        message: Cow::Borrowed("max_buffer_size exceeded"),
+        kind: Some(ErrorKind::LimitExceeded),
+        pos: Some(pos),
+        path: None,
+        marker: None,
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn unpacker_new(
+    subtype: *mut PyTypeObject,
+    args: *mut PyObject,
+    kwds: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut State = PyType_GetModuleState(subtype).cast();
+    if Py_SIZE(args) != 0 {
+        return raise_unpackb_exception(state, "Unpacker() takes no positional arguments");
+    }
+    let mut config = Config::default();
+    let mut optsbits: Opt = 0;
+    let mut limits = Limits::new();
+    let mut max_buffer_size = MAX_BUFFER_SIZE;
+    if !kwds.is_null() {
+        for (key, value) in PyDictIter::from_pyobject(kwds) {
+            if PyUnicode_Compare(key.as_ptr(), (*state).ext_hook_str) == 0 {
+                if value.as_ptr() != Py_None() {
+                    config.hook = Some(value);
+                }
+            } else if PyUnicode_Compare(key.as_ptr(), (*state).ext_registry_str) == 0 {
+                if value.as_ptr() != Py_None() {
+                    if unlikely!(ob_type!(value.as_ptr()) != (*state).ext_registry_type) {
+                        return raise_type_error("ext_registry must be an ExtRegistry");
+                    }
+                    config.registry = Some(value);
+                }
+            } else if PyUnicode_Compare(key.as_ptr(), (*state).max_buffer_size_str) == 0 {
+                match parse_size_arg(value.as_ptr()) {
+                    Some(val) => max_buffer_size = val,
+                    None => return raise_unpackb_exception(state, "Invalid max_buffer_size"),
+                }
+            } else if PyUnicode_Compare(key.as_ptr(), (*state).option_str) == 0 {
+                match parse_option_arg(value.as_ptr(), UNPACKB_PARTIAL_OPT_MASK) {
+                    Ok(val) => optsbits = val,
+                    Err(()) => return raise_unpackb_exception(state, "Invalid opts"),
+                }
+            } else {
+                match limits.parse_arg(state, key.as_ptr(), value.as_ptr()) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        return raise_unpackb_exception(
+                            state,
+                            "Unpacker() got an unexpected keyword argument",
+                        )
+                    }
+                    Err(name) => return raise_unpackb_exception(state, &format!("Invalid {name}")),
+                }
+            }
+        }
+    }
+
+    new_unpacker(
+        subtype,
+        state,
+        config,
+        None,
+        optsbits,
+        limits,
+        max_buffer_size,
+    )
+}
+
+/// Parse a size, which is a non-negative `int`, or `None` for no limit.
+pub unsafe fn parse_size_arg(value: *mut PyObject) -> Option<u64> {
+    if value == Py_None() {
+        return Some(u64::MAX);
+    }
+    if Py_TYPE(value) != &raw mut PyLong_Type {
+        return None;
+    }
+    let val = PyLong_AsUnsignedLongLong(value);
+    if val == u64::MAX && !PyErr_Occurred().is_null() {
+        PyErr_Clear();
+        return None;
+    }
+    Some(val)
+}
+
+/// Create an unpacker, which reads from a file object if `read` is set.
+/// The objects of `config` and `read` are borrowed references.
+unsafe fn new_unpacker(
+    subtype: *mut PyTypeObject,
+    state: *mut State,
+    config: Config,
+    read: Option<NonNull<PyObject>>,
+    opts: Opt,
+    limits: Limits,
+    max_buffer_size: u64,
+) -> *mut PyObject {
+    let obj = (*subtype).tp_alloc.unwrap()(subtype, 0);
+    if unlikely!(obj.is_null()) {
+        return null_mut();
+    }
+    let unpacker = obj.cast::<Unpacker>();
+    if let Some(callable) = config.hook {
+        Py_INCREF(callable.as_ptr());
+    }
+    if let Some(registry) = config.registry {
+        Py_INCREF(registry.as_ptr());
+    }
+    if let Some(callable) = read {
+        Py_INCREF(callable.as_ptr());
+    }
+    (*unpacker).state = state;
+    (*unpacker).ext_hook = config.hook;
+    (*unpacker).ext_registry = config.registry;
+    (*unpacker).read = read;
+    (*unpacker).opts = opts;
+    (*unpacker).limits = limits;
+    (*unpacker).max_buffer_size = max_buffer_size;
+    std::ptr::write(&raw mut (*unpacker).buffer, Vec::new());
+    (*unpacker).start = 0;
+    (*unpacker).offset = 0;
+    (*unpacker).framing = Framing::new();
+    (*unpacker).busy = false;
+    obj
+}
+
+#[no_mangle]
+unsafe extern "C" fn unpacker_dealloc(op: *mut PyObject) {
+    PyObject_GC_UnTrack(op.cast::<c_void>());
+    unpacker_clear(op);
+    let unpacker = op.cast::<Unpacker>();
+    std::ptr::drop_in_place(&raw mut (*unpacker).buffer);
+    let tp = ob_type!(op);
+    (*tp).tp_free.unwrap()(op.cast::<c_void>());
+    Py_DECREF(tp.cast::<PyObject>());
+}
+
+#[no_mangle]
+unsafe extern "C" fn unpacker_traverse(
+    op: *mut PyObject,
+    visit: visitproc,
+    arg: *mut c_void,
+) -> c_int {
+    let unpacker = op.cast::<Unpacker>();
+    let ret = visit_optional((*unpacker).ext_hook, visit, arg);
+    if ret != 0 {
+        return ret;
+    }
+    let ret = visit_optional((*unpacker).ext_registry, visit, arg);
+    if ret != 0 {
+        return ret;
+    }
+    let ret = visit_optional((*unpacker).read, visit, arg);
+    if ret != 0 {
+        return ret;
+    }
+    visit(ob_type!(op).cast::<PyObject>(), arg)
+}
+
+#[no_mangle]
+unsafe extern "C" fn unpacker_clear(op: *mut PyObject) -> c_int {
+    let unpacker = op.cast::<Unpacker>();
+    clear_optional(&mut (*unpacker).ext_hook);
+    clear_optional(&mut (*unpacker).ext_registry);
+    clear_optional(&mut (*unpacker).read);
+    0
+}
+
+#[no_mangle]
+unsafe extern "C" fn unpacker_feed(op: *mut PyObject, data: *mut PyObject) -> *mut PyObject {
+    let unpacker = op.cast::<Unpacker>();
+    let contents = match input_as_bytes(data) {
+        Ok(val) => val,
+        Err(err) => return raise_deserialize_error((*unpacker).state, &err),
+    };
+    let mut critical_section = CriticalSection::new();
+    critical_section.begin(op);
+    if unlikely!((*unpacker).busy) {
+        return raise_busy((*unpacker).state);
+    }
+    let buffer = &mut (*unpacker).buffer;
+    if (*unpacker).start > 0 {
+        buffer.drain(..(*unpacker).start);
+        (*unpacker).offset += (*unpacker).start;
+        (*unpacker).start = 0;
+    }
+    if unlikely!((buffer.len() + contents.len()) as u64 > (*unpacker).max_buffer_size) {
+        // The data is not buffered, so that it can be fed again once the
+        // buffered objects are consumed.
+        let pos = (*unpacker).offset + buffer.len();
+        return raise_deserialize_error((*unpacker).state, &buffer_size_error(pos));
+    }
+    buffer.extend_from_slice(contents);
+    Py_INCREF(Py_None());
+    Py_None()
+}
+
+#[no_mangle]
+unsafe extern "C" fn unpacker_next(op: *mut PyObject) -> *mut PyObject {
+    let unpacker = op.cast::<Unpacker>();
+    let mut critical_section = CriticalSection::new();
+    critical_section.begin(op);
+    if unlikely!((*unpacker).busy) {
+        return raise_busy((*unpacker).state);
+    }
+    loop {
+        match (*unpacker).framing.advance(
+            &(&(*unpacker).buffer)[(*unpacker).start..],
+            &(*unpacker).limits,
+        ) {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(name) => {
+                let data = &(&(*unpacker).buffer)[(*unpacker).start..];
+                let pos = (*unpacker).framing.pos;
+                let err = DeserializeError {
+                    message: Cow::Owned(format!("{name} exceeded")),
+                    kind: Some(ErrorKind::LimitExceeded),
+                    pos: Some((*unpacker).offset + (*unpacker).start + pos),
+                    path: None,
+                    marker: Some(data[pos]),
+                };
+                // The stream cannot be resynchronized after an item that is
+                // not decoded.
+                (*unpacker).offset += (*unpacker).buffer.len();
+                (*unpacker).buffer.clear();
+                (*unpacker).start = 0;
+                (*unpacker).framing = Framing::new();
+                return raise_deserialize_error((*unpacker).state, &err);
+            }
+        }
+        let read = match (*unpacker).read {
+            Some(callable) => callable,
+            None => return null_mut(),
+        };
+        let buffer = &mut (*unpacker).buffer;
+        if (*unpacker).start > 0 {
+            buffer.drain(..(*unpacker).start);
+            (*unpacker).offset += (*unpacker).start;
+            (*unpacker).start = 0;
+        }
+        // read may also call back into the unpacker.
+        (*unpacker).busy = true;
+        let res = read_chunk(read.as_ptr(), buffer);
+        (*unpacker).busy = false;
+        match res {
+            Ok(false) => {
+                if unlikely!((*unpacker).buffer.len() as u64 > (*unpacker).max_buffer_size) {
+                    let err = buffer_size_error((*unpacker).offset);
+                    (*unpacker).offset += (*unpacker).buffer.len();
+                    (*unpacker).buffer.clear();
+                    (*unpacker).framing = Framing::new();
+                    return raise_deserialize_error((*unpacker).state, &err);
+                }
+            }
+            Ok(true) => {
+                if (*unpacker).buffer.is_empty() {
+                    return null_mut();
+                }
+                let err = DeserializeError {
+                    message: Cow::Borrowed("unexpected end of file"),
+                    kind: Some(ErrorKind::UnexpectedEof),
+                    pos: Some((*unpacker).offset),
+                    path: None,
+                    marker: Some((&(*unpacker).buffer)[0]),
+                };
+                (*unpacker).offset += (*unpacker).buffer.len();
+                (*unpacker).buffer.clear();
+                (*unpacker).framing = Framing::new();
+                return raise_deserialize_error((*unpacker).state, &err);
+            }
+            Err(None) => return null_mut(),
+            Err(Some(err)) => return raise_deserialize_error((*unpacker).state, &err),
+        }
+    }
+    let data = &(&(*unpacker).buffer)[(*unpacker).start..];
+    let len = (*unpacker).framing.pos;
+    let offset = (*unpacker).offset + (*unpacker).start;
+    (*unpacker).start += len;
+    (*unpacker).framing = Framing::new();
+
+    // ext_hook may call back into the unpacker, which must not modify the
+    // buffer while it is borrowed.
+    (*unpacker).busy = true;
+    let res = deserialize_bytes(
+        &data[..len],
+        (*unpacker).state,
+        Config {
+            hook: (*unpacker).ext_hook,
+            registry: (*unpacker).ext_registry,
+            ..Default::default()
+        },
+        (*unpacker).opts,
+        &(*unpacker).limits,
+        None,
+    );
+    (*unpacker).busy = false;
+    match res {
+        Ok(val) => val.as_ptr(),
+        Err(err) => raise_deserialize_error((*unpacker).state, &err.shift(offset)),
+    }
+}
+
+pub unsafe fn create_unpacker_type(module: *mut PyObject) -> *mut PyTypeObject {
+    let methods: Box<[PyMethodDef; 2]> = Box::new([
+        PyMethodDef {
+            ml_name: c"feed".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: unpacker_feed,
+            },
+            ml_flags: METH_O,
+            ml_doc: FEED_DOC.as_ptr(),
+        },
+        PyMethodDef::zeroed(),
+    ]);
+    let mut slots: [PyType_Slot; 9] = [
+        PyType_Slot {
+            slot: Py_tp_doc,
+            pfunc: UNPACKER_DOC.as_ptr() as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_new,
+            pfunc: unpacker_new as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_dealloc,
+            pfunc: unpacker_dealloc as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_iter,
+            pfunc: PyObject_SelfIter as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_iternext,
+            pfunc: unpacker_next as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_methods,
+            pfunc: Box::into_raw(methods).cast::<c_void>(),
+        },
+        PyType_Slot {
+            slot: Py_tp_traverse,
+            pfunc: unpacker_traverse as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_clear,
+            pfunc: unpacker_clear as *mut c_void,
+        },
+        PyType_Slot {
+            slot: 0,
+            pfunc: null_mut(),
+        },
+    ];
+    let mut spec = PyType_Spec {
+        name: c"ormsgpack.Unpacker".as_ptr(),
+        basicsize: std::mem::size_of::<Unpacker>() as c_int,
+        itemsize: 0,
+        flags: (Py_TPFLAGS_DEFAULT | Py_TPFLAGS_HAVE_GC) as c_uint,
+        slots: slots.as_mut_ptr(),
+    };
+    PyType_FromModuleAndSpec(module, &mut spec, null_mut()).cast::<PyTypeObject>()
+}
+
+/// Deserialize the first object of a file object, which is read in chunks
+/// until the object is complete. The data following it is only read, to
+/// check that there is none, if `REJECT_TRAILING_DATA` is set. An
+/// `Err(None)` result means that a Python exception raised by the file
+/// object is set.
+pub fn deserialize_file(
+    fp: *mut PyObject,
+    state: *mut State,
+    config: Config,
+    opts: Opt,
+    limits: &Limits,
+) -> Result<NonNull<PyObject>, Option<DeserializeError<'static>>> {
+    let read = unsafe { PyObject_GetAttr(fp, (*state).read_str) };
+    if read.is_null() {
+        return Err(None);
+    }
+    let mut buffer = Vec::new();
+    let mut framing = Framing::new();
+    let mut eof = false;
+    let res = loop {
+        match framing.advance(&buffer, limits) {
+            Ok(true) => break Ok(()),
+            Ok(false) => {}
+            Err(name) => {
+                let pos = framing.pos;
+                break Err(Some(DeserializeError {
+                    message: Cow::Owned(format!("{name} exceeded")),
+                    kind: Some(ErrorKind::LimitExceeded),
+                    pos: Some(pos),
+                    path: None,
+                    marker: Some(buffer[pos]),
+                }));
+            }
+        }
+        if eof {
+            // The truncated object is reported by the deserializer.
+            break Ok(());
+        }
+        match read_chunk(read, &mut buffer) {
+            Ok(val) => eof = val,
+            Err(err) => break Err(err),
+        }
+    };
+    let res = res.and_then(|()| {
+        if opts & REJECT_TRAILING_DATA != 0 && !eof && buffer.len() == framing.pos {
+            read_chunk(read, &mut buffer).map(|_| ())
+        } else {
+            Ok(())
+        }
+    });
+    unsafe { Py_DECREF(read) };
+    res?;
+    let contents = if opts & REJECT_TRAILING_DATA != 0 || framing.pending > 0 {
+        &buffer[..]
+    } else {
+        &buffer[..framing.pos]
+    };
+    deserialize_bytes(contents, state, config, opts, limits, None).map_err(Some)
+}
+
+/// Create an unpacker that reads from a file object.
+pub fn unpacker_from_file(
+    fp: *mut PyObject,
+    state: *mut State,
+    config: Config,
+    opts: Opt,
+    limits: Limits,
+    max_buffer_size: u64,
+) -> *mut PyObject {
+    unsafe {
+        let read = PyObject_GetAttr(fp, (*state).read_str);
+        if read.is_null() {
+            return null_mut();
+        }
+        let obj = new_unpacker(
+            (*state).unpacker_type,
+            state,
+            config,
+            NonNull::new(read),
+            opts,
+            limits,
+            max_buffer_size,
+        );
+        Py_DECREF(read);
+        obj
+    }
+}
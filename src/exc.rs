@@ -1,4 +1,262 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use crate::deserialize::DeserializeError;
+use crate::ffi::*;
+use crate::serialize::SerializeError;
+use crate::state::State;
+use pyo3::ffi::*;
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
 pub const RECURSION_LIMIT_REACHED: &str = "Recursion limit reached";
 pub const KEY_MUST_BE_STR: &str = "Dict key must be str";
+
+/// The number of segments kept at each end of a longer error path, whose
+/// middle segments are elided.
+const ERROR_PATH_EDGE_SEGMENTS: usize = 8;
+
+#[inline(always)]
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+/// Format a map key as a segment of an error path, using the attribute
+/// syntax for keys that are identifiers and the subscript syntax otherwise.
+#[cold]
+pub fn path_key_segment(key: *mut PyObject) -> String {
+    unsafe {
+        if ob_type!(key) == &raw mut PyUnicode_Type {
+            if let Ok(name) = unicode_to_str(key) {
+                if is_identifier(name) {
+                    return format!(".{name}");
+                }
+            }
+        }
+        // The exception raised by a hook is kept for the encode or decode
+        // error.
+        let exc = pyerr_take();
+        let repr = PyObject_Repr(key);
+        let segment = if repr.is_null() {
+            PyErr_Clear();
+            String::from("[?]")
+        } else {
+            let segment = match unicode_to_str(repr) {
+                Ok(value) => format!("[{value}]"),
+                Err(_) => String::from("[?]"),
+            };
+            Py_DECREF(repr);
+            segment
+        };
+        if !exc.is_null() {
+            pyerr_restore(exc);
+        }
+        segment
+    }
+}
+
+/// Format a str map key as a segment of an error path.
+#[cold]
+pub fn path_str_key_segment(key: &str) -> String {
+    if is_identifier(key) {
+        return format!(".{key}");
+    }
+    let obj = unicode_from_str(key);
+    let segment = path_key_segment(obj);
+    unsafe { Py_DECREF(obj) };
+    segment
+}
+
+/// Format an error path from its segments, from the innermost, eliding the
+/// middle segments of a longer path.
+#[cold]
+pub fn format_error_path(segments: &[String]) -> String {
+    let len = segments.len();
+    let mut path = String::from("$");
+    for (i, segment) in segments.iter().rev().enumerate() {
+        if len > 2 * ERROR_PATH_EDGE_SEGMENTS + 1
+            && (ERROR_PATH_EDGE_SEGMENTS..len - ERROR_PATH_EDGE_SEGMENTS).contains(&i)
+        {
+            if i == ERROR_PATH_EDGE_SEGMENTS {
+                path.push_str("...");
+            }
+            continue;
+        }
+        path.push_str(segment);
+    }
+    path
+}
+
+/// Describe the raised exception as in a traceback, such as
+/// `ValueError: invalid data`, and leave it raised.
+#[cold]
+pub fn describe_raised_exception() -> String {
+    unsafe {
+        let exc = pyerr_take();
+        if exc.is_null() {
+            return String::new();
+        }
+        let name = CStr::from_ptr((*ob_type!(exc)).tp_name).to_string_lossy();
+        let msg = PyObject_Str(exc);
+        let description = if msg.is_null() {
+            PyErr_Clear();
+            name.into_owned()
+        } else {
+            let description = match unicode_to_str(msg) {
+                Ok("") | Err(_) => name.into_owned(),
+                Ok(value) => format!("{name}: {value}"),
+            };
+            Py_DECREF(msg);
+            description
+        };
+        pyerr_restore(exc);
+        description
+    }
+}
+
+/// Create an exception of type `exception` with the message `msg`, chaining
+/// the raised exception, if any, as its `__cause__`.
+#[cold]
+unsafe fn new_exception(exception: *mut PyObject, msg: &str) -> *mut PyObject {
+    let cause = pyerr_take();
+    let err_msg = PyUnicode_FromStringAndSize(msg.as_ptr().cast::<c_char>(), msg.len() as isize);
+    let exc = pyobject_call_one_arg(exception, err_msg);
+    Py_DECREF(err_msg);
+    if !cause.is_null() {
+        if exc.is_null() {
+            Py_DECREF(cause);
+        } else {
+            PyException_SetCause(exc, cause);
+        }
+    }
+    exc
+}
+
+#[cold]
+#[inline(never)]
+pub fn raise_unpackb_exception(state: *mut State, msg: &str) -> *mut PyObject {
+    raise_deserialize_error(state, &DeserializeError::new(Cow::Borrowed(msg)))
+}
+
+/// Raise a `TypeError` for an invalid argument, chaining the raised
+/// exception, if any, as its cause.
+#[cold]
+#[inline(never)]
+pub fn raise_type_error(msg: &str) -> *mut PyObject {
+    unsafe {
+        let exc = new_exception(PyExc_TypeError, msg);
+        if !exc.is_null() {
+            PyErr_SetObject(PyExc_TypeError, exc);
+            Py_DECREF(exc);
+        }
+    };
+    std::ptr::null_mut()
+}
+
+#[cold]
+unsafe fn set_optional_attr(obj: *mut PyObject, name: *mut PyObject, value: *mut PyObject) {
+    if value.is_null() {
+        PyObject_SetAttr(obj, name, Py_None());
+    } else {
+        PyObject_SetAttr(obj, name, value);
+        Py_DECREF(value);
+    }
+}
+
+/// Raise a `MsgpackDecodeError`, or the subclass for the kind of `err`,
+/// with the `kind`, `pos`, `path` and `marker` attributes of `err`, which
+/// are `None` if not known.
+#[cold]
+#[inline(never)]
+pub fn raise_deserialize_error(state: *mut State, err: &DeserializeError) -> *mut PyObject {
+    unsafe {
+        let exception = err.exception(state);
+        let exc = new_exception(exception, &err.message);
+        if exc.is_null() {
+            return std::ptr::null_mut();
+        }
+        let kind = match err.kind {
+            Some(kind) => {
+                PyObject_GetAttrString((*state).MsgpackDecodeErrorKind, kind.name().as_ptr())
+            }
+            None => std::ptr::null_mut(),
+        };
+        set_optional_attr(exc, (*state).kind_str, kind);
+        let pos = match err.pos {
+            Some(pos) => PyLong_FromSize_t(pos),
+            None => std::ptr::null_mut(),
+        };
+        set_optional_attr(exc, (*state).pos_str, pos);
+        let path = match err.path {
+            Some(ref path) => unicode_from_str(path),
+            None => std::ptr::null_mut(),
+        };
+        set_optional_attr(exc, (*state).path_str, path);
+        let marker = match err.marker {
+            Some(marker) => PyLong_FromLong(marker.into()),
+            None => std::ptr::null_mut(),
+        };
+        set_optional_attr(exc, (*state).marker_str, marker);
+        PyErr_SetObject(exception, exc);
+        Py_DECREF(exc);
+    };
+    std::ptr::null_mut()
+}
+
+#[cold]
+#[inline(never)]
+fn raise_encode_exception(
+    state: *mut State,
+    exception: *mut PyObject,
+    msg: &str,
+    path: Option<&str>,
+) -> *mut PyObject {
+    unsafe {
+        let exc = new_exception(exception, msg);
+        if exc.is_null() {
+            return std::ptr::null_mut();
+        }
+        let path = match path {
+            Some(path) => unicode_from_str(path),
+            None => std::ptr::null_mut(),
+        };
+        set_optional_attr(exc, (*state).path_str, path);
+        PyErr_SetObject(exception, exc);
+        Py_DECREF(exc);
+    };
+    std::ptr::null_mut()
+}
+
+#[cold]
+#[inline(never)]
+pub fn raise_packb_exception(state: *mut State, msg: &str) -> *mut PyObject {
+    raise_encode_exception(state, unsafe { (*state).MsgpackEncodeError }, msg, None)
+}
+
+/// Raise a `MsgpackEncodeError`, or the subclass for the kind of `err`,
+/// with the `path` attribute of `err`.
+#[cold]
+#[inline(never)]
+pub fn raise_serialize_error(state: *mut State, err: &SerializeError) -> *mut PyObject {
+    raise_encode_exception(
+        state,
+        err.exception(state),
+        &err.message,
+        err.path.as_deref(),
+    )
+}
+
+#[cold]
+#[inline(never)]
+pub fn raise_buffer_too_small_exception(state: *mut State, msg: &str) -> *mut PyObject {
+    raise_encode_exception(
+        state,
+        unsafe { (*state).MsgpackBufferTooSmallError },
+        msg,
+        None,
+    )
+}
@@ -1,5 +1,6 @@
 use crate::ffi::*;
 use pyo3::ffi::*;
+use std::mem::offset_of;
 use std::os::raw::{c_int, c_uint, c_void};
 use std::ptr::null_mut;
 
@@ -10,6 +11,18 @@ pub struct PyExt {
     pub data: *mut PyObject,
 }
 
+/// Create an `Ext` instance, stealing the references to `tag` and `data`.
+pub unsafe fn new_ext(
+    ext_type: *mut PyTypeObject,
+    tag: *mut PyObject,
+    data: *mut PyObject,
+) -> *mut PyObject {
+    let obj = (*ext_type).tp_alloc.unwrap()(ext_type, 0);
+    (*obj.cast::<PyExt>()).tag = tag;
+    (*obj.cast::<PyExt>()).data = data;
+    obj
+}
+
 #[no_mangle]
 unsafe extern "C" fn ext_new(
     subtype: *mut PyTypeObject,
@@ -32,19 +45,22 @@ unsafe extern "C" fn ext_new(
         return null_mut();
     }
     let data = pytuple_get_item(args, 1);
-    if PyBytes_Check(data) == 0 {
+    let data = if PyBytes_Check(data) != 0 {
+        Py_NewRef(data)
+    } else if PyObject_CheckBuffer(data) != 0 {
+        let bytes = PyBytes_FromObject(data);
+        if bytes.is_null() {
+            return null_mut();
+        }
+        bytes
+    } else {
         PyErr_SetString(
             PyExc_TypeError,
-            c"Ext.__new__() second argument must be bytes".as_ptr(),
+            c"Ext.__new__() second argument must be a bytes-like object".as_ptr(),
         );
         return null_mut();
-    }
-    let obj = (*subtype).tp_alloc.unwrap()(subtype, 0);
-    Py_INCREF(tag);
-    (*obj.cast::<PyExt>()).tag = tag;
-    Py_INCREF(data);
-    (*obj.cast::<PyExt>()).data = data;
-    obj
+    };
+    new_ext(subtype, Py_NewRef(tag), data)
 }
 
 #[no_mangle]
@@ -54,8 +70,100 @@ unsafe extern "C" fn ext_dealloc(op: *mut PyObject) {
     (*ob_type!(op)).tp_free.unwrap()(op.cast::<c_void>());
 }
 
+#[no_mangle]
+unsafe extern "C" fn ext_richcompare(
+    op: *mut PyObject,
+    other: *mut PyObject,
+    compare_op: c_int,
+) -> *mut PyObject {
+    if ob_type!(other) != ob_type!(op) || (compare_op != Py_EQ && compare_op != Py_NE) {
+        return Py_NewRef(Py_NotImplemented());
+    }
+    let ext = op.cast::<PyExt>();
+    let other = other.cast::<PyExt>();
+    let tag_eq = PyObject_RichCompareBool((*ext).tag, (*other).tag, Py_EQ);
+    if tag_eq == -1 {
+        return null_mut();
+    }
+    let data_eq = PyObject_RichCompareBool((*ext).data, (*other).data, Py_EQ);
+    if data_eq == -1 {
+        return null_mut();
+    }
+    let eq = tag_eq == 1 && data_eq == 1;
+    Py_NewRef(if eq == (compare_op == Py_EQ) {
+        Py_True()
+    } else {
+        Py_False()
+    })
+}
+
+/// Return the `(tag, data)` tuple of an `Ext` instance.
+unsafe fn ext_args(op: *mut PyObject) -> *mut PyObject {
+    let ext = op.cast::<PyExt>();
+    let args = PyTuple_New(2);
+    PyTuple_SET_ITEM(args, 0, Py_NewRef((*ext).tag));
+    PyTuple_SET_ITEM(args, 1, Py_NewRef((*ext).data));
+    args
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_hash(op: *mut PyObject) -> Py_hash_t {
+    let args = ext_args(op);
+    let hash = PyObject_Hash(args);
+    Py_DECREF(args);
+    hash
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_repr(op: *mut PyObject) -> *mut PyObject {
+    let ext = op.cast::<PyExt>();
+    PyUnicode_FromFormat(c"Ext(%R, %R)".as_ptr(), (*ext).tag, (*ext).data)
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_reduce(op: *mut PyObject, _args: *mut PyObject) -> *mut PyObject {
+    let reduced = PyTuple_New(2);
+    PyTuple_SET_ITEM(reduced, 0, Py_NewRef(ob_type!(op).cast::<PyObject>()));
+    PyTuple_SET_ITEM(reduced, 1, ext_args(op));
+    reduced
+}
+
 pub unsafe fn create_ext_type() -> *mut PyTypeObject {
-    let mut slots: [PyType_Slot; 3] = [
+    let members: Box<[PyMemberDef; 3]> = Box::new([
+        PyMemberDef {
+            name: c"tag".as_ptr(),
+            type_code: Py_T_OBJECT_EX,
+            offset: offset_of!(PyExt, tag) as Py_ssize_t,
+            flags: Py_READONLY,
+            doc: c"The extension type.".as_ptr(),
+        },
+        PyMemberDef {
+            name: c"data".as_ptr(),
+            type_code: Py_T_OBJECT_EX,
+            offset: offset_of!(PyExt, data) as Py_ssize_t,
+            flags: Py_READONLY,
+            doc: c"The extension value.".as_ptr(),
+        },
+        PyMemberDef {
+            name: std::ptr::null(),
+            type_code: 0,
+            offset: 0,
+            flags: 0,
+            doc: std::ptr::null(),
+        },
+    ]);
+    let methods: Box<[PyMethodDef; 2]> = Box::new([
+        PyMethodDef {
+            ml_name: c"__reduce__".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: ext_reduce,
+            },
+            ml_flags: METH_NOARGS,
+            ml_doc: null_mut(),
+        },
+        PyMethodDef::zeroed(),
+    ]);
+    let mut slots: [PyType_Slot; 8] = [
         PyType_Slot {
             slot: Py_tp_new,
             pfunc: ext_new as *mut c_void,
@@ -64,6 +172,26 @@ pub unsafe fn create_ext_type() -> *mut PyTypeObject {
             slot: Py_tp_dealloc,
             pfunc: ext_dealloc as *mut c_void,
         },
+        PyType_Slot {
+            slot: Py_tp_members,
+            pfunc: Box::into_raw(members).cast::<c_void>(),
+        },
+        PyType_Slot {
+            slot: Py_tp_methods,
+            pfunc: Box::into_raw(methods).cast::<c_void>(),
+        },
+        PyType_Slot {
+            slot: Py_tp_richcompare,
+            pfunc: ext_richcompare as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_hash,
+            pfunc: ext_hash as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_repr,
+            pfunc: ext_repr as *mut c_void,
+        },
         PyType_Slot {
             slot: 0,
             pfunc: null_mut(),
@@ -76,5 +204,11 @@ pub unsafe fn create_ext_type() -> *mut PyTypeObject {
         flags: Py_TPFLAGS_DEFAULT as c_uint,
         slots: slots.as_mut_ptr(),
     };
-    PyType_FromSpec(&mut spec).cast::<PyTypeObject>()
+    let ext_type = PyType_FromSpec(&mut spec);
+    let match_args = PyTuple_New(2);
+    PyTuple_SET_ITEM(match_args, 0, PyUnicode_InternFromString(c"tag".as_ptr()));
+    PyTuple_SET_ITEM(match_args, 1, PyUnicode_InternFromString(c"data".as_ptr()));
+    PyObject_SetAttrString(ext_type, c"__match_args__".as_ptr(), match_args);
+    Py_DECREF(match_args);
+    ext_type.cast::<PyTypeObject>()
 }
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::ffi::*;
+use pyo3::ffi::*;
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_uint, c_void};
+use std::ptr::null_mut;
+
+const EXT_REGISTRY_DOC: &CStr =
+    c"ExtRegistry()\n--\n\nA registry of classes serialized as MessagePack extension types.";
+const REGISTER_DOC: &CStr = c"register(cls, tag, to_payload, from_payload, /, *, recursive=False)\n--\n\nRegister a class as the extension type tag.";
+
+#[repr(C)]
+pub struct ExtRegistry {
+    ob_base: PyObject,
+    /// The registrations, as `(cls, tag, to_payload, from_payload,
+    /// recursive)` tuples, by class.
+    by_type: *mut PyObject,
+    /// The same registrations by tag.
+    by_tag: *mut PyObject,
+}
+
+/// A registration of an extension type, with borrowed references.
+pub struct ExtEntry {
+    pub tag: u8,
+    pub to_payload: *mut PyObject,
+    pub from_payload: *mut PyObject,
+    /// Whether the payload is serialized to MessagePack, instead of being
+    /// `bytes`.
+    pub recursive: bool,
+}
+
+impl ExtEntry {
+    unsafe fn from_tuple(entry: *mut PyObject) -> Self {
+        ExtEntry {
+            tag: PyLong_AsLong(PyTuple_GET_ITEM(entry, 1)) as u8,
+            to_payload: PyTuple_GET_ITEM(entry, 2),
+            from_payload: PyTuple_GET_ITEM(entry, 3),
+            recursive: PyTuple_GET_ITEM(entry, 4) == Py_True(),
+        }
+    }
+}
+
+/// Return the registration of the exact type `ob_type`, if any.
+#[inline(always)]
+pub unsafe fn lookup_type(registry: *mut PyObject, ob_type: *mut PyTypeObject) -> Option<ExtEntry> {
+    let registry = registry.cast::<ExtRegistry>();
+    let entry = PyDict_GetItem((*registry).by_type, ob_type.cast::<PyObject>());
+    if entry.is_null() {
+        None
+    } else {
+        Some(ExtEntry::from_tuple(entry))
+    }
+}
+
+/// Return the registration of the extension type `tag`, if any.
+#[inline(always)]
+pub unsafe fn lookup_tag(registry: *mut PyObject, tag: i8) -> Option<ExtEntry> {
+    if tag < 0 {
+        return None;
+    }
+    let registry = registry.cast::<ExtRegistry>();
+    let key = PyLong_FromLong(tag.into());
+    let entry = PyDict_GetItem((*registry).by_tag, key);
+    Py_DECREF(key);
+    if entry.is_null() {
+        None
+    } else {
+        Some(ExtEntry::from_tuple(entry))
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_registry_new(
+    subtype: *mut PyTypeObject,
+    args: *mut PyObject,
+    kwds: *mut PyObject,
+) -> *mut PyObject {
+    if Py_SIZE(args) != 0 || (!kwds.is_null() && pydict_size(kwds) != 0) {
+        PyErr_SetString(
+            PyExc_TypeError,
+            c"ExtRegistry() takes no arguments".as_ptr(),
+        );
+        return null_mut();
+    }
+    let obj = (*subtype).tp_alloc.unwrap()(subtype, 0);
+    if unlikely!(obj.is_null()) {
+        return null_mut();
+    }
+    let registry = obj.cast::<ExtRegistry>();
+    (*registry).by_type = PyDict_New();
+    (*registry).by_tag = PyDict_New();
+    obj
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_registry_dealloc(op: *mut PyObject) {
+    PyObject_GC_UnTrack(op.cast::<c_void>());
+    let registry = op.cast::<ExtRegistry>();
+    Py_DECREF((*registry).by_type);
+    Py_DECREF((*registry).by_tag);
+    let tp = ob_type!(op);
+    (*tp).tp_free.unwrap()(op.cast::<c_void>());
+    Py_DECREF(tp.cast::<PyObject>());
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_registry_traverse(
+    op: *mut PyObject,
+    visit: visitproc,
+    arg: *mut c_void,
+) -> c_int {
+    let registry = op.cast::<ExtRegistry>();
+    let ret = visit((*registry).by_type, arg);
+    if ret != 0 {
+        return ret;
+    }
+    let ret = visit((*registry).by_tag, arg);
+    if ret != 0 {
+        return ret;
+    }
+    visit(ob_type!(op).cast::<PyObject>(), arg)
+}
+
+/// Break the reference cycles through the callables of the registrations,
+/// which are removed so that the dicts remain valid.
+#[no_mangle]
+unsafe extern "C" fn ext_registry_clear(op: *mut PyObject) -> c_int {
+    let registry = op.cast::<ExtRegistry>();
+    PyDict_Clear((*registry).by_type);
+    PyDict_Clear((*registry).by_tag);
+    0
+}
+
+#[cold]
+unsafe fn raise_type_error(msg: &CStr) -> *mut PyObject {
+    PyErr_SetString(PyExc_TypeError, msg.as_ptr());
+    null_mut()
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_registry_register(
+    op: *mut PyObject,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+) -> *mut PyObject {
+    let registry = op.cast::<ExtRegistry>();
+    let mut recursive = false;
+
+    let num_args = PyVectorcall_NARGS(nargs as usize);
+    if unlikely!(num_args != 4) {
+        return raise_type_error(c"register() takes exactly 4 positional arguments");
+    }
+    if !kwnames.is_null() {
+        for i in 0..Py_SIZE(kwnames) {
+            let arg = pytuple_get_item(kwnames, i);
+            let value = *args.offset(num_args + i);
+            if PyUnicode_CompareWithASCIIString(arg, c"recursive".as_ptr()) == 0 {
+                if unlikely!(PyBool_Check(value) == 0) {
+                    return raise_type_error(c"recursive must be a bool");
+                }
+                recursive = value == Py_True();
+            } else {
+                return raise_type_error(c"register() got an unexpected keyword argument");
+            }
+        }
+    }
+
+    let cls = *args;
+    let tag = *args.offset(1);
+    let to_payload = *args.offset(2);
+    let from_payload = *args.offset(3);
+    if unlikely!(PyType_Check(cls) == 0) {
+        return raise_type_error(c"cls must be a type");
+    }
+    if unlikely!(PyLong_Check(tag) == 0 || PyBool_Check(tag) != 0) {
+        return raise_type_error(c"tag must be an int");
+    }
+    let tag_value = PyLong_AsLong(tag);
+    if unlikely!(!(0..=127).contains(&tag_value)) {
+        PyErr_Clear();
+        PyErr_SetString(PyExc_ValueError, c"tag must be between 0 and 127".as_ptr());
+        return null_mut();
+    }
+    if unlikely!(PyCallable_Check(to_payload) == 0 || PyCallable_Check(from_payload) == 0) {
+        return raise_type_error(c"to_payload and from_payload must be callable");
+    }
+
+    // A class or a tag that is registered again replaces its registration
+    // in both directions.
+    let key = PyLong_FromLong(tag_value);
+    let previous = PyDict_GetItem((*registry).by_tag, key);
+    if !previous.is_null() {
+        PyDict_DelItem((*registry).by_type, PyTuple_GET_ITEM(previous, 0));
+    }
+    let previous = PyDict_GetItem((*registry).by_type, cls);
+    if !previous.is_null() {
+        PyDict_DelItem((*registry).by_tag, PyTuple_GET_ITEM(previous, 1));
+    }
+    let entry = PyTuple_New(5);
+    PyTuple_SET_ITEM(entry, 0, Py_NewRef(cls));
+    PyTuple_SET_ITEM(entry, 1, Py_NewRef(key));
+    PyTuple_SET_ITEM(entry, 2, Py_NewRef(to_payload));
+    PyTuple_SET_ITEM(entry, 3, Py_NewRef(from_payload));
+    PyTuple_SET_ITEM(
+        entry,
+        4,
+        Py_NewRef(if recursive { Py_True() } else { Py_False() }),
+    );
+    PyDict_SetItem((*registry).by_type, cls, entry);
+    PyDict_SetItem((*registry).by_tag, key, entry);
+    Py_DECREF(entry);
+    Py_DECREF(key);
+
+    Py_INCREF(Py_None());
+    Py_None()
+}
+
+pub unsafe fn create_ext_registry_type(module: *mut PyObject) -> *mut PyTypeObject {
+    let methods: Box<[PyMethodDef; 2]> = Box::new([
+        PyMethodDef {
+            ml_name: c"register".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunctionFastWithKeywords: ext_registry_register,
+            },
+            ml_flags: METH_FASTCALL | METH_KEYWORDS,
+            ml_doc: REGISTER_DOC.as_ptr(),
+        },
+        PyMethodDef::zeroed(),
+    ]);
+    let mut slots: [PyType_Slot; 7] = [
+        PyType_Slot {
+            slot: Py_tp_doc,
+            pfunc: EXT_REGISTRY_DOC.as_ptr() as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_new,
+            pfunc: ext_registry_new as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_dealloc,
+            pfunc: ext_registry_dealloc as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_traverse,
+            pfunc: ext_registry_traverse as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_clear,
+            pfunc: ext_registry_clear as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_methods,
+            pfunc: Box::into_raw(methods).cast::<c_void>(),
+        },
+        PyType_Slot {
+            slot: 0,
+            pfunc: null_mut(),
+        },
+    ];
+    let mut spec = PyType_Spec {
+        name: c"ormsgpack.ExtRegistry".as_ptr(),
+        basicsize: std::mem::size_of::<ExtRegistry>() as c_int,
+        itemsize: 0,
+        flags: (Py_TPFLAGS_DEFAULT | Py_TPFLAGS_HAVE_GC) as c_uint,
+        slots: slots.as_mut_ptr(),
+    };
+    PyType_FromModuleAndSpec(module, &mut spec, null_mut()).cast::<PyTypeObject>()
+}
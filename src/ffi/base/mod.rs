@@ -33,6 +33,13 @@ pub unsafe fn pydict_set_item_known_hash(
     PyDict_SetItem(mp, key, item)
 }
 
+/// Return a borrowed reference to the mapping of a `mappingproxy`, or null
+/// if it is not accessible.
+#[inline(always)]
+pub unsafe fn pymappingproxy_mapping(_: *mut PyObject) -> *mut PyObject {
+    std::ptr::null_mut()
+}
+
 #[inline(always)]
 pub unsafe fn pyobject_call_one_arg(func: *mut PyObject, arg: *mut PyObject) -> *mut PyObject {
     PyObject_CallFunctionObjArgs(func, arg, std::ptr::null_mut::<PyObject>())
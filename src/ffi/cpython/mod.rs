@@ -36,6 +36,18 @@ pub unsafe fn pydict_set_item_known_hash(
     _PyDict_SetItem_KnownHash(mp, key, item, hash)
 }
 
+#[repr(C)]
+struct MappingProxyObject {
+    pub ob_base: PyObject,
+    pub mapping: *mut PyObject,
+}
+
+/// Return a borrowed reference to the mapping of a `mappingproxy`.
+#[inline(always)]
+pub unsafe fn pymappingproxy_mapping(op: *mut PyObject) -> *mut PyObject {
+    (*op.cast::<MappingProxyObject>()).mapping
+}
+
 #[inline(always)]
 pub unsafe fn pyobject_call_one_arg(func: *mut PyObject, arg: *mut PyObject) -> *mut PyObject {
     PyObject_CallOneArg(func, arg)
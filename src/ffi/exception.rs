@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use pyo3::ffi::*;
+
+/// Take the raised exception, normalized, or return null if none is raised.
+#[cfg(Py_3_12)]
+#[cold]
+pub unsafe fn pyerr_take() -> *mut PyObject {
+    PyErr_GetRaisedException()
+}
+
+#[cfg(not(Py_3_12))]
+#[cold]
+pub unsafe fn pyerr_take() -> *mut PyObject {
+    let mut ptype = std::ptr::null_mut();
+    let mut pvalue = std::ptr::null_mut();
+    let mut ptraceback = std::ptr::null_mut();
+    PyErr_Fetch(&mut ptype, &mut pvalue, &mut ptraceback);
+    if ptype.is_null() {
+        return std::ptr::null_mut();
+    }
+    PyErr_NormalizeException(&mut ptype, &mut pvalue, &mut ptraceback);
+    if !ptraceback.is_null() {
+        PyException_SetTraceback(pvalue, ptraceback);
+        Py_DECREF(ptraceback);
+    }
+    Py_DECREF(ptype);
+    pvalue
+}
+
+/// Raise an exception taken by `pyerr_take`, stealing the reference.
+#[cfg(Py_3_12)]
+#[cold]
+pub unsafe fn pyerr_restore(exc: *mut PyObject) {
+    PyErr_SetRaisedException(exc)
+}
+
+#[cfg(not(Py_3_12))]
+#[cold]
+pub unsafe fn pyerr_restore(exc: *mut PyObject) {
+    let ptype = Py_TYPE(exc).cast::<PyObject>();
+    Py_INCREF(ptype);
+    PyErr_Restore(ptype, exc, PyException_GetTraceback(exc))
+}
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use pyo3::ffi::*;
+use std::os::raw::{c_int, c_void};
+use std::ptr::NonNull;
+
+/// Visit an optional strong reference held by an object, in its
+/// `tp_traverse` slot.
+#[inline]
+pub unsafe fn visit_optional(
+    obj: Option<NonNull<PyObject>>,
+    visit: visitproc,
+    arg: *mut c_void,
+) -> c_int {
+    match obj {
+        Some(obj) => visit(obj.as_ptr(), arg),
+        None => 0,
+    }
+}
+
+/// Release an optional strong reference held by an object, in its
+/// `tp_clear` and `tp_dealloc` slots.
+#[inline]
+pub unsafe fn clear_optional(obj: &mut Option<NonNull<PyObject>>) {
+    if let Some(ptr) = obj.take() {
+        Py_DECREF(ptr.as_ptr());
+    }
+}
@@ -1,16 +1,22 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 mod critical_section;
+mod exception;
+mod gc;
 #[cfg_attr(any(PyPy, GraalPy), path = "base/mod.rs")]
 #[cfg_attr(not(any(PyPy, GraalPy)), path = "cpython/mod.rs")]
 mod impl_;
 mod int;
 mod unicode;
+mod weakkeydict;
 
 pub use critical_section::*;
+pub use exception::*;
+pub use gc::*;
 pub use impl_::*;
 pub use int::*;
 pub use unicode::*;
+pub use weakkeydict::*;
 
 use pyo3::ffi::*;
 use std::os::raw::{c_char, c_int};
@@ -104,3 +110,34 @@ impl Iterator for PyDictIter {
         (len, Some(len))
     }
 }
+
+/// An iterator over the elements of a set or frozenset, as borrowed
+/// references.
+pub struct PySetIter {
+    op: *mut PyObject,
+    pos: isize,
+}
+
+impl PySetIter {
+    #[inline]
+    pub fn from_pyobject(op: *mut PyObject) -> Self {
+        PySetIter { op: op, pos: 0 }
+    }
+}
+
+impl Iterator for PySetIter {
+    type Item = NonNull<PyObject>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut key: *mut PyObject = std::ptr::null_mut();
+        let mut hash: Py_hash_t = 0;
+        unsafe {
+            if _PySet_NextEntry(self.op, &mut self.pos, &mut key, &mut hash) == 1 {
+                Some(NonNull::new_unchecked(key))
+            } else {
+                None
+            }
+        }
+    }
+}
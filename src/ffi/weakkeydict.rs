@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use pyo3::ffi::*;
+use std::ptr::null_mut;
+
+/// A dict keyed by weak references to its keys, for the caches by type that
+/// must not keep the types alive. An entry is removed when its key is
+/// destroyed, so the values must not refer to their key.
+#[derive(Clone, Copy)]
+pub struct WeakKeyDict {
+    dict: *mut PyObject,
+    /// The callback of the weak references, bound to `dict`.
+    callback: *mut PyObject,
+}
+
+unsafe extern "C" fn remove_entry(dict: *mut PyObject, weakref: *mut PyObject) -> *mut PyObject {
+    // The entry is missing if the dict was cleared.
+    if PyDict_DelItem(dict, weakref) < 0 {
+        PyErr_Clear();
+    }
+    Py_INCREF(Py_None());
+    Py_None()
+}
+
+impl WeakKeyDict {
+    #[cold]
+    pub unsafe fn new() -> Self {
+        let dict = PyDict_New();
+        let method = Box::new(PyMethodDef {
+            ml_name: c"remove_entry".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: remove_entry,
+            },
+            ml_flags: METH_O,
+            ml_doc: null_mut(),
+        });
+        WeakKeyDict {
+            dict: dict,
+            callback: PyCFunction_New(Box::into_raw(method), dict),
+        }
+    }
+
+    /// Return a new reference to the value of `key`, or null if there is
+    /// none.
+    #[inline]
+    pub unsafe fn get(&self, key: *mut PyObject) -> *mut PyObject {
+        let weakref = PyWeakref_NewRef(key, null_mut());
+        if weakref.is_null() {
+            PyErr_Clear();
+            return null_mut();
+        }
+        let mut value: *mut PyObject = null_mut();
+        if compat::PyDict_GetItemRef(self.dict, weakref, &mut value) < 0 {
+            PyErr_Clear();
+        }
+        Py_DECREF(weakref);
+        value
+    }
+
+    /// Set the value of `key`. Nothing is stored if `key` does not support
+    /// weak references.
+    pub unsafe fn set(&self, key: *mut PyObject, value: *mut PyObject) {
+        let weakref = PyWeakref_NewRef(key, self.callback);
+        if weakref.is_null() {
+            PyErr_Clear();
+            return;
+        }
+        if PyDict_SetItem(self.dict, weakref, value) < 0 {
+            PyErr_Clear();
+        }
+        Py_DECREF(weakref);
+    }
+
+    pub unsafe fn clear(&self) {
+        PyDict_Clear(self.dict);
+    }
+}
+
+/// Return a new reference to the referent of `weakref`, or null if it was
+/// destroyed.
+#[inline]
+pub unsafe fn weakref_get(weakref: *mut PyObject) -> *mut PyObject {
+    let mut obj: *mut PyObject = null_mut();
+    if compat::PyWeakref_GetRef(weakref, &mut obj) < 0 {
+        PyErr_Clear();
+    }
+    obj
+}
@@ -12,3 +12,14 @@ where
         (**self).write_slices(bufs)
     }
 }
+
+impl WriteSlices for Vec<u8> {
+    fn write_slices<const N: usize>(&mut self, bufs: [&[u8]; N]) -> Result<(), std::io::Error> {
+        let len: usize = bufs.iter().map(|b| b.len()).sum();
+        self.reserve(len);
+        for buf in bufs {
+            self.extend_from_slice(buf);
+        }
+        Ok(())
+    }
+}
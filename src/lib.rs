@@ -16,26 +16,35 @@ mod util;
 mod deserialize;
 mod exc;
 mod ext;
+mod ext_registry;
 mod ffi;
 mod io;
 mod msgpack;
 mod opt;
 mod serialize;
 mod state;
+mod timestamp;
 
+use crate::exc::*;
 use crate::ffi::*;
+use crate::opt::parse_option_arg;
 use pyo3::ffi::*;
 use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::os::raw::c_int;
-use std::os::raw::c_long;
 use std::os::raw::c_void;
 use std::ptr::NonNull;
 
+const DUMP_DOC: &CStr = c"dump(obj, fp, /, default=None, option=None, *, ext_registry=None)\n--\n\nSerialize Python objects to msgpack and write them to a file object.";
+const LOAD_DOC: &CStr = c"load(fp, /, *, ext_hook=None, ext_registry=None, option=None, max_depth=1023, max_str_len=None, max_bin_len=None, max_array_len=None, max_map_len=None, max_ext_len=None, max_alloc_size=None)\n--\n\nRead a msgpack object from a file object and deserialize it.";
+const LOAD_ITER_DOC: &CStr = c"load_iter(fp, /, *, ext_hook=None, ext_registry=None, option=None, max_depth=1023, max_str_len=None, max_bin_len=None, max_array_len=None, max_map_len=None, max_ext_len=None, max_alloc_size=None, max_buffer_size=104857600)\n--\n\nReturn an iterator over the msgpack objects read from a file object.";
 const PACKB_DOC: &CStr =
-    c"packb(obj, /, default=None, option=None)\n--\n\nSerialize Python objects to msgpack.";
+    c"packb(obj, /, default=None, option=None, *, ext_registry=None)\n--\n\nSerialize Python objects to msgpack.";
+const PACKB_INTO_DOC: &CStr = c"packb_into(obj, buffer, /, offset=0, *, default=None, option=None, ext_registry=None)\n--\n\nSerialize Python objects to msgpack into a buffer and return the number of bytes written.";
+const REGISTER_ENCODER_DOC: &CStr = c"register_encoder(cls, encoder, /, *, subclasses=False)\n--\n\nRegister a callable converting instances of a type to serializable objects.";
 const UNPACKB_DOC: &CStr =
-    c"unpackb(obj, /, *, ext_hook=None, option=None)\n--\n\nDeserialize msgpack to Python objects.";
+    c"unpackb(obj, /, *, type=None, model=None, ext_hook=None, ext_registry=None, tzinfo=None, datetime_keys=None, option=None, max_depth=1023, max_str_len=None, max_bin_len=None, max_array_len=None, max_map_len=None, max_ext_len=None, max_alloc_size=None)\n--\n\nDeserialize msgpack to Python objects.";
+const UNPACKB_PARTIAL_DOC: &CStr = c"unpackb_partial(obj, /, offset=0, *, ext_hook=None, ext_registry=None, option=None, max_depth=1023, max_str_len=None, max_bin_len=None, max_array_len=None, max_map_len=None, max_ext_len=None, max_alloc_size=None)\n--\n\nDeserialize the msgpack object at offset and return it with the offset following it.";
 
 macro_rules! module_add_object {
     ($mptr: expr, $name: expr, $object:expr) => {
@@ -45,7 +54,7 @@ macro_rules! module_add_object {
 
 macro_rules! module_add_int {
     ($mptr:expr, $name:expr, $int:expr) => {
-        PyModule_AddIntConstant($mptr, $name.as_ptr(), $int as c_long);
+        PyModule_AddObject($mptr, $name.as_ptr(), PyLong_FromLongLong($int as i64));
     };
 }
 
@@ -53,7 +62,31 @@ macro_rules! module_add_int {
 #[no_mangle]
 #[cold]
 pub unsafe extern "C" fn PyInit_ormsgpack() -> *mut PyModuleDef {
-    let methods: Box<[PyMethodDef; 3]> = Box::new([
+    let methods: Box<[PyMethodDef; 9]> = Box::new([
+        PyMethodDef {
+            ml_name: c"dump".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunctionFastWithKeywords: dump,
+            },
+            ml_flags: METH_FASTCALL | METH_KEYWORDS,
+            ml_doc: DUMP_DOC.as_ptr(),
+        },
+        PyMethodDef {
+            ml_name: c"load".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunctionFastWithKeywords: load,
+            },
+            ml_flags: METH_FASTCALL | METH_KEYWORDS,
+            ml_doc: LOAD_DOC.as_ptr(),
+        },
+        PyMethodDef {
+            ml_name: c"load_iter".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunctionFastWithKeywords: load_iter,
+            },
+            ml_flags: METH_FASTCALL | METH_KEYWORDS,
+            ml_doc: LOAD_ITER_DOC.as_ptr(),
+        },
         PyMethodDef {
             ml_name: c"packb".as_ptr(),
             ml_meth: PyMethodDefPointer {
@@ -62,6 +95,22 @@ pub unsafe extern "C" fn PyInit_ormsgpack() -> *mut PyModuleDef {
             ml_flags: METH_FASTCALL | METH_KEYWORDS,
             ml_doc: PACKB_DOC.as_ptr(),
         },
+        PyMethodDef {
+            ml_name: c"packb_into".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunctionFastWithKeywords: packb_into,
+            },
+            ml_flags: METH_FASTCALL | METH_KEYWORDS,
+            ml_doc: PACKB_INTO_DOC.as_ptr(),
+        },
+        PyMethodDef {
+            ml_name: c"register_encoder".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunctionFastWithKeywords: register_encoder,
+            },
+            ml_flags: METH_FASTCALL | METH_KEYWORDS,
+            ml_doc: REGISTER_ENCODER_DOC.as_ptr(),
+        },
         PyMethodDef {
             ml_name: c"unpackb".as_ptr(),
             ml_meth: PyMethodDefPointer {
@@ -70,6 +119,14 @@ pub unsafe extern "C" fn PyInit_ormsgpack() -> *mut PyModuleDef {
             ml_flags: METH_FASTCALL | METH_KEYWORDS,
             ml_doc: UNPACKB_DOC.as_ptr(),
         },
+        PyMethodDef {
+            ml_name: c"unpackb_partial".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunctionFastWithKeywords: unpackb_partial,
+            },
+            ml_flags: METH_FASTCALL | METH_KEYWORDS,
+            ml_doc: UNPACKB_PARTIAL_DOC.as_ptr(),
+        },
         PyMethodDef::zeroed(),
     ]);
 
@@ -117,7 +174,7 @@ pub unsafe extern "C" fn ormsgpack_exec(mptr: *mut PyObject) -> c_int {
     PyDateTime_IMPORT();
 
     let state: *mut state::State = PyModule_GetState(mptr).cast();
-    *state = state::State::new();
+    *state = state::State::new(mptr);
 
     let version = env!("CARGO_PKG_VERSION");
     module_add_object!(
@@ -126,17 +183,69 @@ pub unsafe extern "C" fn ormsgpack_exec(mptr: *mut PyObject) -> c_int {
         PyUnicode_FromStringAndSize(version.as_ptr().cast::<c_char>(), version.len() as isize)
     );
     module_add_object!(mptr, c"Ext", (*state).ext_type.cast::<PyObject>());
+    module_add_object!(
+        mptr,
+        c"ExtRegistry",
+        (*state).ext_registry_type.cast::<PyObject>()
+    );
     module_add_object!(mptr, c"MsgpackDecodeError", (*state).MsgpackDecodeError);
+    module_add_object!(
+        mptr,
+        c"MsgpackDecodeErrorKind",
+        (*state).MsgpackDecodeErrorKind
+    );
     module_add_object!(mptr, c"MsgpackEncodeError", (*state).MsgpackEncodeError);
+    module_add_object!(
+        mptr,
+        c"MsgpackBufferTooSmallError",
+        (*state).MsgpackBufferTooSmallError
+    );
+    module_add_object!(
+        mptr,
+        c"MsgpackUnsupportedTypeError",
+        (*state).MsgpackUnsupportedTypeError
+    );
+    module_add_object!(
+        mptr,
+        c"MsgpackIntegerOverflowError",
+        (*state).MsgpackIntegerOverflowError
+    );
+    module_add_object!(
+        mptr,
+        c"MsgpackTruncatedError",
+        (*state).MsgpackTruncatedError
+    );
+    module_add_object!(
+        mptr,
+        c"MsgpackInvalidUtf8Error",
+        (*state).MsgpackInvalidUtf8Error
+    );
+    module_add_object!(mptr, c"MsgpackDepthError", (*state).MsgpackDepthError);
+    module_add_object!(mptr, c"MsgpackExtError", (*state).MsgpackExtError);
+    module_add_object!(mptr, c"MsgpackLimitError", (*state).MsgpackLimitError);
+    module_add_object!(mptr, c"Packer", (*state).packer_type.cast::<PyObject>());
+    module_add_object!(
+        mptr,
+        c"Timestamp",
+        (*state).timestamp_type.cast::<PyObject>()
+    );
+    module_add_object!(mptr, c"Unpacker", (*state).unpacker_type.cast::<PyObject>());
 
+    module_add_int!(mptr, c"OPT_BIG_INT_AS_BIN", opt::BIG_INT_AS_BIN);
+    module_add_int!(mptr, c"OPT_BIG_INT_AS_EXT", opt::BIG_INT_AS_EXT);
     module_add_int!(
         mptr,
         c"OPT_DATETIME_AS_TIMESTAMP_EXT",
         opt::DATETIME_AS_TIMESTAMP_EXT
     );
+    module_add_int!(mptr, c"OPT_DECIMAL_AS_EXT", opt::DECIMAL_AS_EXT);
+    module_add_int!(mptr, c"OPT_DECIMAL_AS_STR", opt::DECIMAL_AS_STR);
+    module_add_int!(mptr, c"OPT_KEEP_UNKNOWN_EXT", opt::KEEP_UNKNOWN_EXT);
     module_add_int!(mptr, c"OPT_NAIVE_UTC", opt::NAIVE_UTC);
+    module_add_int!(mptr, c"OPT_NATIVE_TIMESTAMP", opt::NATIVE_TIMESTAMP);
     module_add_int!(mptr, c"OPT_NON_STR_KEYS", opt::NON_STR_KEYS);
     module_add_int!(mptr, c"OPT_OMIT_MICROSECONDS", opt::OMIT_MICROSECONDS);
+    module_add_int!(mptr, c"OPT_PARSE_DATETIME", opt::PARSE_DATETIME);
     module_add_int!(mptr, c"OPT_PASSTHROUGH_BIG_INT", opt::PASSTHROUGH_BIG_INT);
     module_add_int!(
         mptr,
@@ -148,56 +257,34 @@ pub unsafe extern "C" fn ormsgpack_exec(mptr: *mut PyObject) -> c_int {
     module_add_int!(mptr, c"OPT_PASSTHROUGH_SUBCLASS", opt::PASSTHROUGH_SUBCLASS);
     module_add_int!(mptr, c"OPT_PASSTHROUGH_TUPLE", opt::PASSTHROUGH_TUPLE);
     module_add_int!(mptr, c"OPT_PASSTHROUGH_UUID", opt::PASSTHROUGH_UUID);
+    module_add_int!(mptr, c"OPT_REJECT_TRAILING_DATA", opt::REJECT_TRAILING_DATA);
     module_add_int!(mptr, c"OPT_REPLACE_SURROGATES", opt::REPLACE_SURROGATES);
+    module_add_int!(
+        mptr,
+        c"OPT_SERIALIZE_COLLECTIONS",
+        opt::SERIALIZE_COLLECTIONS
+    );
+    module_add_int!(mptr, c"OPT_SERIALIZE_NAMEDTUPLE", opt::SERIALIZE_NAMEDTUPLE);
     module_add_int!(mptr, c"OPT_SERIALIZE_NUMPY", opt::SERIALIZE_NUMPY);
     module_add_int!(mptr, c"OPT_SERIALIZE_PYDANTIC", opt::SERIALIZE_PYDANTIC);
+    module_add_int!(mptr, c"OPT_SERIALIZE_SET", opt::SERIALIZE_SET);
+    module_add_int!(mptr, c"OPT_SET_AS_EXT", opt::SET_AS_EXT);
     module_add_int!(mptr, c"OPT_SORT_KEYS", opt::SORT_KEYS);
+    module_add_int!(mptr, c"OPT_TIMEDELTA_AS_EXT", opt::TIMEDELTA_AS_EXT);
+    module_add_int!(mptr, c"OPT_TIMEDELTA_AS_ISO8601", opt::TIMEDELTA_AS_ISO8601);
+    module_add_int!(
+        mptr,
+        c"OPT_TIMEDELTA_AS_MICROSECONDS",
+        opt::TIMEDELTA_AS_MICROSECONDS
+    );
+    module_add_int!(mptr, c"OPT_TIMEDELTA_AS_SECONDS", opt::TIMEDELTA_AS_SECONDS);
+    module_add_int!(mptr, c"OPT_TIMESTAMP_AS_NANOS", opt::TIMESTAMP_AS_NANOS);
+    module_add_int!(mptr, c"OPT_TIMESTAMP_AS_SECONDS", opt::TIMESTAMP_AS_SECONDS);
     module_add_int!(mptr, c"OPT_UTC_Z", opt::UTC_Z);
 
     0
 }
 
-#[cold]
-#[inline(never)]
-fn raise_unpackb_exception(state: *mut state::State, msg: &str) -> *mut PyObject {
-    unsafe {
-        let err_msg =
-            PyUnicode_FromStringAndSize(msg.as_ptr().cast::<c_char>(), msg.len() as isize);
-        let args = PyTuple_New(1);
-        pytuple_set_item(args, 0, err_msg);
-        PyErr_SetObject((*state).MsgpackDecodeError, args);
-        Py_DECREF(args);
-    };
-    std::ptr::null_mut()
-}
-
-#[cold]
-#[inline(never)]
-fn raise_packb_exception(state: *mut state::State, msg: &str) -> *mut PyObject {
-    unsafe {
-        let err_msg =
-            PyUnicode_FromStringAndSize(msg.as_ptr().cast::<c_char>(), msg.len() as isize);
-        PyErr_SetObject((*state).MsgpackEncodeError, err_msg);
-        Py_DECREF(err_msg);
-    };
-    std::ptr::null_mut()
-}
-
-unsafe fn parse_option_arg(opts: *mut PyObject, mask: i32) -> Result<i32, ()> {
-    if Py_TYPE(opts) == &raw mut PyLong_Type {
-        let val = PyLong_AsLong(opts) as i32;
-        if val & !mask == 0 {
-            Ok(val)
-        } else {
-            Err(())
-        }
-    } else if opts == Py_None() {
-        Ok(0)
-    } else {
-        Err(())
-    }
-}
-
 #[no_mangle]
 pub unsafe extern "C" fn unpackb(
     module: *mut PyObject,
@@ -207,7 +294,13 @@ pub unsafe extern "C" fn unpackb(
 ) -> *mut PyObject {
     let state: *mut state::State = PyModule_GetState(module).cast();
     let mut ext_hook: Option<NonNull<PyObject>> = None;
+    let mut ext_registry: Option<NonNull<PyObject>> = None;
+    let mut tzinfo: Option<NonNull<PyObject>> = None;
+    let mut datetime_keys: Option<NonNull<PyObject>> = None;
     let mut optsptr: Option<NonNull<PyObject>> = None;
+    let mut typeptr: Option<NonNull<PyObject>> = None;
+    let mut modelptr: Option<NonNull<PyObject>> = None;
+    let mut limits = crate::deserialize::Limits::new();
 
     let num_args = PyVectorcall_NARGS(nargs as usize);
     if unlikely!(num_args != 1) {
@@ -223,29 +316,249 @@ pub unsafe extern "C" fn unpackb(
         for i in 0..tuple_size {
             let arg = pytuple_get_item(kwnames, i as Py_ssize_t);
             if PyUnicode_Compare(arg, (*state).ext_hook_str) == 0 {
-                ext_hook = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+                let hook = *args.offset(num_args + i);
+                if hook != Py_None() {
+                    ext_hook = Some(NonNull::new_unchecked(hook));
+                }
+            } else if PyUnicode_Compare(arg, (*state).datetime_keys_str) == 0 {
+                let keys = *args.offset(num_args + i);
+                if keys != Py_None() {
+                    datetime_keys = Some(NonNull::new_unchecked(keys));
+                }
+            } else if PyUnicode_Compare(arg, (*state).ext_registry_str) == 0 {
+                let registry = *args.offset(num_args + i);
+                if registry != Py_None() {
+                    if unlikely!(ob_type!(registry) != (*state).ext_registry_type) {
+                        return raise_type_error("ext_registry must be an ExtRegistry");
+                    }
+                    ext_registry = Some(NonNull::new_unchecked(registry));
+                }
             } else if PyUnicode_Compare(arg, (*state).option_str) == 0 {
                 optsptr = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).type_str) == 0 {
+                let annotation = *args.offset(num_args + i);
+                if annotation != Py_None() {
+                    typeptr = Some(NonNull::new_unchecked(annotation));
+                }
+            } else if PyUnicode_Compare(arg, (*state).model_str) == 0 {
+                let model = *args.offset(num_args + i);
+                if model != Py_None() {
+                    modelptr = Some(NonNull::new_unchecked(model));
+                }
+            } else if PyUnicode_Compare(arg, (*state).tzinfo_str) == 0 {
+                let value = *args.offset(num_args + i);
+                if value != Py_None() {
+                    if unlikely!(PyTZInfo_Check(value) == 0) {
+                        return raise_type_error("tzinfo must be a datetime.tzinfo");
+                    }
+                    tzinfo = Some(NonNull::new_unchecked(value));
+                }
             } else {
-                return raise_unpackb_exception(
-                    state,
-                    "unpackb() got an unexpected keyword argument",
-                );
+                match limits.parse_arg(state, arg, *args.offset(num_args + i)) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        return raise_unpackb_exception(
+                            state,
+                            "unpackb() got an unexpected keyword argument",
+                        )
+                    }
+                    Err(name) => return raise_unpackb_exception(state, &format!("Invalid {name}")),
+                }
             }
         }
     }
 
-    let mut optsbits: i32 = 0;
+    let mut optsbits: opt::Opt = 0;
     if let Some(opts) = optsptr {
         match parse_option_arg(opts.as_ptr(), opt::UNPACKB_OPT_MASK) {
             Ok(val) => optsbits = val,
             Err(()) => return raise_unpackb_exception(state, "Invalid opts"),
         }
     }
+    if unlikely!(tzinfo.is_some() && optsbits & opt::NAIVE_UTC != 0) {
+        return raise_unpackb_exception(
+            state,
+            "unpackb() accepts only one of tzinfo and OPT_NAIVE_UTC",
+        );
+    }
+    if unlikely!(datetime_keys.is_some() && optsbits & opt::PARSE_DATETIME == 0) {
+        return raise_unpackb_exception(state, "datetime_keys requires OPT_PARSE_DATETIME");
+    }
+
+    if let Some(model) = modelptr {
+        if unlikely!(typeptr.is_some()) {
+            return raise_unpackb_exception(state, "unpackb() accepts only one of type and model");
+        }
+        if unlikely!(!crate::deserialize::is_pydantic_model_class(
+            state,
+            model.as_ptr()
+        )) {
+            return raise_type_error("model must be a pydantic model class");
+        }
+        typeptr = modelptr;
+    }
+
+    let mut schema: *mut PyObject = std::ptr::null_mut();
+    if let Some(annotation) = typeptr {
+        match crate::deserialize::get_schema(state, annotation.as_ptr()) {
+            Ok(capsule) => schema = capsule,
+            Err(msg) => return raise_type_error(&msg),
+        }
+    }
+
+    if let Some(keys) = datetime_keys {
+        let frozenset = PyFrozenSet_New(keys.as_ptr());
+        if unlikely!(frozenset.is_null()) {
+            Py_XDECREF(schema);
+            return raise_type_error("datetime_keys must be an iterable");
+        }
+        datetime_keys = Some(NonNull::new_unchecked(frozenset));
+    }
 
-    match crate::deserialize::deserialize(*args, state, ext_hook, optsbits as opt::Opt) {
+    let res = crate::deserialize::deserialize(
+        *args,
+        state,
+        crate::deserialize::Config {
+            hook: ext_hook,
+            registry: ext_registry,
+            tzinfo: tzinfo,
+            datetime_keys: datetime_keys,
+        },
+        optsbits,
+        &limits,
+        if schema.is_null() {
+            None
+        } else {
+            Some(crate::deserialize::schema_from_capsule(schema))
+        },
+    );
+    Py_XDECREF(schema);
+    if let Some(keys) = datetime_keys {
+        Py_DECREF(keys.as_ptr());
+    }
+    match res {
         Ok(val) => val.as_ptr(),
-        Err(err) => raise_unpackb_exception(state, &err.message),
+        Err(err) => raise_deserialize_error(state, &err),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unpackb_partial(
+    module: *mut PyObject,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut state::State = PyModule_GetState(module).cast();
+    let mut offsetptr: Option<NonNull<PyObject>> = None;
+    let mut ext_hook: Option<NonNull<PyObject>> = None;
+    let mut ext_registry: Option<NonNull<PyObject>> = None;
+    let mut optsptr: Option<NonNull<PyObject>> = None;
+    let mut limits = crate::deserialize::Limits::new();
+
+    let num_args = PyVectorcall_NARGS(nargs as usize);
+    if unlikely!(num_args == 0) {
+        return raise_unpackb_exception(
+            state,
+            "unpackb_partial() missing 1 required positional argument: 'obj'",
+        );
+    }
+    if unlikely!(num_args > 2) {
+        return raise_unpackb_exception(
+            state,
+            "unpackb_partial() accepts at most 2 positional arguments",
+        );
+    }
+    if num_args == 2 {
+        offsetptr = Some(NonNull::new_unchecked(*args.offset(1)));
+    }
+    if !kwnames.is_null() {
+        let tuple_size = Py_SIZE(kwnames);
+        for i in 0..tuple_size {
+            let arg = pytuple_get_item(kwnames, i as Py_ssize_t);
+            if PyUnicode_Compare(arg, (*state).offset_str) == 0 {
+                if unlikely!(offsetptr.is_some()) {
+                    return raise_unpackb_exception(
+                        state,
+                        "unpackb_partial() got multiple values for argument: 'offset'",
+                    );
+                }
+                offsetptr = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).ext_hook_str) == 0 {
+                let hook = *args.offset(num_args + i);
+                if hook != Py_None() {
+                    ext_hook = Some(NonNull::new_unchecked(hook));
+                }
+            } else if PyUnicode_Compare(arg, (*state).ext_registry_str) == 0 {
+                let registry = *args.offset(num_args + i);
+                if registry != Py_None() {
+                    if unlikely!(ob_type!(registry) != (*state).ext_registry_type) {
+                        return raise_type_error("ext_registry must be an ExtRegistry");
+                    }
+                    ext_registry = Some(NonNull::new_unchecked(registry));
+                }
+            } else if PyUnicode_Compare(arg, (*state).option_str) == 0 {
+                optsptr = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else {
+                match limits.parse_arg(state, arg, *args.offset(num_args + i)) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        return raise_unpackb_exception(
+                            state,
+                            "unpackb_partial() got an unexpected keyword argument",
+                        )
+                    }
+                    Err(name) => return raise_unpackb_exception(state, &format!("Invalid {name}")),
+                }
+            }
+        }
+    }
+
+    let mut optsbits: opt::Opt = 0;
+    if let Some(opts) = optsptr {
+        match parse_option_arg(opts.as_ptr(), opt::UNPACKB_PARTIAL_OPT_MASK) {
+            Ok(val) => optsbits = val,
+            Err(()) => return raise_unpackb_exception(state, "Invalid opts"),
+        }
+    }
+
+    let contents = match crate::deserialize::input_as_bytes(*args) {
+        Ok(val) => val,
+        Err(err) => return raise_deserialize_error(state, &err),
+    };
+    let mut offset: usize = 0;
+    if let Some(value) = offsetptr {
+        if unlikely!(Py_TYPE(value.as_ptr()) != &raw mut PyLong_Type) {
+            return raise_unpackb_exception(state, "offset must be an int");
+        }
+        let val = PyLong_AsSsize_t(value.as_ptr());
+        if unlikely!(val < 0 || val as usize > contents.len()) {
+            PyErr_Clear();
+            return raise_unpackb_exception(state, "offset out of range");
+        }
+        offset = val as usize;
+    }
+
+    match crate::deserialize::deserialize_partial(
+        &contents[offset..],
+        state,
+        crate::deserialize::Config {
+            hook: ext_hook,
+            registry: ext_registry,
+            ..Default::default()
+        },
+        optsbits,
+        &limits,
+        None,
+    ) {
+        Ok((val, len)) => {
+            let end = PyLong_FromSize_t(offset + len);
+            let tuple = PyTuple_New(2);
+            PyTuple_SET_ITEM(tuple, 0, val.as_ptr());
+            PyTuple_SET_ITEM(tuple, 1, end);
+            tuple
+        }
+        Err(err) => raise_deserialize_error(state, &err.shift(offset)),
     }
 }
 
@@ -259,6 +572,7 @@ pub unsafe extern "C" fn packb(
     let state: *mut state::State = PyModule_GetState(module).cast();
     let mut default: Option<NonNull<PyObject>> = None;
     let mut optsptr: Option<NonNull<PyObject>> = None;
+    let mut ext_registry: Option<NonNull<PyObject>> = None;
 
     let num_args = PyVectorcall_NARGS(nargs as usize);
     if unlikely!(num_args == 0) {
@@ -293,13 +607,198 @@ pub unsafe extern "C" fn packb(
                     );
                 }
                 optsptr = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).ext_registry_str) == 0 {
+                let registry = *args.offset(num_args + i);
+                if registry != Py_None() {
+                    if unlikely!(ob_type!(registry) != (*state).ext_registry_type) {
+                        return raise_packb_exception(state, "ext_registry must be an ExtRegistry");
+                    }
+                    ext_registry = Some(NonNull::new_unchecked(registry));
+                }
             } else {
                 return raise_packb_exception(state, "packb() got an unexpected keyword argument");
             }
         }
     }
 
-    let mut optsbits: i32 = 0;
+    let mut optsbits: opt::Opt = 0;
+    if let Some(opts) = optsptr {
+        match parse_option_arg(opts.as_ptr(), opt::PACKB_OPT_MASK) {
+            Ok(val) => optsbits = val,
+            Err(()) => return raise_packb_exception(state, "Invalid opts"),
+        }
+    }
+
+    match crate::serialize::serialize(*args, state, default, ext_registry, optsbits) {
+        Ok(val) => val.as_ptr(),
+        Err(err) => raise_serialize_error(state, &err),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn packb_into(
+    module: *mut PyObject,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut state::State = PyModule_GetState(module).cast();
+    let mut offsetptr: Option<NonNull<PyObject>> = None;
+    let mut default: Option<NonNull<PyObject>> = None;
+    let mut optsptr: Option<NonNull<PyObject>> = None;
+    let mut ext_registry: Option<NonNull<PyObject>> = None;
+
+    let num_args = PyVectorcall_NARGS(nargs as usize);
+    if unlikely!(num_args < 2) {
+        let msg = if num_args == 0 {
+            "packb_into() missing 2 required positional arguments: 'obj' and 'buffer'"
+        } else {
+            "packb_into() missing 1 required positional argument: 'buffer'"
+        };
+        return raise_packb_exception(state, msg);
+    }
+    if unlikely!(num_args > 3) {
+        return raise_packb_exception(state, "packb_into() accepts at most 3 positional arguments");
+    }
+    if num_args == 3 {
+        offsetptr = Some(NonNull::new_unchecked(*args.offset(2)));
+    }
+    if !kwnames.is_null() {
+        let tuple_size = Py_SIZE(kwnames);
+        for i in 0..tuple_size {
+            let arg = pytuple_get_item(kwnames, i as Py_ssize_t);
+            if PyUnicode_Compare(arg, (*state).offset_str) == 0 {
+                if unlikely!(offsetptr.is_some()) {
+                    return raise_packb_exception(
+                        state,
+                        "packb_into() got multiple values for argument: 'offset'",
+                    );
+                }
+                offsetptr = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).default_str) == 0 {
+                default = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).option_str) == 0 {
+                optsptr = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).ext_registry_str) == 0 {
+                let registry = *args.offset(num_args + i);
+                if registry != Py_None() {
+                    if unlikely!(ob_type!(registry) != (*state).ext_registry_type) {
+                        return raise_packb_exception(state, "ext_registry must be an ExtRegistry");
+                    }
+                    ext_registry = Some(NonNull::new_unchecked(registry));
+                }
+            } else {
+                return raise_packb_exception(
+                    state,
+                    "packb_into() got an unexpected keyword argument",
+                );
+            }
+        }
+    }
+
+    let mut optsbits: opt::Opt = 0;
+    if let Some(opts) = optsptr {
+        match parse_option_arg(opts.as_ptr(), opt::PACKB_OPT_MASK) {
+            Ok(val) => optsbits = val,
+            Err(()) => return raise_packb_exception(state, "Invalid opts"),
+        }
+    }
+
+    let mut offset: usize = 0;
+    if let Some(value) = offsetptr {
+        if unlikely!(Py_TYPE(value.as_ptr()) != &raw mut PyLong_Type) {
+            return raise_packb_exception(state, "offset must be an int");
+        }
+        let val = PyLong_AsSsize_t(value.as_ptr());
+        if unlikely!(val < 0) {
+            PyErr_Clear();
+            return raise_packb_exception(state, "offset out of range");
+        }
+        offset = val as usize;
+    }
+
+    match crate::serialize::serialize_into_buffer(
+        *args,
+        *args.offset(1),
+        offset,
+        state,
+        default,
+        ext_registry,
+        optsbits,
+    ) {
+        Ok(len) => PyLong_FromSize_t(len),
+        Err(crate::serialize::BufferError::TooSmall(msg)) => {
+            raise_buffer_too_small_exception(state, msg)
+        }
+        Err(crate::serialize::BufferError::Invalid(err)) => raise_serialize_error(state, &err),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dump(
+    module: *mut PyObject,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut state::State = PyModule_GetState(module).cast();
+    let mut default: Option<NonNull<PyObject>> = None;
+    let mut optsptr: Option<NonNull<PyObject>> = None;
+    let mut ext_registry: Option<NonNull<PyObject>> = None;
+
+    let num_args = PyVectorcall_NARGS(nargs as usize);
+    if unlikely!(num_args < 2) {
+        let msg = if num_args == 0 {
+            "dump() missing 2 required positional arguments: 'obj' and 'fp'"
+        } else {
+            "dump() missing 1 required positional argument: 'fp'"
+        };
+        return raise_packb_exception(state, msg);
+    }
+    if unlikely!(num_args > 4) {
+        return raise_packb_exception(state, "dump() accepts at most 4 positional arguments");
+    }
+    if num_args >= 3 {
+        default = Some(NonNull::new_unchecked(*args.offset(2)));
+    }
+    if num_args >= 4 {
+        optsptr = Some(NonNull::new_unchecked(*args.offset(3)));
+    }
+    if !kwnames.is_null() {
+        let tuple_size = Py_SIZE(kwnames);
+        for i in 0..tuple_size {
+            let arg = pytuple_get_item(kwnames, i as Py_ssize_t);
+            if PyUnicode_Compare(arg, (*state).default_str) == 0 {
+                if unlikely!(default.is_some()) {
+                    return raise_packb_exception(
+                        state,
+                        "dump() got multiple values for argument: 'default'",
+                    );
+                }
+                default = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).option_str) == 0 {
+                if unlikely!(optsptr.is_some()) {
+                    return raise_packb_exception(
+                        state,
+                        "dump() got multiple values for argument: 'option'",
+                    );
+                }
+                optsptr = Some(NonNull::new_unchecked(*args.offset(num_args + i)));
+            } else if PyUnicode_Compare(arg, (*state).ext_registry_str) == 0 {
+                let registry = *args.offset(num_args + i);
+                if registry != Py_None() {
+                    if unlikely!(ob_type!(registry) != (*state).ext_registry_type) {
+                        return raise_packb_exception(state, "ext_registry must be an ExtRegistry");
+                    }
+                    ext_registry = Some(NonNull::new_unchecked(registry));
+                }
+            } else {
+                return raise_packb_exception(state, "dump() got an unexpected keyword argument");
+            }
+        }
+    }
+
+    let mut optsbits: opt::Opt = 0;
     if let Some(opts) = optsptr {
         match parse_option_arg(opts.as_ptr(), opt::PACKB_OPT_MASK) {
             Ok(val) => optsbits = val,
@@ -307,8 +806,212 @@ pub unsafe extern "C" fn packb(
         }
     }
 
-    match crate::serialize::serialize(*args, state, default, optsbits as opt::Opt) {
+    match crate::serialize::serialize_to_file(
+        *args,
+        *args.offset(1),
+        state,
+        default,
+        ext_registry,
+        optsbits,
+    ) {
+        Ok(()) => {
+            Py_INCREF(Py_None());
+            Py_None()
+        }
+        Err(None) => std::ptr::null_mut(),
+        Err(Some(err)) => raise_serialize_error(state, &err),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn register_encoder(
+    module: *mut PyObject,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut state::State = PyModule_GetState(module).cast();
+    let mut subclasses = false;
+
+    let num_args = PyVectorcall_NARGS(nargs as usize);
+    if unlikely!(num_args != 2) {
+        return raise_packb_exception(
+            state,
+            "register_encoder() takes exactly 2 positional arguments: 'cls' and 'encoder'",
+        );
+    }
+    if !kwnames.is_null() {
+        let tuple_size = Py_SIZE(kwnames);
+        for i in 0..tuple_size {
+            let arg = pytuple_get_item(kwnames, i as Py_ssize_t);
+            let value = *args.offset(num_args + i);
+            if PyUnicode_Compare(arg, (*state).subclasses_str) == 0 {
+                if unlikely!(PyBool_Check(value) == 0) {
+                    return raise_packb_exception(state, "subclasses must be a bool");
+                }
+                subclasses = value == Py_True();
+            } else {
+                return raise_packb_exception(
+                    state,
+                    "register_encoder() got an unexpected keyword argument",
+                );
+            }
+        }
+    }
+
+    let cls = *args;
+    let encoder = *args.offset(1);
+    if unlikely!(PyType_Check(cls) == 0) {
+        return raise_packb_exception(state, "cls must be a type");
+    }
+    if unlikely!(encoder != Py_None() && PyCallable_Check(encoder) == 0) {
+        return raise_packb_exception(state, "encoder must be a callable or None");
+    }
+    match crate::serialize::register_encoder(state, cls, encoder, subclasses) {
+        Ok(()) => {
+            Py_INCREF(Py_None());
+            Py_None()
+        }
+        Err(()) => std::ptr::null_mut(),
+    }
+}
+
+/// Parse the arguments of load() and load_iter(), which are the same as
+/// those of unpackb(), accepting the options in `mask`. The max_buffer_size
+/// argument is accepted only if `max_buffer_size` is set.
+unsafe fn parse_load_args(
+    state: *mut state::State,
+    name: &str,
+    mask: opt::Opt,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+    mut max_buffer_size: Option<&mut u64>,
+) -> Result<
+    (
+        crate::deserialize::Config,
+        opt::Opt,
+        crate::deserialize::Limits,
+    ),
+    *mut PyObject,
+> {
+    let mut ext_hook: Option<NonNull<PyObject>> = None;
+    let mut ext_registry: Option<NonNull<PyObject>> = None;
+    let mut optsbits: opt::Opt = 0;
+    let mut limits = crate::deserialize::Limits::new();
+
+    let num_args = PyVectorcall_NARGS(nargs as usize);
+    if unlikely!(num_args != 1) {
+        let msg = if num_args > 1 {
+            format!("{name}() accepts only 1 positional argument")
+        } else {
+            format!("{name}() missing 1 required positional argument: 'fp'")
+        };
+        return Err(raise_unpackb_exception(state, &msg));
+    }
+    if !kwnames.is_null() {
+        let tuple_size = Py_SIZE(kwnames);
+        for i in 0..tuple_size {
+            let arg = pytuple_get_item(kwnames, i as Py_ssize_t);
+            let value = *args.offset(num_args + i);
+            if PyUnicode_Compare(arg, (*state).ext_hook_str) == 0 {
+                if value != Py_None() {
+                    ext_hook = Some(NonNull::new_unchecked(value));
+                }
+            } else if PyUnicode_Compare(arg, (*state).ext_registry_str) == 0 {
+                if value != Py_None() {
+                    if unlikely!(ob_type!(value) != (*state).ext_registry_type) {
+                        return Err(raise_type_error("ext_registry must be an ExtRegistry"));
+                    }
+                    ext_registry = Some(NonNull::new_unchecked(value));
+                }
+            } else if PyUnicode_Compare(arg, (*state).option_str) == 0 {
+                match parse_option_arg(value, mask) {
+                    Ok(val) => optsbits = val,
+                    Err(()) => return Err(raise_unpackb_exception(state, "Invalid opts")),
+                }
+            } else if let Some(size) = max_buffer_size
+                .as_deref_mut()
+                .filter(|_| PyUnicode_Compare(arg, (*state).max_buffer_size_str) == 0)
+            {
+                match crate::deserialize::parse_size_arg(value) {
+                    Some(val) => *size = val,
+                    None => return Err(raise_unpackb_exception(state, "Invalid max_buffer_size")),
+                }
+            } else {
+                match limits.parse_arg(state, arg, value) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        return Err(raise_unpackb_exception(
+                            state,
+                            &format!("{name}() got an unexpected keyword argument"),
+                        ))
+                    }
+                    Err(limit) => {
+                        return Err(raise_unpackb_exception(state, &format!("Invalid {limit}")))
+                    }
+                }
+            }
+        }
+    }
+    Ok((
+        crate::deserialize::Config {
+            hook: ext_hook,
+            registry: ext_registry,
+            ..Default::default()
+        },
+        optsbits,
+        limits,
+    ))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn load(
+    module: *mut PyObject,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut state::State = PyModule_GetState(module).cast();
+    let (config, opts, limits) = match parse_load_args(
+        state,
+        "load",
+        opt::UNPACKB_OPT_MASK,
+        args,
+        nargs,
+        kwnames,
+        None,
+    ) {
+        Ok(val) => val,
+        Err(err) => return err,
+    };
+    match crate::deserialize::deserialize_file(*args, state, config, opts, &limits) {
         Ok(val) => val.as_ptr(),
-        Err(err) => raise_packb_exception(state, &err),
+        Err(None) => std::ptr::null_mut(),
+        Err(Some(err)) => raise_deserialize_error(state, &err),
     }
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn load_iter(
+    module: *mut PyObject,
+    args: *const *mut PyObject,
+    nargs: Py_ssize_t,
+    kwnames: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut state::State = PyModule_GetState(module).cast();
+    let mut max_buffer_size = crate::deserialize::MAX_BUFFER_SIZE;
+    let (config, opts, limits) = match parse_load_args(
+        state,
+        "load_iter",
+        opt::UNPACKB_PARTIAL_OPT_MASK,
+        args,
+        nargs,
+        kwnames,
+        Some(&mut max_buffer_size),
+    ) {
+        Ok(val) => val,
+        Err(err) => return err,
+    };
+    crate::deserialize::unpacker_from_file(*args, state, config, opts, limits, max_buffer_size)
+}
@@ -3,6 +3,33 @@
 use crate::io::WriteSlices;
 use crate::msgpack::marker::Marker;
 
+/// The extension type of the durations serialized with
+/// `OPT_TIMEDELTA_AS_EXT`.
+pub const TIMEDELTA_EXT_TAG: i8 = 127;
+
+/// The extension type of the decimals serialized with `OPT_DECIMAL_AS_EXT`.
+pub const DECIMAL_EXT_TAG: i8 = 126;
+
+/// The extension type of the integers serialized with `OPT_BIG_INT_AS_EXT`,
+/// whose data is a sign byte, 0 or 1 for negative integers, followed by the
+/// magnitude as a big-endian unsigned integer.
+pub const BIG_INT_EXT_TAG: i8 = 125;
+
+/// The extension types of the sets and frozensets serialized with
+/// `OPT_SET_AS_EXT`, whose data is an array of their elements.
+pub const SET_EXT_TAG: i8 = 124;
+pub const FROZENSET_EXT_TAG: i8 = 123;
+
+/// The kinds of decimal values, stored in bits 1 and 2 of the first byte of
+/// the extension data, after the sign in bit 0. The byte is followed by the
+/// exponent as a big-endian `i32` and the digits of the coefficient, or of
+/// the payload of a NaN, packed two per byte from the most significant, with
+/// a leading zero digit if their number is odd.
+pub const DECIMAL_FINITE: u8 = 0;
+pub const DECIMAL_INFINITY: u8 = 1;
+pub const DECIMAL_NAN: u8 = 2;
+pub const DECIMAL_SNAN: u8 = 3;
+
 pub fn write_ext<W>(writer: &mut W, value: &[u8], tag: i8) -> Result<(), std::io::Error>
 where
     W: WriteSlices,
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-pub type Opt = u16;
+use pyo3::ffi::*;
+
+pub type Opt = u64;
 
 pub const NAIVE_UTC: Opt = 1;
 pub const NON_STR_KEYS: Opt = 1 << 1;
@@ -18,6 +20,24 @@ pub const PASSTHROUGH_UUID: Opt = 1 << 12;
 pub const PASSTHROUGH_ENUM: Opt = 1 << 13;
 pub const DATETIME_AS_TIMESTAMP_EXT: Opt = 1 << 14;
 pub const REPLACE_SURROGATES: Opt = 1 << 15;
+pub const REJECT_TRAILING_DATA: Opt = 1 << 16;
+pub const KEEP_UNKNOWN_EXT: Opt = 1 << 17;
+pub const NATIVE_TIMESTAMP: Opt = 1 << 18;
+pub const TIMESTAMP_AS_NANOS: Opt = 1 << 19;
+pub const TIMESTAMP_AS_SECONDS: Opt = 1 << 20;
+pub const TIMEDELTA_AS_ISO8601: Opt = 1 << 21;
+pub const TIMEDELTA_AS_SECONDS: Opt = 1 << 22;
+pub const TIMEDELTA_AS_MICROSECONDS: Opt = 1 << 23;
+pub const TIMEDELTA_AS_EXT: Opt = 1 << 24;
+pub const PARSE_DATETIME: Opt = 1 << 25;
+pub const DECIMAL_AS_STR: Opt = 1 << 26;
+pub const DECIMAL_AS_EXT: Opt = 1 << 27;
+pub const BIG_INT_AS_EXT: Opt = 1 << 28;
+pub const BIG_INT_AS_BIN: Opt = 1 << 29;
+pub const SERIALIZE_SET: Opt = 1 << 30;
+pub const SET_AS_EXT: Opt = 1 << 31;
+pub const SERIALIZE_COLLECTIONS: Opt = 1 << 32;
+pub const SERIALIZE_NAMEDTUPLE: Opt = 1 << 33;
 
 pub const NOT_PASSTHROUGH: Opt = !(PASSTHROUGH_BIG_INT
     | PASSTHROUGH_DATACLASS
@@ -27,7 +47,27 @@ pub const NOT_PASSTHROUGH: Opt = !(PASSTHROUGH_BIG_INT
     | PASSTHROUGH_TUPLE
     | PASSTHROUGH_UUID);
 
-pub const PACKB_OPT_MASK: i32 = (DATETIME_AS_TIMESTAMP_EXT
+pub const BIG_INT_OPTS: Opt = BIG_INT_AS_BIN | BIG_INT_AS_EXT;
+
+pub const DECIMAL_OPTS: Opt = DECIMAL_AS_EXT | DECIMAL_AS_STR;
+
+pub const SET_OPTS: Opt = SERIALIZE_SET | SET_AS_EXT;
+
+pub const TIMESTAMP_OPTS: Opt =
+    DATETIME_AS_TIMESTAMP_EXT | NATIVE_TIMESTAMP | TIMESTAMP_AS_NANOS | TIMESTAMP_AS_SECONDS;
+
+/// The options selecting the type of the deserialized timestamp extension
+/// objects, instead of `datetime.datetime`.
+pub const TIMESTAMP_TYPE_OPTS: Opt = NATIVE_TIMESTAMP | TIMESTAMP_AS_NANOS | TIMESTAMP_AS_SECONDS;
+
+pub const TIMEDELTA_OPTS: Opt =
+    TIMEDELTA_AS_EXT | TIMEDELTA_AS_ISO8601 | TIMEDELTA_AS_MICROSECONDS | TIMEDELTA_AS_SECONDS;
+
+pub const PACKB_OPT_MASK: Opt = BIG_INT_AS_BIN
+    | BIG_INT_AS_EXT
+    | DATETIME_AS_TIMESTAMP_EXT
+    | DECIMAL_AS_EXT
+    | DECIMAL_AS_STR
     | NAIVE_UTC
     | NON_STR_KEYS
     | OMIT_MICROSECONDS
@@ -39,9 +79,60 @@ pub const PACKB_OPT_MASK: i32 = (DATETIME_AS_TIMESTAMP_EXT
     | PASSTHROUGH_TUPLE
     | PASSTHROUGH_UUID
     | REPLACE_SURROGATES
+    | SERIALIZE_COLLECTIONS
+    | SERIALIZE_NAMEDTUPLE
     | SERIALIZE_NUMPY
     | SERIALIZE_PYDANTIC
+    | SERIALIZE_SET
+    | SET_AS_EXT
     | SORT_KEYS
-    | UTC_Z) as i32;
+    | TIMEDELTA_AS_EXT
+    | TIMEDELTA_AS_ISO8601
+    | TIMEDELTA_AS_MICROSECONDS
+    | TIMEDELTA_AS_SECONDS
+    | UTC_Z;
+
+pub const UNPACKB_OPT_MASK: Opt = BIG_INT_AS_EXT
+    | DATETIME_AS_TIMESTAMP_EXT
+    | DECIMAL_AS_EXT
+    | KEEP_UNKNOWN_EXT
+    | NAIVE_UTC
+    | NATIVE_TIMESTAMP
+    | NON_STR_KEYS
+    | PARSE_DATETIME
+    | REJECT_TRAILING_DATA
+    | SET_AS_EXT
+    | TIMEDELTA_AS_EXT
+    | TIMESTAMP_AS_NANOS
+    | TIMESTAMP_AS_SECONDS;
+
+pub const UNPACKB_PARTIAL_OPT_MASK: Opt = UNPACKB_OPT_MASK & !REJECT_TRAILING_DATA;
+
+/// The groups of options of which at most one can be specified.
+const EXCLUSIVE_OPTS: [Opt; 5] = [
+    BIG_INT_OPTS,
+    DECIMAL_OPTS,
+    SET_OPTS,
+    TIMEDELTA_OPTS,
+    TIMESTAMP_TYPE_OPTS,
+];
 
-pub const UNPACKB_OPT_MASK: i32 = (DATETIME_AS_TIMESTAMP_EXT | NON_STR_KEYS) as i32;
+pub unsafe fn parse_option_arg(opts: *mut PyObject, mask: Opt) -> Result<Opt, ()> {
+    if Py_TYPE(opts) == &raw mut PyLong_Type {
+        let val = PyLong_AsLongLong(opts);
+        if val >= 0
+            && val as Opt & !mask == 0
+            && EXCLUSIVE_OPTS
+                .iter()
+                .all(|group| (val as Opt & group).count_ones() <= 1)
+        {
+            Ok(val as Opt)
+        } else {
+            Err(())
+        }
+    } else if opts == Py_None() {
+        Ok(0)
+    } else {
+        Err(())
+    }
+}
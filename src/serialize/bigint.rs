@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::msgpack::BIG_INT_EXT_TAG;
+use crate::opt::*;
+use serde::ser::{Serialize, Serializer};
+use serde_bytes::Bytes;
+
+/// An `int` instance outside of the 64-bit range, serialized as a sign byte
+/// followed by its magnitude in the format selected by the `OPT_BIG_INT_*`
+/// options.
+pub struct BigInt {
+    ptr: *mut pyo3::ffi::PyObject,
+    opts: Opt,
+}
+
+impl BigInt {
+    pub fn new(ptr: *mut pyo3::ffi::PyObject, opts: Opt) -> Self {
+        BigInt {
+            ptr: ptr,
+            opts: opts,
+        }
+    }
+
+    fn data(&self) -> Result<Vec<u8>, &'static str> {
+        unsafe {
+            // The integer overflows to the sign of its value.
+            let mut overflow = 0;
+            pyo3::ffi::PyLong_AsLongLongAndOverflow(self.ptr, &mut overflow);
+            let negative = overflow < 0;
+            // The absolute value of int is used, as a subclass may override
+            // __abs__.
+            let long_abs = (*pyo3::ffi::PyLong_Type.tp_as_number).nb_absolute.unwrap();
+            let magnitude = long_abs(self.ptr);
+            if unlikely!(magnitude.is_null()) {
+                pyo3::ffi::PyErr_Clear();
+                return Err("abs(int) failed");
+            }
+            let num_bits = pyo3::ffi::_PyLong_NumBits(magnitude);
+            if unlikely!(num_bits == usize::MAX) {
+                pyo3::ffi::PyErr_Clear();
+                pyo3::ffi::Py_DECREF(magnitude);
+                return Err("Integer is too large");
+            }
+            let len = num_bits.div_ceil(8);
+            let mut data = vec![0u8; 1 + len];
+            data[0] = u8::from(negative);
+            pyo3::ffi::_PyLong_AsByteArray(
+                magnitude.cast::<pyo3::ffi::PyLongObject>(),
+                data[1..].as_mut_ptr(),
+                len,
+                0, // little_endian
+                0, // is_signed
+            );
+            pyo3::ffi::Py_DECREF(magnitude);
+            Ok(data)
+        }
+    }
+}
+
+impl Serialize for BigInt {
+    #[inline(never)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let data = self.data().map_err(serde::ser::Error::custom)?;
+        if self.opts & BIG_INT_AS_EXT != 0 {
+            serializer.serialize_newtype_variant(
+                "",
+                BIG_INT_EXT_TAG as u8 as u32,
+                "",
+                Bytes::new(&data),
+            )
+        } else {
+            serializer.serialize_bytes(&data)
+        }
+    }
+}
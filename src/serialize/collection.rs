@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::exc::describe_raised_exception;
+use crate::ffi::pymappingproxy_mapping;
+use crate::opt::*;
+use crate::serialize::default::DefaultHook;
+use crate::serialize::dict::Dict;
+use crate::serialize::list::List;
+use crate::serialize::serializer::PyObject;
+use crate::state::State;
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+#[derive(Clone, Copy)]
+pub enum CollectionKind {
+    Mapping,
+    MappingProxy,
+    Sequence,
+    /// A dict view or a `collections.deque` instance, whose iteration fails
+    /// if it is modified.
+    Iterable,
+}
+
+/// Return how an object of a type that is not otherwise serializable is
+/// serialized with `OPT_SERIALIZE_COLLECTIONS`, if it is a collection.
+pub fn collection_kind(
+    ptr: *mut pyo3::ffi::PyObject,
+    ob_type: *mut pyo3::ffi::PyTypeObject,
+    state: *mut State,
+    opts: Opt,
+) -> Option<CollectionKind> {
+    unsafe {
+        if ob_type == &raw mut pyo3::ffi::PyDictProxy_Type {
+            return Some(CollectionKind::MappingProxy);
+        }
+        if ob_type == (*state).deque_type
+            || pyo3::ffi::PyType_IsSubtype(ob_type, &raw mut pyo3::ffi::PyDictKeys_Type) != 0
+            || pyo3::ffi::PyType_IsSubtype(ob_type, &raw mut pyo3::ffi::PyDictValues_Type) != 0
+            || pyo3::ffi::PyType_IsSubtype(ob_type, &raw mut pyo3::ffi::PyDictItems_Type) != 0
+        {
+            return Some(CollectionKind::Iterable);
+        }
+        // str, bytes, bytearray and UserString subclasses are sequences, but
+        // serializing them as arrays of their elements is never intended, and
+        // the elements of a UserString are UserString instances.
+        if pyo3::ffi::PyType_HasFeature(
+            ob_type,
+            pyo3::ffi::Py_TPFLAGS_UNICODE_SUBCLASS | pyo3::ffi::Py_TPFLAGS_BYTES_SUBCLASS,
+        ) != 0
+            || pyo3::ffi::PyByteArray_Check(ptr) != 0
+            || pyo3::ffi::PyType_IsSubtype(ob_type, (*state).user_string_type) != 0
+        {
+            return None;
+        }
+        if opts & PASSTHROUGH_SUBCLASS != 0
+            && pyo3::ffi::PyType_HasFeature(
+                ob_type,
+                pyo3::ffi::Py_TPFLAGS_LIST_SUBCLASS | pyo3::ffi::Py_TPFLAGS_DICT_SUBCLASS,
+            ) != 0
+        {
+            return None;
+        }
+        if opts & PASSTHROUGH_TUPLE != 0 && ob_type == &raw mut pyo3::ffi::PyTuple_Type {
+            return None;
+        }
+        if is_instance(ptr, (*state).mapping_type) {
+            Some(CollectionKind::Mapping)
+        } else if is_instance(ptr, (*state).sequence_type) {
+            Some(CollectionKind::Sequence)
+        } else {
+            None
+        }
+    }
+}
+
+unsafe fn is_instance(
+    ptr: *mut pyo3::ffi::PyObject,
+    ob_type: *mut pyo3::ffi::PyTypeObject,
+) -> bool {
+    match pyo3::ffi::PyObject_IsInstance(ptr, ob_type.cast::<pyo3::ffi::PyObject>()) {
+        1 => true,
+        0 => false,
+        _ => {
+            pyo3::ffi::PyErr_Clear();
+            false
+        }
+    }
+}
+
+/// An object implementing the `collections.abc.Mapping` protocol, serialized
+/// as a map, or an object implementing the `collections.abc.Sequence`
+/// protocol, a dict view or a `collections.deque` instance, serialized as an
+/// array. The `dict` of a `mappingproxy`, dict views and deques are
+/// serialized directly, and the other objects are first copied to a `dict`
+/// or a `list`, respectively.
+pub struct Collection<'a> {
+    ptr: *mut pyo3::ffi::PyObject,
+    kind: CollectionKind,
+    state: *mut State,
+    opts: Opt,
+    default: &'a DefaultHook,
+}
+
+impl<'a> Collection<'a> {
+    pub fn new(
+        ptr: *mut pyo3::ffi::PyObject,
+        kind: CollectionKind,
+        state: *mut State,
+        opts: Opt,
+        default: &'a DefaultHook,
+    ) -> Self {
+        Collection {
+            ptr: ptr,
+            kind: kind,
+            state: state,
+            opts: opts,
+            default: default,
+        }
+    }
+}
+
+/// Return the error for an exception raised while copying a collection,
+/// which is left raised to be chained as the cause.
+#[cold]
+fn iteration_failed<E: serde::ser::Error>(kind: &str) -> E {
+    E::custom(format!(
+        "{kind} iteration failed ({})",
+        describe_raised_exception()
+    ))
+}
+
+impl Collection<'_> {
+    unsafe fn serialize_mapping<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let dict = pyo3::ffi::PyDict_New();
+        if unlikely!(pyo3::ffi::PyDict_Merge(dict, self.ptr, 1) == -1) {
+            pyo3::ffi::Py_DECREF(dict);
+            return Err(iteration_failed("Mapping"));
+        }
+        let res = Dict::new(dict, self.state, self.opts, self.default).serialize(serializer);
+        pyo3::ffi::Py_DECREF(dict);
+        res
+    }
+
+    unsafe fn serialize_iterable<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = pyo3::ffi::PyObject_Size(self.ptr);
+        if unlikely!(len == -1) {
+            return Err(iteration_failed("Sequence"));
+        }
+        let iter = pyo3::ffi::PyObject_GetIter(self.ptr);
+        if unlikely!(iter.is_null()) {
+            return Err(iteration_failed("Sequence"));
+        }
+        let mut seq = match serializer.serialize_seq(Some(len as usize)) {
+            Ok(seq) => seq,
+            Err(err) => {
+                pyo3::ffi::Py_DECREF(iter);
+                return Err(err);
+            }
+        };
+        let mut count: usize = 0;
+        loop {
+            let item = pyo3::ffi::PyIter_Next(iter);
+            if item.is_null() {
+                break;
+            }
+            // The length is written first, so the iteration must not yield
+            // more elements.
+            let res = if unlikely!(count == len as usize) {
+                Err(serde::ser::Error::custom(SIZE_CHANGED))
+            } else {
+                seq.serialize_element(&PyObject::new(item, self.state, self.opts, self.default))
+            };
+            pyo3::ffi::Py_DECREF(item);
+            if let Err(err) = res {
+                self.default.push_error_index(count);
+                pyo3::ffi::Py_DECREF(iter);
+                return Err(err);
+            }
+            count += 1;
+        }
+        pyo3::ffi::Py_DECREF(iter);
+        if unlikely!(!pyo3::ffi::PyErr_Occurred().is_null()) {
+            return Err(iteration_failed("Sequence"));
+        }
+        if unlikely!(count != len as usize) {
+            return Err(serde::ser::Error::custom(SIZE_CHANGED));
+        }
+        seq.end()
+    }
+}
+
+const SIZE_CHANGED: &str = "Sequence changed size during iteration";
+
+impl Serialize for Collection<'_> {
+    #[inline(never)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        unsafe {
+            match self.kind {
+                CollectionKind::Mapping => self.serialize_mapping(serializer),
+                CollectionKind::MappingProxy => {
+                    let mapping = pymappingproxy_mapping(self.ptr);
+                    if !mapping.is_null() && pyo3::ffi::PyDict_CheckExact(mapping) != 0 {
+                        Dict::new(mapping, self.state, self.opts, self.default)
+                            .serialize(serializer)
+                    } else {
+                        self.serialize_mapping(serializer)
+                    }
+                }
+                CollectionKind::Iterable => self.serialize_iterable(serializer),
+                CollectionKind::Sequence => {
+                    let list = pyo3::ffi::PySequence_List(self.ptr);
+                    if unlikely!(list.is_null()) {
+                        return Err(iteration_failed("Sequence"));
+                    }
+                    let res =
+                        List::new(list, self.state, self.opts, self.default).serialize(serializer);
+                    pyo3::ffi::Py_DECREF(list);
+                    res
+                }
+            }
+        }
+    }
+}
@@ -110,7 +110,10 @@ impl Serialize for Dataclass<'_> {
         for (key, value) in items.iter() {
             let pyvalue = PyObject::new(*value, self.state, self.opts, self.default);
             map.serialize_key(key).unwrap();
-            map.serialize_value(&pyvalue)?
+            if let Err(err) = map.serialize_value(&pyvalue) {
+                self.default.push_error_str_key(key);
+                return Err(err);
+            }
         }
         map.end()
     }
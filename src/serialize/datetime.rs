@@ -244,7 +244,7 @@ impl Serialize for DateTime {
             DateTimeLike::write_timestamp(self, &mut cursor).unwrap();
             let len = cursor.position() as usize;
             let timestamp = &cursor.get_ref()[0..len];
-            serializer.serialize_newtype_variant("", 128, "", Bytes::new(timestamp))
+            serializer.serialize_newtype_variant("", 0xff, "", Bytes::new(timestamp))
         } else {
             DateTimeLike::write_rfc3339(self, &mut cursor, self.opts).unwrap();
             let len = cursor.position() as usize;
@@ -116,19 +116,32 @@ pub trait DateTimeLike: DateLike + TimeLike {
         W: std::io::Write,
     {
         let (seconds, nanoseconds) = self.timestamp();
-        if seconds >> 34 == 0 {
-            let value = (i64::from(nanoseconds) << 34) | seconds;
-            if value <= 4294967295 {
-                writer.write_all(&(value as u32).to_be_bytes())?;
-            } else {
-                writer.write_all(&(value as u64).to_be_bytes())?;
-            }
+        write_timestamp(writer, seconds, nanoseconds)
+    }
+}
+
+/// Write the data of a timestamp extension object in the smallest format
+/// that can represent it.
+pub fn write_timestamp<W>(
+    writer: &mut W,
+    seconds: i64,
+    nanoseconds: u32,
+) -> Result<(), std::io::Error>
+where
+    W: std::io::Write,
+{
+    if seconds >> 34 == 0 {
+        let value = (u64::from(nanoseconds) << 34) | seconds as u64;
+        if value >> 32 == 0 {
+            writer.write_all(&(value as u32).to_be_bytes())?;
         } else {
-            writer.write_all(&nanoseconds.to_be_bytes())?;
-            writer.write_all(&seconds.to_be_bytes())?;
+            writer.write_all(&value.to_be_bytes())?;
         }
-        Ok(())
+    } else {
+        writer.write_all(&nanoseconds.to_be_bytes())?;
+        writer.write_all(&seconds.to_be_bytes())?;
     }
+    Ok(())
 }
 
 pub struct NaiveDateTime {
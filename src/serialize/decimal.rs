@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::ffi::*;
+use crate::msgpack::*;
+use crate::opt::*;
+use crate::state::State;
+use serde::ser::{Serialize, Serializer};
+use serde_bytes::Bytes;
+
+/// A `decimal.Decimal` instance, serialized in the format selected by the
+/// `OPT_DECIMAL_*` options.
+pub struct Decimal {
+    ptr: *mut pyo3::ffi::PyObject,
+    state: *mut State,
+    opts: Opt,
+}
+
+impl Decimal {
+    pub fn new(ptr: *mut pyo3::ffi::PyObject, state: *mut State, opts: Opt) -> Self {
+        Decimal {
+            ptr: ptr,
+            state: state,
+            opts: opts,
+        }
+    }
+
+    /// Write the data of the extension object from the components returned
+    /// by `Decimal.as_tuple()`.
+    fn ext_data(&self) -> Result<Vec<u8>, &'static str> {
+        unsafe {
+            let tuple = pyo3::ffi::PyObject_CallMethodNoArgs(self.ptr, (*self.state).as_tuple_str);
+            if unlikely!(tuple.is_null()) {
+                pyo3::ffi::PyErr_Clear();
+                return Err("Decimal.as_tuple() failed");
+            }
+            let sign = pyo3::ffi::PyLong_AsLong(pytuple_get_item(tuple, 0)) as u8;
+            let digits = pytuple_get_item(tuple, 1);
+            let exponent = pytuple_get_item(tuple, 2);
+
+            let (kind, exponent) = if pyo3::ffi::PyLong_Check(exponent) != 0 {
+                let mut overflow = 0;
+                let value = pyo3::ffi::PyLong_AsLongLongAndOverflow(exponent, &mut overflow);
+                match i32::try_from(value) {
+                    Ok(value) if overflow == 0 => (DECIMAL_FINITE, value),
+                    _ => {
+                        pyo3::ffi::Py_DECREF(tuple);
+                        return Err("Decimal exponent out of range for OPT_DECIMAL_AS_EXT");
+                    }
+                }
+            } else {
+                let kind = match unicode_to_str(exponent) {
+                    Ok("F") => DECIMAL_INFINITY,
+                    Ok("n") => DECIMAL_NAN,
+                    _ => DECIMAL_SNAN,
+                };
+                (kind, 0)
+            };
+
+            let num_digits = if kind == DECIMAL_INFINITY {
+                0
+            } else {
+                pyo3::ffi::PyTuple_GET_SIZE(digits)
+            };
+            let mut data = Vec::with_capacity(5 + (num_digits as usize).div_ceil(2));
+            data.push((kind << 1) | sign);
+            data.extend_from_slice(&exponent.to_be_bytes());
+            let offset = num_digits % 2;
+            if offset != 0 {
+                data.push(0);
+            }
+            for i in 0..num_digits {
+                let digit = pyo3::ffi::PyLong_AsLong(pytuple_get_item(digits, i)) as u8;
+                if (i + offset) % 2 == 0 {
+                    data.push(digit << 4);
+                } else {
+                    *data.last_mut().unwrap() |= digit;
+                }
+            }
+            pyo3::ffi::Py_DECREF(tuple);
+            Ok(data)
+        }
+    }
+}
+
+impl Serialize for Decimal {
+    #[inline(never)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.opts & DECIMAL_AS_EXT != 0 {
+            match self.ext_data() {
+                Ok(data) => serializer.serialize_newtype_variant(
+                    "",
+                    DECIMAL_EXT_TAG as u8 as u32,
+                    "",
+                    Bytes::new(&data),
+                ),
+                Err(err) => Err(serde::ser::Error::custom(err)),
+            }
+        } else {
+            unsafe {
+                let value = pyo3::ffi::PyObject_Str(self.ptr);
+                if unlikely!(value.is_null()) {
+                    pyo3::ffi::PyErr_Clear();
+                    return Err(serde::ser::Error::custom("str(Decimal) failed"));
+                }
+                let res = match unicode_to_str(value) {
+                    Ok(uni) => serializer.serialize_str(uni),
+                    Err(err) => Err(serde::ser::Error::custom(err)),
+                };
+                pyo3::ffi::Py_DECREF(value);
+                res
+            }
+        }
+    }
+}
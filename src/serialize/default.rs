@@ -1,14 +1,26 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use crate::exc::{
+    describe_raised_exception, format_error_path, path_key_segment, path_str_key_segment,
+};
 use crate::ffi::*;
+use crate::serialize::encoder::lookup_encoder;
+use crate::serialize::error::ErrorKind;
 use crate::serialize::RECURSION_LIMIT;
+use crate::state::State;
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::ffi::CStr;
 use std::ptr::NonNull;
 
 pub enum Error {
     InvalidType(*mut pyo3::ffi::PyObject),
+    /// The default hook raised an exception other than the ones meaning that
+    /// the type is not supported, which is described in the message.
+    DefaultFailed(*mut pyo3::ffi::PyObject, String),
+    /// The encoder of the type raised an exception, which is described in
+    /// the message.
+    EncoderFailed(*mut pyo3::ffi::PyObject, String),
     RecursionLimitReached,
 }
 
@@ -20,21 +32,69 @@ impl std::fmt::Display for Error {
                 let name = unsafe { CStr::from_ptr((*ob_type!(ptr)).tp_name).to_string_lossy() };
                 write!(f, "Type is not msgpack serializable: {name}")
             }
+            Error::DefaultFailed(ptr, ref description) => {
+                let name = unsafe { CStr::from_ptr((*ob_type!(ptr)).tp_name).to_string_lossy() };
+                write!(
+                    f,
+                    "Type is not msgpack serializable: {name} (default raised {description})"
+                )
+            }
+            Error::EncoderFailed(ptr, ref description) => {
+                let name = unsafe { CStr::from_ptr((*ob_type!(ptr)).tp_name).to_string_lossy() };
+                write!(
+                    f,
+                    "Type is not msgpack serializable: {name} (encoder raised {description})"
+                )
+            }
             Error::RecursionLimitReached => f.write_str("Recursion limit for default hook reached"),
         }
     }
 }
 
+/// Return the error for an exception raised by the default hook. A
+/// `TypeError` or a `NotImplementedError` means that the type is not
+/// supported, and other exceptions are described in the message.
+#[cold]
+fn default_error(ptr: *mut pyo3::ffi::PyObject) -> Error {
+    unsafe {
+        if pyo3::ffi::PyErr_ExceptionMatches(pyo3::ffi::PyExc_TypeError) != 0
+            || pyo3::ffi::PyErr_ExceptionMatches(pyo3::ffi::PyExc_NotImplementedError) != 0
+        {
+            Error::InvalidType(ptr)
+        } else {
+            Error::DefaultFailed(ptr, describe_raised_exception())
+        }
+    }
+}
+
 pub struct DefaultHook {
     pub inner: Option<NonNull<pyo3::ffi::PyObject>>,
+    /// The encoders of a `Packer`, by exact type, which take precedence over
+    /// the registered encoders.
+    pub encoders: Option<NonNull<pyo3::ffi::PyObject>>,
+    /// The `ExtRegistry` of the classes serialized as extension types.
+    pub ext_registry: Option<NonNull<pyo3::ffi::PyObject>>,
     recursion: Cell<u8>,
+    /// The path segments of the object that failed to serialize, from the
+    /// innermost. They are kept here as the hook is shared by the
+    /// serializers of all the nested objects.
+    error_path: RefCell<Vec<String>>,
+    error_kind: Cell<Option<ErrorKind>>,
 }
 
 impl DefaultHook {
-    pub fn new(default: Option<NonNull<pyo3::ffi::PyObject>>) -> Self {
+    pub fn new(
+        default: Option<NonNull<pyo3::ffi::PyObject>>,
+        encoders: Option<NonNull<pyo3::ffi::PyObject>>,
+        ext_registry: Option<NonNull<pyo3::ffi::PyObject>>,
+    ) -> Self {
         DefaultHook {
             inner: default,
+            encoders: encoders,
+            ext_registry: ext_registry,
             recursion: Cell::new(0),
+            error_path: RefCell::new(Vec::new()),
+            error_kind: Cell::new(None),
         }
     }
 
@@ -48,15 +108,75 @@ impl DefaultHook {
                 if unlikely!(recursion == RECURSION_LIMIT) {
                     return Err(Error::RecursionLimitReached);
                 }
-                self.recursion.set(recursion + 1);
                 let default_obj = unsafe { pyobject_call_one_arg(callable.as_ptr(), ptr) };
                 if unlikely!(default_obj.is_null()) {
-                    Err(Error::InvalidType(ptr))
+                    let err = default_error(ptr);
+                    if let Error::InvalidType(_) = err {
+                        self.set_error_kind(ErrorKind::UnsupportedType);
+                    }
+                    Err(err)
                 } else {
+                    self.recursion.set(recursion + 1);
                     Ok(default_obj)
                 }
             }
-            None => Err(Error::InvalidType(ptr)),
+            None => {
+                self.set_error_kind(ErrorKind::UnsupportedType);
+                Err(Error::InvalidType(ptr))
+            }
+        }
+    }
+
+    /// Return a new reference to the encoder of `ob_type`, or null if there
+    /// is none.
+    #[inline(always)]
+    pub fn encoder(
+        &self,
+        ob_type: *mut pyo3::ffi::PyTypeObject,
+        state: *mut State,
+    ) -> *mut pyo3::ffi::PyObject {
+        if let Some(encoders) = self.encoders {
+            let mut encoder: *mut pyo3::ffi::PyObject = std::ptr::null_mut();
+            unsafe {
+                if pyo3::ffi::compat::PyDict_GetItemRef(
+                    encoders.as_ptr(),
+                    ob_type.cast(),
+                    &mut encoder,
+                ) < 0
+                {
+                    pyo3::ffi::PyErr_Clear();
+                }
+            }
+            if !encoder.is_null() {
+                return encoder;
+            }
+        }
+        lookup_encoder(state, ob_type)
+    }
+
+    /// Call `encoder` with `ptr`, like the default hook, and return the
+    /// object to serialize in its place.
+    pub fn enter_encoder_call(
+        &self,
+        ptr: *mut pyo3::ffi::PyObject,
+        encoder: *mut pyo3::ffi::PyObject,
+    ) -> Result<*mut pyo3::ffi::PyObject, Error> {
+        let recursion = self.recursion.get();
+        if unlikely!(recursion == RECURSION_LIMIT) {
+            return Err(Error::RecursionLimitReached);
+        }
+        let obj = unsafe {
+            // The encoder may be unregistered while it is called.
+            pyo3::ffi::Py_INCREF(encoder);
+            let obj = pyobject_call_one_arg(encoder, ptr);
+            pyo3::ffi::Py_DECREF(encoder);
+            obj
+        };
+        if unlikely!(obj.is_null()) {
+            Err(Error::EncoderFailed(ptr, describe_raised_exception()))
+        } else {
+            self.recursion.set(recursion + 1);
+            Ok(obj)
         }
     }
 
@@ -64,4 +184,40 @@ impl DefaultHook {
         let recursion = self.recursion.get();
         self.recursion.set(recursion - 1);
     }
+
+    /// Record the index of the failed element of a sequence.
+    #[cold]
+    pub fn push_error_index(&self, index: usize) {
+        self.error_path.borrow_mut().push(format!("[{index}]"));
+    }
+
+    /// Record the key of the failed value of a map.
+    #[cold]
+    pub fn push_error_key(&self, key: *mut pyo3::ffi::PyObject) {
+        self.error_path.borrow_mut().push(path_key_segment(key));
+    }
+
+    /// Record the str key of the failed value of a map.
+    #[cold]
+    pub fn push_error_str_key(&self, key: &str) {
+        self.error_path.borrow_mut().push(path_str_key_segment(key));
+    }
+
+    /// Record the kind of the error, which selects the exception type.
+    #[cold]
+    pub fn set_error_kind(&self, kind: ErrorKind) {
+        self.error_kind.set(Some(kind));
+    }
+
+    /// Return the kind of the error and reset it.
+    #[cold]
+    pub fn take_error_kind(&self) -> Option<ErrorKind> {
+        self.error_kind.take()
+    }
+
+    /// Return the path of the object that failed to serialize and reset it.
+    #[cold]
+    pub fn take_error_path(&self) -> String {
+        format_error_path(&self.error_path.take())
+    }
 }
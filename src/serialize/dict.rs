@@ -72,7 +72,10 @@ impl Dict<'_> {
             let key_as_str = unicode_to_str(key.as_ptr()).map_err(serde::ser::Error::custom)?;
             let pyvalue = PyObject::new(value.as_ptr(), self.state, self.opts, self.default);
             map.serialize_key(key_as_str).unwrap();
-            map.serialize_value(&pyvalue)?;
+            if let Err(err) = map.serialize_value(&pyvalue) {
+                self.default.push_error_key(key.as_ptr());
+                return Err(err);
+            }
         }
         map.end()
     }
@@ -99,7 +102,10 @@ impl Dict<'_> {
         for (key, val) in items.iter() {
             let pyvalue = PyObject::new(*val, self.state, self.opts, self.default);
             map.serialize_key(key).unwrap();
-            map.serialize_value(&pyvalue)?;
+            if let Err(err) = map.serialize_value(&pyvalue) {
+                self.default.push_error_str_key(key);
+                return Err(err);
+            }
         }
         map.end()
     }
@@ -115,15 +121,14 @@ impl Dict<'_> {
         for (key, value) in PyDictIter::from_pyobject(self.ptr) {
             if ob_type!(key.as_ptr()) == &raw mut pyo3::ffi::PyUnicode_Type {
                 let key_as_str = unicode_to_str(key.as_ptr()).map_err(serde::ser::Error::custom)?;
-                map.serialize_entry(
-                    key_as_str,
-                    &PyObject::new(value.as_ptr(), self.state, self.opts, self.default),
-                )?;
+                map.serialize_key(key_as_str)?;
             } else {
-                map.serialize_entry(
-                    &DictKey::new(key.as_ptr(), self.state, opts),
-                    &PyObject::new(value.as_ptr(), self.state, self.opts, self.default),
-                )?;
+                map.serialize_key(&DictKey::new(key.as_ptr(), self.state, opts, self.default))?;
+            }
+            let pyvalue = PyObject::new(value.as_ptr(), self.state, self.opts, self.default);
+            if let Err(err) = map.serialize_value(&pyvalue) {
+                self.default.push_error_key(key.as_ptr());
+                return Err(err);
             }
         }
         map.end()
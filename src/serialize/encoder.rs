@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::ffi::pydict_size;
+use crate::state::State;
+use pyo3::ffi::*;
+use std::ptr::null_mut;
+
+/// Register `encoder` for `cls` and its subclasses if `subclasses` is set, or
+/// unregister the encoder of `cls` if `encoder` is `None`. The encoders
+/// resolved for types are cached in `State`, so the cache is cleared.
+#[cold]
+pub unsafe fn register_encoder(
+    state: *mut State,
+    cls: *mut PyObject,
+    encoder: *mut PyObject,
+    subclasses: bool,
+) -> Result<(), ()> {
+    let res = if encoder == Py_None() {
+        if PyDict_Contains((*state).encoders, cls) == 1 {
+            PyDict_DelItem((*state).encoders, cls)
+        } else {
+            0
+        }
+    } else {
+        let entry = PyTuple_New(2);
+        PyTuple_SET_ITEM(entry, 0, Py_NewRef(encoder));
+        PyTuple_SET_ITEM(
+            entry,
+            1,
+            Py_NewRef(if subclasses { Py_True() } else { Py_False() }),
+        );
+        let res = PyDict_SetItem((*state).encoders, cls, entry);
+        Py_DECREF(entry);
+        res
+    };
+    (*state).encoder_cache.clear();
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(())
+    }
+}
+
+/// Return a new reference to the encoder registered for `ob_type`, or null
+/// if there is none.
+#[inline(always)]
+pub fn lookup_encoder(state: *mut State, ob_type: *mut PyTypeObject) -> *mut PyObject {
+    unsafe {
+        if pydict_size((*state).encoders) == 0 {
+            return null_mut();
+        }
+        let cached = (*state).encoder_cache.get(ob_type.cast::<PyObject>());
+        if cached.is_null() {
+            resolve_encoder(state, ob_type)
+        } else if cached == Py_None() {
+            Py_DECREF(cached);
+            null_mut()
+        } else {
+            cached
+        }
+    }
+}
+
+/// Find the encoder of `ob_type`, which is the encoder registered for the
+/// first class of its MRO that is either the type itself or registered with
+/// its subclasses, and cache it.
+#[cold]
+unsafe fn resolve_encoder(state: *mut State, ob_type: *mut PyTypeObject) -> *mut PyObject {
+    let mut encoder = Py_NewRef(Py_None());
+    let mro = (*ob_type).tp_mro;
+    if !mro.is_null() {
+        for i in 0..PyTuple_GET_SIZE(mro) {
+            let base = PyTuple_GET_ITEM(mro, i);
+            let mut entry: *mut PyObject = null_mut();
+            if compat::PyDict_GetItemRef((*state).encoders, base, &mut entry) < 0 {
+                PyErr_Clear();
+            }
+            if entry.is_null() {
+                continue;
+            }
+            let found =
+                base == ob_type.cast::<PyObject>() || PyTuple_GET_ITEM(entry, 1) == Py_True();
+            if found {
+                Py_DECREF(encoder);
+                encoder = Py_NewRef(PyTuple_GET_ITEM(entry, 0));
+            }
+            Py_DECREF(entry);
+            if found {
+                break;
+            }
+        }
+    }
+    (*state)
+        .encoder_cache
+        .set(ob_type.cast::<PyObject>(), encoder);
+    if encoder == Py_None() {
+        Py_DECREF(encoder);
+        null_mut()
+    } else {
+        encoder
+    }
+}
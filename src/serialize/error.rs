@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::state::State;
+use pyo3::ffi::PyObject;
+
+/// The kind of an encode error that is raised as a subclass of
+/// `MsgpackEncodeError`.
+#[derive(Debug, Clone, Copy)]
+pub enum ErrorKind {
+    IntegerOverflow,
+    UnsupportedType,
+}
+
+#[derive(Debug)]
+pub struct SerializeError {
+    pub message: String,
+    pub kind: Option<ErrorKind>,
+    /// The location of the object that failed to serialize, such as
+    /// `$.a[0]`, if the error occurred while serializing.
+    pub path: Option<String>,
+}
+
+impl SerializeError {
+    #[cold]
+    pub fn new(message: String) -> Self {
+        SerializeError {
+            message,
+            kind: None,
+            path: None,
+        }
+    }
+
+    /// Return the exception type to raise for the error.
+    #[cold]
+    pub fn exception(&self, state: *mut State) -> *mut PyObject {
+        unsafe {
+            match self.kind {
+                Some(ErrorKind::IntegerOverflow) => (*state).MsgpackIntegerOverflowError,
+                Some(ErrorKind::UnsupportedType) => (*state).MsgpackUnsupportedTypeError,
+                None => (*state).MsgpackEncodeError,
+            }
+        }
+    }
+}
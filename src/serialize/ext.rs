@@ -1,7 +1,14 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use crate::ext::PyExt;
+use crate::ext_registry::ExtEntry;
 use crate::ffi::pybytes_as_bytes;
+use crate::opt::Opt;
+use crate::serialize::datetimelike::write_timestamp;
+use crate::serialize::default::DefaultHook;
+use crate::serialize::serializer::{MessagePackSerializer, PyObject};
+use crate::state::State;
+use crate::timestamp::PyTimestamp;
 use serde::ser::{Serialize, Serializer};
 use serde_bytes::Bytes;
 
@@ -23,11 +30,110 @@ impl Serialize for Ext {
     {
         let ext = self.ptr.cast::<PyExt>();
         let tag = unsafe { pyo3::ffi::PyLong_AsLongLong((*ext).tag) };
-        if unlikely!(!(0..=127).contains(&tag)) {
+        let overflow = tag == -1 && !unsafe { pyo3::ffi::PyErr_Occurred() }.is_null();
+        if unlikely!(!(-128..=127).contains(&tag) || overflow) {
             return Err(serde::ser::Error::custom("Extension type out of range"));
         }
         let data = unsafe { pybytes_as_bytes((*ext).data) };
 
-        serializer.serialize_newtype_variant("", tag as u32, "", Bytes::new(data))
+        serializer.serialize_newtype_variant("", u32::from(tag as u8), "", Bytes::new(data))
+    }
+}
+
+#[repr(transparent)]
+pub struct Timestamp {
+    ptr: *mut pyo3::ffi::PyObject,
+}
+
+impl Timestamp {
+    pub fn new(ptr: *mut pyo3::ffi::PyObject) -> Self {
+        Timestamp { ptr: ptr }
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let timestamp = self.ptr.cast::<PyTimestamp>();
+        let mut cursor = std::io::Cursor::new([0u8; 12]);
+        unsafe { write_timestamp(&mut cursor, (*timestamp).seconds, (*timestamp).nanoseconds) }
+            .unwrap();
+        let len = cursor.position() as usize;
+        let data = &cursor.get_ref()[0..len];
+        serializer.serialize_newtype_variant("", 0xff, "", Bytes::new(data))
+    }
+}
+
+/// An instance of a class registered in an `ExtRegistry`, serialized as an
+/// extension type with the payload returned by `to_payload`.
+pub struct RegisteredExt<'a> {
+    ptr: *mut pyo3::ffi::PyObject,
+    entry: ExtEntry,
+    state: *mut State,
+    opts: Opt,
+    default: &'a DefaultHook,
+}
+
+impl<'a> RegisteredExt<'a> {
+    pub fn new(
+        ptr: *mut pyo3::ffi::PyObject,
+        entry: ExtEntry,
+        state: *mut State,
+        opts: Opt,
+        default: &'a DefaultHook,
+    ) -> Self {
+        RegisteredExt {
+            ptr: ptr,
+            entry: entry,
+            state: state,
+            opts: opts,
+            default: default,
+        }
+    }
+
+    fn serialize_payload<S>(
+        &self,
+        serializer: S,
+        payload: *mut pyo3::ffi::PyObject,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let tag = u32::from(self.entry.tag);
+        if self.entry.recursive {
+            let mut data: Vec<u8> = Vec::new();
+            {
+                let mut ser = MessagePackSerializer::new(&mut data);
+                PyObject::new(payload, self.state, self.opts, self.default)
+                    .serialize(&mut ser)
+                    .map_err(serde::ser::Error::custom)?;
+            }
+            serializer.serialize_newtype_variant("", tag, "", Bytes::new(&data))
+        } else if unsafe { pyo3::ffi::PyBytes_Check(payload) } != 0 {
+            let data = unsafe { pybytes_as_bytes(payload) };
+            serializer.serialize_newtype_variant("", tag, "", Bytes::new(data))
+        } else {
+            Err(serde::ser::Error::custom(format!(
+                "to_payload of extension type {tag} must return bytes"
+            )))
+        }
+    }
+}
+
+impl Serialize for RegisteredExt<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let payload = self
+            .default
+            .enter_encoder_call(self.ptr, self.entry.to_payload)
+            .map_err(serde::ser::Error::custom)?;
+        let res = self.serialize_payload(serializer, payload);
+        self.default.leave_call();
+        unsafe { pyo3::ffi::Py_DECREF(payload) };
+        res
     }
 }
@@ -43,7 +43,10 @@ impl Serialize for List<'_> {
         for i in 0..len {
             let item = unsafe { pyo3::ffi::PyList_GET_ITEM(self.ptr, i as isize) };
             let value = PyObject::new(item, self.state, self.opts, self.default);
-            seq.serialize_element(&value)?;
+            if let Err(err) = seq.serialize_element(&value) {
+                self.default.push_error_index(i);
+                return Err(err);
+            }
         }
         seq.end()
     }
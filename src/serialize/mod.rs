@@ -1,23 +1,35 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+mod bigint;
 mod bytearray;
 mod bytes;
+mod collection;
 mod dataclass;
 mod datetime;
 mod datetimelike;
+mod decimal;
 mod default;
 mod dict;
+mod encoder;
+mod error;
 mod ext;
 mod list;
 mod memoryview;
+mod namedtuple;
 mod numpy;
+mod packer;
 mod pydantic;
 mod serializer;
+mod set;
 mod str;
+mod timedelta;
 mod tuple;
 mod uuid;
 mod writer;
 
-pub use serializer::serialize;
+pub use encoder::register_encoder;
+pub use error::SerializeError;
+pub use packer::create_packer_type;
+pub use serializer::{serialize, serialize_into_buffer, serialize_to_file, BufferError};
 
 const RECURSION_LIMIT: u8 = 255;
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::ffi::*;
+use crate::opt::*;
+use crate::serialize::default::DefaultHook;
+use crate::serialize::serializer::*;
+use crate::state::State;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Return a new reference to the `_fields` of `ob_type`, a tuple subclass,
+/// or null if it is not a named tuple type. The `_fields` are cached by type
+/// when first looked up, so reassigning them is not supported.
+#[inline(always)]
+pub fn lookup_namedtuple_fields(
+    state: *mut State,
+    ob_type: *mut pyo3::ffi::PyTypeObject,
+) -> *mut pyo3::ffi::PyObject {
+    unsafe {
+        let mut fields = (*state)
+            .namedtuple_fields_cache
+            .get(ob_type.cast::<pyo3::ffi::PyObject>());
+        if fields.is_null() {
+            fields = resolve_namedtuple_fields(state, ob_type);
+            (*state)
+                .namedtuple_fields_cache
+                .set(ob_type.cast::<pyo3::ffi::PyObject>(), fields);
+        }
+        if fields == pyo3::ffi::Py_None() {
+            pyo3::ffi::Py_DECREF(fields);
+            std::ptr::null_mut()
+        } else {
+            fields
+        }
+    }
+}
+
+/// Return a new reference to the `_fields` of `ob_type`, or to `None` if it
+/// has none or they are not a tuple of strings.
+unsafe fn resolve_namedtuple_fields(
+    state: *mut State,
+    ob_type: *mut pyo3::ffi::PyTypeObject,
+) -> *mut pyo3::ffi::PyObject {
+    let fields = pyo3::ffi::PyObject_GetAttr(
+        ob_type.cast::<pyo3::ffi::PyObject>(),
+        (*state).namedtuple_fields_str,
+    );
+    if fields.is_null() {
+        pyo3::ffi::PyErr_Clear();
+    } else if pyo3::ffi::PyTuple_CheckExact(fields) == 0
+        || (0..pyo3::ffi::PyTuple_GET_SIZE(fields))
+            .any(|i| pyo3::ffi::PyUnicode_Check(pyo3::ffi::PyTuple_GET_ITEM(fields, i)) == 0)
+    {
+        pyo3::ffi::Py_DECREF(fields);
+    } else {
+        return fields;
+    }
+    pyo3::ffi::Py_INCREF(pyo3::ffi::Py_None());
+    pyo3::ffi::Py_None()
+}
+
+/// A named tuple instance, serialized with `OPT_SERIALIZE_NAMEDTUPLE` as a
+/// map of its field names to its elements.
+pub struct NamedTuple<'a> {
+    ptr: *mut pyo3::ffi::PyObject,
+    fields: *mut pyo3::ffi::PyObject,
+    state: *mut State,
+    opts: Opt,
+    default: &'a DefaultHook,
+}
+
+impl<'a> NamedTuple<'a> {
+    pub fn new(
+        ptr: *mut pyo3::ffi::PyObject,
+        fields: *mut pyo3::ffi::PyObject,
+        state: *mut State,
+        opts: Opt,
+        default: &'a DefaultHook,
+    ) -> Self {
+        NamedTuple {
+            ptr: ptr,
+            fields: fields,
+            state: state,
+            opts: opts,
+            default: default,
+        }
+    }
+}
+
+impl Serialize for NamedTuple<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = unsafe { pyo3::ffi::PyTuple_GET_SIZE(self.ptr) };
+        if unlikely!(len != unsafe { pyo3::ffi::PyTuple_GET_SIZE(self.fields) }) {
+            return Err(serde::ser::Error::custom(
+                "Named tuple length does not match its _fields",
+            ));
+        }
+        let mut map = serializer.serialize_map(Some(len as usize))?;
+        for i in 0..len {
+            let key = unsafe { pyo3::ffi::PyTuple_GET_ITEM(self.fields, i) };
+            let key_as_str = unicode_to_str(key).map_err(serde::ser::Error::custom)?;
+            let item = unsafe { pyo3::ffi::PyTuple_GET_ITEM(self.ptr, i) };
+            let value = PyObject::new(item, self.state, self.opts, self.default);
+            map.serialize_key(key_as_str).unwrap();
+            if let Err(err) = map.serialize_value(&value) {
+                self.default.push_error_str_key(key_as_str);
+                return Err(err);
+            }
+        }
+        map.end()
+    }
+}
@@ -1,6 +1,7 @@
 use crate::ffi::*;
 use crate::opt::*;
 use crate::serialize::datetimelike::NaiveDateTime;
+use crate::serialize::timedelta::Duration;
 use crate::state::State;
 use chrono::{DateTime, NaiveDate};
 use pyo3::ffi::*;
@@ -42,6 +43,7 @@ enum ItemType {
     I16,
     I32,
     I64,
+    TIMEDELTA64(NumpyDatetimeUnit),
     U8,
     U16,
     U32,
@@ -53,6 +55,7 @@ impl ItemType {
         array: *mut PyArrayInterface,
         ptr: *mut PyObject,
         state: *mut State,
+        opts: Opt,
     ) -> Option<ItemType> {
         match unsafe { ((*array).typekind, (*array).itemsize) } {
             (098, 1) => Some(ItemType::BOOL),
@@ -67,6 +70,10 @@ impl ItemType {
             (105, 2) => Some(ItemType::I16),
             (105, 4) => Some(ItemType::I32),
             (105, 8) => Some(ItemType::I64),
+            (109, 8) if opts & TIMEDELTA_OPTS != 0 => {
+                let unit = NumpyDatetimeUnit::from_pyobject(ptr, state);
+                Some(ItemType::TIMEDELTA64(unit))
+            }
             (117, 1) => Some(ItemType::U8),
             (117, 2) => Some(ItemType::U16),
             (117, 4) => Some(ItemType::U32),
@@ -165,6 +172,16 @@ impl Serialize for NumpyArrayData {
                     seq.serialize_element(&each).unwrap();
                 }
             }
+            ItemType::TIMEDELTA64(unit) => {
+                let slice: &[i64] =
+                    unsafe { std::slice::from_raw_parts(self.data.cast::<i64>(), self.len) };
+                for &each in slice.iter() {
+                    let value = unit
+                        .duration(each, self.opts)
+                        .map_err(serde::ser::Error::custom)?;
+                    seq.serialize_element(&value)?;
+                }
+            }
             ItemType::U8 => {
                 let slice: &[u8] =
                     unsafe { std::slice::from_raw_parts(self.data.cast::<u8>(), self.len) };
@@ -254,7 +271,7 @@ impl NumpyArray {
                 pyo3::ffi::Py_DECREF(capsule);
                 return Err(PyArrayError::UnsupportedDataType);
             }
-            match ItemType::find(array, ptr, state) {
+            match ItemType::find(array, ptr, state, opts) {
                 None => {
                     pyo3::ffi::Py_DECREF(capsule);
                     Err(PyArrayError::UnsupportedDataType)
@@ -399,6 +416,22 @@ impl std::fmt::Display for NumpyDateTimeError {
     }
 }
 
+enum NumpyTimedeltaError {
+    UnsupportedUnit(NumpyDatetimeUnit),
+    Unrepresentable { unit: NumpyDatetimeUnit, val: i64 },
+}
+
+impl std::fmt::Display for NumpyTimedeltaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedUnit(unit) => write!(f, "unsupported numpy.timedelta64 unit: {unit}"),
+            Self::Unrepresentable { unit, val } => {
+                write!(f, "unrepresentable numpy.timedelta64: {val} {unit}")
+            }
+        }
+    }
+}
+
 impl NumpyDatetimeUnit {
     /// Create a `NumpyDatetimeUnit` from a pointer to a Python object holding a
     /// numpy array.
@@ -510,6 +543,40 @@ impl NumpyDatetimeUnit {
         }
         .map(|dt| NaiveDateTime { dt, opts })
     }
+
+    /// Return a `Duration` for a value in a timedelta64 array with this unit.
+    ///
+    /// Returns an `Err(NumpyTimedeltaError)` if the value is NaT or the unit
+    /// has no fixed length.
+    fn duration(&self, val: i64, opts: Opt) -> Result<Duration, NumpyTimedeltaError> {
+        if val == i64::MIN {
+            return Err(NumpyTimedeltaError::Unrepresentable { unit: *self, val });
+        }
+        let (seconds, nanoseconds) = match self {
+            Self::Weeks => (val.checked_mul(7 * 24 * 60 * 60), 0),
+            Self::Days => (val.checked_mul(24 * 60 * 60), 0),
+            Self::Hours => (val.checked_mul(60 * 60), 0),
+            Self::Minutes => (val.checked_mul(60), 0),
+            Self::Seconds => (Some(val), 0),
+            Self::Milliseconds => (
+                Some(val.div_euclid(1_000)),
+                val.rem_euclid(1_000) as u32 * 1_000_000,
+            ),
+            Self::Microseconds => (
+                Some(val.div_euclid(1_000_000)),
+                val.rem_euclid(1_000_000) as u32 * 1_000,
+            ),
+            Self::Nanoseconds => (
+                Some(val.div_euclid(1_000_000_000)),
+                val.rem_euclid(1_000_000_000) as u32,
+            ),
+            _ => return Err(NumpyTimedeltaError::UnsupportedUnit(*self)),
+        };
+        match seconds {
+            Some(seconds) => Ok(Duration::new(seconds, nanoseconds, opts)),
+            None => Err(NumpyTimedeltaError::Unrepresentable { unit: *self, val }),
+        }
+    }
 }
 
 macro_rules! define_numpy_type {
@@ -586,6 +653,33 @@ impl Serialize for NumpyDatetime64 {
     }
 }
 
+pub struct NumpyTimedelta64 {
+    ptr: *mut PyObject,
+    state: *mut State,
+    opts: Opt,
+}
+
+impl NumpyTimedelta64 {
+    pub fn new(ptr: *mut PyObject, state: *mut State, opts: Opt) -> Self {
+        NumpyTimedelta64 { ptr, state, opts }
+    }
+}
+
+impl Serialize for NumpyTimedelta64 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let unit = NumpyDatetimeUnit::from_pyobject(self.ptr, self.state);
+        // numpy.timedelta64 instances have the layout of numpy.datetime64
+        // instances.
+        let value = unsafe { (*self.ptr.cast::<NumpyDatetime64Object>()).value };
+        unit.duration(value, self.opts)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
 #[repr(C)]
 struct NumpyFloat16Object {
     ob_base: PyObject,
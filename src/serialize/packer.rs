@@ -0,0 +1,289 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::exc::*;
+use crate::ffi::*;
+use crate::opt::*;
+use crate::serialize::default::DefaultHook;
+use crate::serialize::serializer::serialize_into;
+use crate::state::State;
+use pyo3::ffi::*;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::ptr::{null_mut, NonNull};
+
+const PACKER_DOC: &CStr = c"Packer(default=None, option=None, *, encoders=None, ext_registry=None)\n--\n\nSerialize Python objects to MessagePack with fixed arguments.";
+const PACK_DOC: &CStr = c"pack(obj, /)\n--\n\nSerialize a Python object to MessagePack.";
+
+/// Capacity kept by the output buffer between calls.
+const RETAINED_CAPACITY: usize = 1024 * 1024;
+
+#[repr(C)]
+pub struct Packer {
+    ob_base: PyObject,
+    state: *mut State,
+    default_hook: DefaultHook,
+    opts: Opt,
+    buffer: Vec<u8>,
+    busy: bool,
+}
+
+/// Return whether `obj` is a dict of types to callables.
+#[cold]
+unsafe fn is_encoder_dict(obj: *mut PyObject) -> bool {
+    PyDict_Check(obj) != 0
+        && PyDictIter::from_pyobject(obj).all(|(key, value)| {
+            PyType_Check(key.as_ptr()) != 0 && PyCallable_Check(value.as_ptr()) != 0
+        })
+}
+
+#[no_mangle]
+unsafe extern "C" fn packer_new(
+    subtype: *mut PyTypeObject,
+    args: *mut PyObject,
+    kwds: *mut PyObject,
+) -> *mut PyObject {
+    let state: *mut State = PyType_GetModuleState(subtype).cast();
+    let mut default: Option<NonNull<PyObject>> = None;
+    let mut optsptr: Option<NonNull<PyObject>> = None;
+    let mut encoders: Option<NonNull<PyObject>> = None;
+    let mut ext_registry: Option<NonNull<PyObject>> = None;
+
+    let num_args = Py_SIZE(args);
+    if unlikely!(num_args > 2) {
+        return raise_packb_exception(state, "Packer() accepts at most 2 positional arguments");
+    }
+    if num_args >= 1 {
+        default = Some(NonNull::new_unchecked(pytuple_get_item(args, 0)));
+    }
+    if num_args >= 2 {
+        optsptr = Some(NonNull::new_unchecked(pytuple_get_item(args, 1)));
+    }
+    if !kwds.is_null() {
+        for (key, value) in PyDictIter::from_pyobject(kwds) {
+            if PyUnicode_Compare(key.as_ptr(), (*state).default_str) == 0 {
+                if unlikely!(default.is_some()) {
+                    return raise_packb_exception(
+                        state,
+                        "Packer() got multiple values for argument: 'default'",
+                    );
+                }
+                default = Some(value);
+            } else if PyUnicode_Compare(key.as_ptr(), (*state).option_str) == 0 {
+                if unlikely!(optsptr.is_some()) {
+                    return raise_packb_exception(
+                        state,
+                        "Packer() got multiple values for argument: 'option'",
+                    );
+                }
+                optsptr = Some(value);
+            } else if PyUnicode_Compare(key.as_ptr(), (*state).encoders_str) == 0 {
+                encoders = Some(value);
+            } else if PyUnicode_Compare(key.as_ptr(), (*state).ext_registry_str) == 0 {
+                if value.as_ptr() != Py_None() {
+                    if unlikely!(ob_type!(value.as_ptr()) != (*state).ext_registry_type) {
+                        return raise_packb_exception(state, "ext_registry must be an ExtRegistry");
+                    }
+                    ext_registry = Some(value);
+                }
+            } else {
+                return raise_packb_exception(state, "Packer() got an unexpected keyword argument");
+            }
+        }
+    }
+
+    let mut optsbits: Opt = 0;
+    if let Some(opts) = optsptr {
+        match parse_option_arg(opts.as_ptr(), PACKB_OPT_MASK) {
+            Ok(val) => optsbits = val,
+            Err(()) => return raise_packb_exception(state, "Invalid opts"),
+        }
+    }
+    let default = default.filter(|callable| callable.as_ptr() != Py_None());
+    let encoders = encoders.filter(|encoders| encoders.as_ptr() != Py_None());
+    if let Some(encoders) = encoders {
+        if unlikely!(!is_encoder_dict(encoders.as_ptr())) {
+            return raise_packb_exception(state, "encoders must be a dict of types to callables");
+        }
+    }
+
+    // The encoders are copied so that they cannot change between calls.
+    let encoders = match encoders {
+        Some(encoders) => {
+            let copy = PyDict_Copy(encoders.as_ptr());
+            if unlikely!(copy.is_null()) {
+                return null_mut();
+            }
+            Some(NonNull::new_unchecked(copy))
+        }
+        None => None,
+    };
+    let obj = (*subtype).tp_alloc.unwrap()(subtype, 0);
+    if unlikely!(obj.is_null()) {
+        if let Some(encoders) = encoders {
+            Py_DECREF(encoders.as_ptr());
+        }
+        return null_mut();
+    }
+    let packer = obj.cast::<Packer>();
+    if let Some(callable) = default {
+        Py_INCREF(callable.as_ptr());
+    }
+    if let Some(registry) = ext_registry {
+        Py_INCREF(registry.as_ptr());
+    }
+    (*packer).state = state;
+    std::ptr::write(
+        &raw mut (*packer).default_hook,
+        DefaultHook::new(default, encoders, ext_registry),
+    );
+    (*packer).opts = optsbits;
+    std::ptr::write(&raw mut (*packer).buffer, Vec::new());
+    (*packer).busy = false;
+    obj
+}
+
+#[no_mangle]
+unsafe extern "C" fn packer_dealloc(op: *mut PyObject) {
+    PyObject_GC_UnTrack(op.cast::<c_void>());
+    packer_clear(op);
+    let packer = op.cast::<Packer>();
+    std::ptr::drop_in_place(&raw mut (*packer).buffer);
+    let tp = ob_type!(op);
+    (*tp).tp_free.unwrap()(op.cast::<c_void>());
+    Py_DECREF(tp.cast::<PyObject>());
+}
+
+#[no_mangle]
+unsafe extern "C" fn packer_traverse(
+    op: *mut PyObject,
+    visit: visitproc,
+    arg: *mut c_void,
+) -> c_int {
+    let packer = op.cast::<Packer>();
+    let ret = visit_optional((*packer).default_hook.inner, visit, arg);
+    if ret != 0 {
+        return ret;
+    }
+    let ret = visit_optional((*packer).default_hook.encoders, visit, arg);
+    if ret != 0 {
+        return ret;
+    }
+    let ret = visit_optional((*packer).default_hook.ext_registry, visit, arg);
+    if ret != 0 {
+        return ret;
+    }
+    visit(ob_type!(op).cast::<PyObject>(), arg)
+}
+
+#[no_mangle]
+unsafe extern "C" fn packer_clear(op: *mut PyObject) -> c_int {
+    let packer = op.cast::<Packer>();
+    clear_optional(&mut (*packer).default_hook.inner);
+    clear_optional(&mut (*packer).default_hook.encoders);
+    clear_optional(&mut (*packer).default_hook.ext_registry);
+    0
+}
+
+#[inline]
+unsafe fn pack_buffer(
+    buffer: &mut Vec<u8>,
+    obj: *mut PyObject,
+    state: *mut State,
+    default_hook: &DefaultHook,
+    opts: Opt,
+) -> *mut PyObject {
+    buffer.clear();
+    match serialize_into(&mut *buffer, obj, state, default_hook, opts) {
+        Ok(()) => {
+            PyBytes_FromStringAndSize(buffer.as_ptr().cast::<c_char>(), buffer.len() as isize)
+        }
+        Err(err) => raise_serialize_error(state, &err),
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn packer_pack(op: *mut PyObject, obj: *mut PyObject) -> *mut PyObject {
+    let packer = op.cast::<Packer>();
+    let mut critical_section = CriticalSection::new();
+    critical_section.begin(op);
+    if unlikely!((*packer).busy) {
+        // The packer is used by default or by another thread, which owns
+        // the buffer until it returns.
+        let default_hook = DefaultHook::new(
+            (*packer).default_hook.inner,
+            (*packer).default_hook.encoders,
+            (*packer).default_hook.ext_registry,
+        );
+        let mut buffer = Vec::new();
+        return pack_buffer(
+            &mut buffer,
+            obj,
+            (*packer).state,
+            &default_hook,
+            (*packer).opts,
+        );
+    }
+    (*packer).busy = true;
+    let res = pack_buffer(
+        &mut (*packer).buffer,
+        obj,
+        (*packer).state,
+        &(*packer).default_hook,
+        (*packer).opts,
+    );
+    (*packer).buffer.shrink_to(RETAINED_CAPACITY);
+    (*packer).busy = false;
+    res
+}
+
+pub unsafe fn create_packer_type(module: *mut PyObject) -> *mut PyTypeObject {
+    let methods: Box<[PyMethodDef; 2]> = Box::new([
+        PyMethodDef {
+            ml_name: c"pack".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: packer_pack,
+            },
+            ml_flags: METH_O,
+            ml_doc: PACK_DOC.as_ptr(),
+        },
+        PyMethodDef::zeroed(),
+    ]);
+    let mut slots: [PyType_Slot; 7] = [
+        PyType_Slot {
+            slot: Py_tp_doc,
+            pfunc: PACKER_DOC.as_ptr() as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_new,
+            pfunc: packer_new as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_dealloc,
+            pfunc: packer_dealloc as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_traverse,
+            pfunc: packer_traverse as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_clear,
+            pfunc: packer_clear as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_methods,
+            pfunc: Box::into_raw(methods).cast::<c_void>(),
+        },
+        PyType_Slot {
+            slot: 0,
+            pfunc: null_mut(),
+        },
+    ];
+    let mut spec = PyType_Spec {
+        name: c"ormsgpack.Packer".as_ptr(),
+        basicsize: std::mem::size_of::<Packer>() as c_int,
+        itemsize: 0,
+        flags: (Py_TPFLAGS_DEFAULT | Py_TPFLAGS_HAVE_GC) as c_uint,
+        slots: slots.as_mut_ptr(),
+    };
+    PyType_FromModuleAndSpec(module, &mut spec, null_mut()).cast::<PyTypeObject>()
+}
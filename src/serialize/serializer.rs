@@ -1,22 +1,30 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use crate::exc::*;
+use crate::ext_registry::lookup_type;
 use crate::ffi::*;
 use crate::io::WriteSlices;
 use crate::msgpack;
 use crate::opt::*;
+use crate::serialize::bigint::*;
 use crate::serialize::bytearray::*;
 use crate::serialize::bytes::*;
+use crate::serialize::collection::*;
 use crate::serialize::dataclass::*;
 use crate::serialize::datetime::*;
+use crate::serialize::decimal::*;
 use crate::serialize::default::*;
 use crate::serialize::dict::*;
+use crate::serialize::error::{ErrorKind, SerializeError};
 use crate::serialize::ext::*;
 use crate::serialize::list::*;
 use crate::serialize::memoryview::*;
+use crate::serialize::namedtuple::*;
 use crate::serialize::numpy::*;
 use crate::serialize::pydantic::*;
+use crate::serialize::set::*;
 use crate::serialize::str::*;
+use crate::serialize::timedelta::*;
 use crate::serialize::tuple::*;
 use crate::serialize::uuid::*;
 use crate::serialize::writer::*;
@@ -453,10 +461,8 @@ where
     where
         T: ?Sized + Serialize,
     {
-        let tag: i8 = match variant_index {
-            128 => -1,
-            _ => variant_index.try_into().unwrap_or_else(|_| unreachable!()),
-        };
+        // The variant index is the extension type as an unsigned byte.
+        let tag = variant_index as u8 as i8;
         let mut ext_se = ExtSerializer::new(tag, &mut self.writer);
         value.serialize(&mut ext_se)
     }
@@ -536,22 +542,154 @@ pub fn serialize(
     ptr: *mut pyo3::ffi::PyObject,
     state: *mut State,
     default: Option<NonNull<pyo3::ffi::PyObject>>,
+    ext_registry: Option<NonNull<pyo3::ffi::PyObject>>,
     opts: Opt,
-) -> Result<NonNull<pyo3::ffi::PyObject>, String> {
+) -> Result<NonNull<pyo3::ffi::PyObject>, SerializeError> {
     let mut buf = BytesWriter::default();
-    let default_hook = DefaultHook::new(default);
-    let obj = PyObject::new(ptr, state, opts, &default_hook);
-    let mut ser = MessagePackSerializer::new(&mut buf);
-    let res = obj.serialize(&mut ser);
+    let default_hook = DefaultHook::new(default, None, ext_registry);
+    let res = serialize_into(&mut buf, ptr, state, &default_hook, opts);
     match res {
-        Ok(_) => Ok(buf.finish()),
+        Ok(()) => Ok(buf.finish()),
         Err(err) => {
             unsafe { pyo3::ffi::Py_DECREF(buf.finish().as_ptr()) };
-            Err(err.to_string())
+            Err(err)
+        }
+    }
+}
+
+const SCRATCH_BUFFER_LENGTH: usize = 1024;
+
+pub enum BufferError {
+    TooSmall(&'static str),
+    Invalid(SerializeError),
+}
+
+#[cold]
+fn offset_out_of_range() -> BufferError {
+    BufferError::Invalid(SerializeError::new("offset out of range".to_string()))
+}
+
+/// Serialize an object into a writable buffer at `offset` and return the
+/// number of bytes written. A bytearray is extended as needed.
+pub fn serialize_into_buffer(
+    ptr: *mut pyo3::ffi::PyObject,
+    buffer: *mut pyo3::ffi::PyObject,
+    offset: usize,
+    state: *mut State,
+    default: Option<NonNull<pyo3::ffi::PyObject>>,
+    ext_registry: Option<NonNull<pyo3::ffi::PyObject>>,
+    opts: Opt,
+) -> Result<usize, BufferError> {
+    let default_hook = DefaultHook::new(default, None, ext_registry);
+    unsafe {
+        let is_bytearray = pyo3::ffi::PyByteArray_Check(buffer) != 0;
+        if is_bytearray && offset > pyo3::ffi::PyByteArray_Size(buffer) as usize {
+            return Err(offset_out_of_range());
+        }
+        // The object is serialized to a temporary buffer and copied once, so
+        // that the buffer is left unchanged if an error occurs, and may be
+        // modified by `default` or be part of the object.
+        let mut buf: Vec<u8> = Vec::with_capacity(SCRATCH_BUFFER_LENGTH);
+        serialize_into(&mut buf, ptr, state, &default_hook, opts).map_err(BufferError::Invalid)?;
+        if is_bytearray {
+            let mut critical_section = CriticalSection::new();
+            critical_section.begin(buffer);
+            let size = pyo3::ffi::PyByteArray_Size(buffer) as usize;
+            if offset > size {
+                return Err(offset_out_of_range());
+            }
+            let end = offset + buf.len();
+            if end > size && pyo3::ffi::PyByteArray_Resize(buffer, end as isize) != 0 {
+                pyo3::ffi::PyErr_Clear();
+                return Err(BufferError::TooSmall("bytearray cannot be resized"));
+            }
+            std::ptr::copy_nonoverlapping(
+                buf.as_ptr(),
+                pyo3::ffi::PyByteArray_AsString(buffer)
+                    .cast::<u8>()
+                    .add(offset),
+                buf.len(),
+            );
+            Ok(buf.len())
+        } else {
+            let mut view: pyo3::ffi::Py_buffer = std::mem::zeroed();
+            if pyo3::ffi::PyObject_GetBuffer(buffer, &mut view, pyo3::ffi::PyBUF_WRITABLE) != 0 {
+                pyo3::ffi::PyErr_Clear();
+                return Err(BufferError::Invalid(SerializeError::new(
+                    "Buffer must be a bytearray or a writable C contiguous buffer".to_string(),
+                )));
+            }
+            let res = if offset > view.len as usize {
+                Err(offset_out_of_range())
+            } else if buf.len() > view.len as usize - offset {
+                Err(BufferError::TooSmall("buffer too small"))
+            } else {
+                std::ptr::copy_nonoverlapping(
+                    buf.as_ptr(),
+                    view.buf.cast::<u8>().add(offset),
+                    buf.len(),
+                );
+                Ok(buf.len())
+            };
+            pyo3::ffi::PyBuffer_Release(&mut view);
+            res
         }
     }
 }
 
+/// Serialize an object to a file object through its `write` method. An
+/// `Err(None)` result means that a Python exception raised by the file
+/// object is set.
+pub fn serialize_to_file(
+    ptr: *mut pyo3::ffi::PyObject,
+    fp: *mut pyo3::ffi::PyObject,
+    state: *mut State,
+    default: Option<NonNull<pyo3::ffi::PyObject>>,
+    ext_registry: Option<NonNull<pyo3::ffi::PyObject>>,
+    opts: Opt,
+) -> Result<(), Option<SerializeError>> {
+    let write = unsafe { pyo3::ffi::PyObject_GetAttr(fp, (*state).write_str) };
+    if write.is_null() {
+        return Err(None);
+    }
+    let default_hook = DefaultHook::new(default, None, ext_registry);
+    let mut writer = FileWriter::new(write);
+    let res = match serialize_into(&mut writer, ptr, state, &default_hook, opts) {
+        Ok(()) => writer.flush_buffer().map_err(|_| None),
+        Err(_) if writer.failed => Err(None),
+        Err(err) => Err(Some(err)),
+    };
+    unsafe { pyo3::ffi::Py_DECREF(write) };
+    res
+}
+
+pub fn serialize_into<W>(
+    writer: W,
+    ptr: *mut pyo3::ffi::PyObject,
+    state: *mut State,
+    default_hook: &DefaultHook,
+    opts: Opt,
+) -> Result<(), SerializeError>
+where
+    W: WriteSlices,
+{
+    let obj = PyObject::new(ptr, state, opts, default_hook);
+    let mut ser = MessagePackSerializer::new(writer);
+    obj.serialize(&mut ser).map_err(|err| {
+        let path = default_hook.take_error_path();
+        let message = if path == "$" {
+            err.to_string()
+        } else {
+            format!("{err} at {path}")
+        };
+        SerializeError {
+            message,
+            kind: default_hook.take_error_kind(),
+            path: Some(path),
+        }
+    })
+}
+
 #[inline(always)]
 fn is_subclass(op: *mut pyo3::ffi::PyTypeObject, feature: c_ulong) -> bool {
     unsafe { pyo3::ffi::PyType_HasFeature(op, feature) != 0 }
@@ -593,6 +731,24 @@ impl<'a> PyObject<'a> {
         res
     }
 
+    fn serialize_with_encoder<S>(
+        &self,
+        serializer: S,
+        encoder: *mut pyo3::ffi::PyObject,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let obj = self
+            .default
+            .enter_encoder_call(self.ptr, encoder)
+            .map_err(serde::ser::Error::custom)?;
+        let res = PyObject::new(obj, self.state, self.opts, self.default).serialize(serializer);
+        self.default.leave_call();
+        unsafe { pyo3::ffi::Py_DECREF(obj) };
+        res
+    }
+
     #[inline(never)]
     fn serialize_unlikely<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -600,6 +756,20 @@ impl<'a> PyObject<'a> {
     {
         let ob_type = ob_type!(self.ptr);
 
+        let encoder = self.default.encoder(ob_type, self.state);
+        if unlikely!(!encoder.is_null()) {
+            let res = self.serialize_with_encoder(serializer, encoder);
+            unsafe { pyo3::ffi::Py_DECREF(encoder) };
+            return res;
+        }
+
+        if let Some(registry) = self.default.ext_registry {
+            if let Some(entry) = unsafe { lookup_type(registry.as_ptr(), ob_type) } {
+                return RegisteredExt::new(self.ptr, entry, self.state, self.opts, self.default)
+                    .serialize(serializer);
+            }
+        }
+
         if self.opts & PASSTHROUGH_DATETIME == 0 {
             let datetime_api = unsafe { *pyo3::ffi::PyDateTimeAPI() };
             if ob_type == datetime_api.DateTimeType {
@@ -619,14 +789,44 @@ impl<'a> PyObject<'a> {
             }
         }
 
+        if self.opts & TIMEDELTA_OPTS != 0
+            && ob_type == unsafe { (*pyo3::ffi::PyDateTimeAPI()).DeltaType }
+        {
+            return Duration::from_timedelta(self.ptr, self.opts).serialize(serializer);
+        }
+
         if self.opts & PASSTHROUGH_TUPLE == 0 && ob_type == &raw mut pyo3::ffi::PyTuple_Type {
             return Tuple::new(self.ptr, self.state, self.opts, self.default).serialize(serializer);
         }
 
+        if self.opts & SERIALIZE_NAMEDTUPLE != 0
+            && ob_type != &raw mut pyo3::ffi::PyTuple_Type
+            && is_subclass(ob_type, pyo3::ffi::Py_TPFLAGS_TUPLE_SUBCLASS)
+        {
+            let fields = lookup_namedtuple_fields(self.state, ob_type);
+            if !fields.is_null() {
+                let res = NamedTuple::new(self.ptr, fields, self.state, self.opts, self.default)
+                    .serialize(serializer);
+                unsafe { pyo3::ffi::Py_DECREF(fields) };
+                return res;
+            }
+        }
+
+        if self.opts & SET_OPTS != 0
+            && (ob_type == &raw mut pyo3::ffi::PySet_Type
+                || ob_type == &raw mut pyo3::ffi::PyFrozenSet_Type)
+        {
+            return Set::new(self.ptr, self.state, self.opts, self.default).serialize(serializer);
+        }
+
         if self.opts & PASSTHROUGH_UUID == 0 && ob_type == unsafe { (*self.state).uuid_type } {
             return UUID::new(self.ptr, self.state).serialize(serializer);
         }
 
+        if self.opts & DECIMAL_OPTS != 0 && ob_type == unsafe { (*self.state).decimal_type } {
+            return Decimal::new(self.ptr, self.state, self.opts).serialize(serializer);
+        }
+
         if ob_type!(ob_type) == unsafe { (*self.state).enum_type } {
             if self.opts & PASSTHROUGH_ENUM == 0 {
                 let value =
@@ -649,7 +849,10 @@ impl<'a> PyObject<'a> {
                     Err(err) => {
                         if self.opts & PASSTHROUGH_BIG_INT != 0 {
                             return self.serialize_with_default_hook(serializer);
+                        } else if self.opts & BIG_INT_OPTS != 0 {
+                            return BigInt::new(self.ptr, self.opts).serialize(serializer);
                         } else {
+                            self.default.set_error_kind(ErrorKind::IntegerOverflow);
                             return Err(serde::ser::Error::custom(err));
                         }
                     }
@@ -669,6 +872,10 @@ impl<'a> PyObject<'a> {
             return Ext::new(self.ptr).serialize(serializer);
         }
 
+        if ob_type == unsafe { (*self.state).timestamp_type } {
+            return Timestamp::new(self.ptr).serialize(serializer);
+        }
+
         if self.opts & PASSTHROUGH_DATACLASS == 0 && is_dataclass(ob_type, self.state) {
             return Dataclass::new(self.ptr, self.state, self.opts, self.default)
                 .serialize(serializer);
@@ -688,6 +895,10 @@ impl<'a> PyObject<'a> {
                     return NumpyDatetime64::new(self.ptr, self.state, self.opts)
                         .serialize(serializer);
                 }
+                if self.opts & TIMEDELTA_OPTS != 0 && ob_type == numpy_types_ref.timedelta64 {
+                    return NumpyTimedelta64::new(self.ptr, self.state, self.opts)
+                        .serialize(serializer);
+                }
                 if ob_type == numpy_types_ref.float16 {
                     return NumpyFloat16::new(self.ptr).serialize(serializer);
                 }
@@ -745,6 +956,13 @@ impl<'a> PyObject<'a> {
             return MemoryView::new(self.ptr).serialize(serializer);
         }
 
+        if self.opts & SERIALIZE_COLLECTIONS != 0 {
+            if let Some(kind) = collection_kind(self.ptr, ob_type, self.state, self.opts) {
+                return Collection::new(self.ptr, kind, self.state, self.opts, self.default)
+                    .serialize(serializer);
+            }
+        }
+
         self.serialize_with_default_hook(serializer)
     }
 }
@@ -765,7 +983,10 @@ impl Serialize for PyObject<'_> {
                 Err(err) => {
                     if self.opts & PASSTHROUGH_BIG_INT != 0 {
                         self.serialize_with_default_hook(serializer)
+                    } else if self.opts & BIG_INT_OPTS != 0 {
+                        BigInt::new(self.ptr, self.opts).serialize(serializer)
                     } else {
+                        self.default.set_error_kind(ErrorKind::IntegerOverflow);
                         Err(serde::ser::Error::custom(err))
                     }
                 }
@@ -786,23 +1007,30 @@ impl Serialize for PyObject<'_> {
     }
 }
 
-pub struct DictTupleKey {
+pub struct DictTupleKey<'a> {
     ptr: *mut pyo3::ffi::PyObject,
     state: *mut State,
     opts: Opt,
+    default: &'a DefaultHook,
 }
 
-impl DictTupleKey {
-    pub fn new(ptr: *mut pyo3::ffi::PyObject, state: *mut State, opts: Opt) -> Self {
+impl<'a> DictTupleKey<'a> {
+    pub fn new(
+        ptr: *mut pyo3::ffi::PyObject,
+        state: *mut State,
+        opts: Opt,
+        default: &'a DefaultHook,
+    ) -> Self {
         DictTupleKey {
             ptr: ptr,
             state: state,
             opts: opts,
+            default: default,
         }
     }
 }
 
-impl Serialize for DictTupleKey {
+impl Serialize for DictTupleKey<'_> {
     #[inline(never)]
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -812,25 +1040,32 @@ impl Serialize for DictTupleKey {
         let mut seq = serializer.serialize_seq(Some(len))?;
         for i in 0..len {
             let item = unsafe { pytuple_get_item(self.ptr, i as isize) };
-            let value = DictKey::new(item, self.state, self.opts);
+            let value = DictKey::new(item, self.state, self.opts, self.default);
             seq.serialize_element(&value)?;
         }
         seq.end()
     }
 }
 
-pub struct DictKey {
+pub struct DictKey<'a> {
     ptr: *mut pyo3::ffi::PyObject,
     state: *mut State,
     opts: Opt,
+    default: &'a DefaultHook,
 }
 
-impl DictKey {
-    pub fn new(ptr: *mut pyo3::ffi::PyObject, state: *mut State, opts: Opt) -> Self {
+impl<'a> DictKey<'a> {
+    pub fn new(
+        ptr: *mut pyo3::ffi::PyObject,
+        state: *mut State,
+        opts: Opt,
+        default: &'a DefaultHook,
+    ) -> Self {
         DictKey {
             ptr: ptr,
             state: state,
             opts: opts,
+            default: default,
         }
     }
 
@@ -857,19 +1092,31 @@ impl DictKey {
                 Err(err) => return Err(serde::ser::Error::custom(err)),
             };
         }
+        if self.opts & TIMEDELTA_OPTS != 0 && ob_type == datetime_api.DeltaType {
+            return Duration::from_timedelta(self.ptr, self.opts).serialize(serializer);
+        }
 
         if ob_type == &raw mut pyo3::ffi::PyTuple_Type {
-            return DictTupleKey::new(self.ptr, self.state, self.opts).serialize(serializer);
+            return DictTupleKey::new(self.ptr, self.state, self.opts, self.default)
+                .serialize(serializer);
         }
 
         if ob_type == unsafe { (*self.state).uuid_type } {
             return UUID::new(self.ptr, self.state).serialize(serializer);
         }
 
+        if self.opts & SET_OPTS != 0 && ob_type == &raw mut pyo3::ffi::PyFrozenSet_Type {
+            return Set::new(self.ptr, self.state, self.opts, self.default).serialize(serializer);
+        }
+
+        if self.opts & DECIMAL_OPTS != 0 && ob_type == unsafe { (*self.state).decimal_type } {
+            return Decimal::new(self.ptr, self.state, self.opts).serialize(serializer);
+        }
+
         if ob_type!(ob_type) == unsafe { (*self.state).enum_type } {
             let value = unsafe { pyo3::ffi::PyObject_GetAttr(self.ptr, (*self.state).value_str) };
             unsafe { pyo3::ffi::Py_DECREF(value) };
-            return DictKey::new(value, self.state, self.opts).serialize(serializer);
+            return DictKey::new(value, self.state, self.opts, self.default).serialize(serializer);
         }
 
         if is_subclass(ob_type, pyo3::ffi::Py_TPFLAGS_UNICODE_SUBCLASS) {
@@ -878,7 +1125,13 @@ impl DictKey {
         if is_subclass(ob_type, pyo3::ffi::Py_TPFLAGS_LONG_SUBCLASS) {
             match Int::new(self.ptr) {
                 Ok(val) => return val.serialize(serializer),
-                Err(err) => return Err(serde::ser::Error::custom(err)),
+                Err(_) if self.opts & BIG_INT_OPTS != 0 => {
+                    return BigInt::new(self.ptr, self.opts).serialize(serializer);
+                }
+                Err(err) => {
+                    self.default.set_error_kind(ErrorKind::IntegerOverflow);
+                    return Err(serde::ser::Error::custom(err));
+                }
             }
         }
 
@@ -886,13 +1139,14 @@ impl DictKey {
             return MemoryView::new(self.ptr).serialize(serializer);
         }
 
+        self.default.set_error_kind(ErrorKind::UnsupportedType);
         Err(serde::ser::Error::custom(
             "Dict key must a type serializable with OPT_NON_STR_KEYS",
         ))
     }
 }
 
-impl Serialize for DictKey {
+impl Serialize for DictKey<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -905,7 +1159,13 @@ impl Serialize for DictKey {
         } else if ob_type == &raw mut pyo3::ffi::PyLong_Type {
             match Int::new(self.ptr) {
                 Ok(val) => val.serialize(serializer),
-                Err(err) => Err(serde::ser::Error::custom(err)),
+                Err(_) if self.opts & BIG_INT_OPTS != 0 => {
+                    BigInt::new(self.ptr, self.opts).serialize(serializer)
+                }
+                Err(err) => {
+                    self.default.set_error_kind(ErrorKind::IntegerOverflow);
+                    Err(serde::ser::Error::custom(err))
+                }
             }
         } else if ob_type == &raw mut pyo3::ffi::PyBool_Type {
             serializer.serialize_bool(unsafe { self.ptr == pyo3::ffi::Py_True() })
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::exc::describe_raised_exception;
+use crate::ffi::{CriticalSection, PySetIter};
+use crate::msgpack::{FROZENSET_EXT_TAG, SET_EXT_TAG};
+use crate::opt::*;
+use crate::serialize::default::DefaultHook;
+use crate::serialize::list::List;
+use crate::serialize::serializer::*;
+use crate::state::State;
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use serde_bytes::Bytes;
+
+/// A `set` or `frozenset` instance, serialized as an array of its elements,
+/// sorted if `OPT_SORT_KEYS` is specified, which requires them to be totally
+/// ordered, or as an extension object holding
+/// this array if `OPT_SET_AS_EXT` is specified.
+pub struct Set<'a> {
+    ptr: *mut pyo3::ffi::PyObject,
+    state: *mut State,
+    opts: Opt,
+    default: &'a DefaultHook,
+}
+
+impl<'a> Set<'a> {
+    pub fn new(
+        ptr: *mut pyo3::ffi::PyObject,
+        state: *mut State,
+        opts: Opt,
+        default: &'a DefaultHook,
+    ) -> Self {
+        Set {
+            ptr: ptr,
+            state: state,
+            opts: opts,
+            default: default,
+        }
+    }
+
+    fn serialize_elements<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.opts & SORT_KEYS != 0 {
+            return self.serialize_sorted_elements(serializer);
+        }
+        let mut critical_section = CriticalSection::new();
+        critical_section.begin(self.ptr);
+        let len = unsafe { pyo3::ffi::PySet_Size(self.ptr) } as usize;
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        for (i, item) in PySetIter::from_pyobject(self.ptr).enumerate() {
+            let value = PyObject::new(item.as_ptr(), self.state, self.opts, self.default);
+            if let Err(err) = seq.serialize_element(&value) {
+                self.default.push_error_index(i);
+                return Err(err);
+            }
+        }
+        seq.end()
+    }
+
+    #[inline(never)]
+    fn serialize_sorted_elements<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        unsafe {
+            let list = pyo3::ffi::PySequence_List(self.ptr);
+            if unlikely!(list.is_null() || pyo3::ffi::PyList_Sort(list) == -1) {
+                pyo3::ffi::Py_XDECREF(list);
+                // The exception is left raised to be chained as the cause.
+                return Err(serde::ser::Error::custom(format!(
+                    "Set elements must be orderable with OPT_SORT_KEYS ({})",
+                    describe_raised_exception()
+                )));
+            }
+            match is_strictly_increasing(list) {
+                Ok(true) => {}
+                Ok(false) => {
+                    pyo3::ffi::Py_DECREF(list);
+                    return Err(serde::ser::Error::custom(
+                        "Set elements must be totally ordered with OPT_SORT_KEYS",
+                    ));
+                }
+                Err(()) => {
+                    pyo3::ffi::Py_DECREF(list);
+                    return Err(serde::ser::Error::custom(format!(
+                        "Set elements must be orderable with OPT_SORT_KEYS ({})",
+                        describe_raised_exception()
+                    )));
+                }
+            }
+            let res = List::new(list, self.state, self.opts, self.default).serialize(serializer);
+            pyo3::ffi::Py_DECREF(list);
+            res
+        }
+    }
+}
+
+/// Return whether each element of a sorted list is less than the next one.
+/// Otherwise, some elements are not comparable, such as sets ordered by
+/// inclusion, and their sorted order depends on the iteration order.
+unsafe fn is_strictly_increasing(list: *mut pyo3::ffi::PyObject) -> Result<bool, ()> {
+    let len = pyo3::ffi::PyList_GET_SIZE(list);
+    for i in 1..len {
+        let res = pyo3::ffi::PyObject_RichCompareBool(
+            pyo3::ffi::PyList_GET_ITEM(list, i - 1),
+            pyo3::ffi::PyList_GET_ITEM(list, i),
+            pyo3::ffi::Py_LT,
+        );
+        if res == -1 {
+            return Err(());
+        }
+        if res == 0 {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+impl Serialize for Set<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.opts & SET_AS_EXT != 0 {
+            let tag = if ob_type!(self.ptr) == &raw mut pyo3::ffi::PySet_Type {
+                SET_EXT_TAG
+            } else {
+                FROZENSET_EXT_TAG
+            };
+            let mut data: Vec<u8> = Vec::new();
+            {
+                let mut ser = MessagePackSerializer::new(&mut data);
+                self.serialize_elements(&mut ser)
+                    .map_err(serde::ser::Error::custom)?;
+            }
+            serializer.serialize_newtype_variant("", tag as u8 as u32, "", Bytes::new(&data))
+        } else {
+            self.serialize_elements(serializer)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::msgpack::TIMEDELTA_EXT_TAG;
+use crate::opt::*;
+use serde::ser::{Serialize, Serializer};
+use serde_bytes::Bytes;
+use std::fmt::Write;
+
+const NANOSECONDS_PER_SECOND: u128 = 1_000_000_000;
+
+/// A duration as the number of seconds, rounded down, and the remaining
+/// nanoseconds, serialized in the format selected by the `OPT_TIMEDELTA_*`
+/// options.
+pub struct Duration {
+    seconds: i64,
+    nanoseconds: u32,
+    opts: Opt,
+}
+
+impl Duration {
+    pub fn new(seconds: i64, nanoseconds: u32, opts: Opt) -> Self {
+        Duration {
+            seconds: seconds,
+            nanoseconds: nanoseconds,
+            opts: opts,
+        }
+    }
+
+    /// Create the duration of a `datetime.timedelta` instance.
+    pub fn from_timedelta(ptr: *mut pyo3::ffi::PyObject, opts: Opt) -> Self {
+        let (days, seconds, microseconds) = unsafe {
+            (
+                pyo3::ffi::PyDateTime_DELTA_GET_DAYS(ptr),
+                pyo3::ffi::PyDateTime_DELTA_GET_SECONDS(ptr),
+                pyo3::ffi::PyDateTime_DELTA_GET_MICROSECONDS(ptr),
+            )
+        };
+        Duration::new(
+            i64::from(days) * 86400 + i64::from(seconds),
+            microseconds as u32 * 1000,
+            opts,
+        )
+    }
+
+    fn total_nanoseconds(&self) -> i128 {
+        i128::from(self.seconds) * NANOSECONDS_PER_SECOND as i128 + i128::from(self.nanoseconds)
+    }
+
+    /// Format the duration as an ISO 8601 duration with days, hours,
+    /// minutes and seconds, preceded by a minus sign if it is negative.
+    fn iso8601(&self) -> String {
+        let total = self.total_nanoseconds();
+        let abs = total.unsigned_abs();
+        let days = abs / (86400 * NANOSECONDS_PER_SECOND);
+        let rem = abs % (86400 * NANOSECONDS_PER_SECOND);
+        let hours = rem / (3600 * NANOSECONDS_PER_SECOND);
+        let minutes = rem / (60 * NANOSECONDS_PER_SECOND) % 60;
+        let seconds = rem / NANOSECONDS_PER_SECOND % 60;
+        let fraction = rem % NANOSECONDS_PER_SECOND;
+
+        let mut value = String::with_capacity(32);
+        if total < 0 {
+            value.push('-');
+        }
+        value.push('P');
+        if days != 0 {
+            write!(value, "{days}D").unwrap();
+        }
+        if rem != 0 || days == 0 {
+            value.push('T');
+            if hours != 0 {
+                write!(value, "{hours}H").unwrap();
+            }
+            if minutes != 0 {
+                write!(value, "{minutes}M").unwrap();
+            }
+            if seconds != 0 || fraction != 0 || rem == 0 {
+                write!(value, "{seconds}").unwrap();
+                if fraction != 0 {
+                    let digits = format!("{fraction:09}");
+                    write!(value, ".{}", digits.trim_end_matches('0')).unwrap();
+                }
+                value.push('S');
+            }
+        }
+        value
+    }
+}
+
+impl Serialize for Duration {
+    #[inline(never)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.opts & TIMEDELTA_AS_EXT != 0 {
+            let mut data = [0u8; 12];
+            data[..4].copy_from_slice(&self.nanoseconds.to_be_bytes());
+            data[4..].copy_from_slice(&self.seconds.to_be_bytes());
+            serializer.serialize_newtype_variant(
+                "",
+                TIMEDELTA_EXT_TAG as u8 as u32,
+                "",
+                Bytes::new(&data),
+            )
+        } else if self.opts & TIMEDELTA_AS_MICROSECONDS != 0 {
+            match i64::try_from(self.total_nanoseconds().div_euclid(1000)) {
+                Ok(value) => serializer.serialize_i64(value),
+                Err(_) => Err(serde::ser::Error::custom(
+                    "timedelta out of range for OPT_TIMEDELTA_AS_MICROSECONDS",
+                )),
+            }
+        } else if self.opts & TIMEDELTA_AS_SECONDS != 0 {
+            let total = self.total_nanoseconds();
+            // Dividing the microseconds gives the same value as
+            // `timedelta.total_seconds()` below 2**53 microseconds.
+            let value = if total % 1000 == 0 {
+                (total / 1000) as f64 / 1e6
+            } else {
+                total as f64 / 1e9
+            };
+            serializer.serialize_f64(value)
+        } else {
+            serializer.serialize_str(&self.iso8601())
+        }
+    }
+}
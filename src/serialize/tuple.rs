@@ -42,7 +42,10 @@ impl Serialize for Tuple<'_> {
         for i in 0..len {
             let item = unsafe { pytuple_get_item(self.ptr, i as isize) };
             let value = PyObject::new(item, self.state, self.opts, self.default);
-            seq.serialize_element(&value)?;
+            if let Err(err) = seq.serialize_element(&value) {
+                self.default.push_error_index(i);
+                return Err(err);
+            }
         }
         seq.end()
     }
@@ -3,6 +3,7 @@
 use crate::ffi::*;
 use crate::io::WriteSlices;
 use pyo3::ffi::*;
+use std::os::raw::c_char;
 use std::ptr::NonNull;
 
 const BUFFER_LENGTH: usize = 1024;
@@ -97,3 +98,90 @@ impl WriteSlices for BytesWriter {
         Ok(())
     }
 }
+
+const FILE_CHUNK_LENGTH: usize = 65536;
+
+/// A writer to a file object, which buffers small writes and calls its
+/// `write` method with chunks of at most 64 KiB, except for larger values.
+/// If `write` raises an exception, it is left set and `failed` is true.
+pub struct FileWriter {
+    write: *mut PyObject,
+    buffer: Vec<u8>,
+    pub failed: bool,
+}
+
+impl FileWriter {
+    pub fn new(write: *mut PyObject) -> Self {
+        FileWriter {
+            write: write,
+            buffer: Vec::with_capacity(FILE_CHUNK_LENGTH),
+            failed: false,
+        }
+    }
+
+    fn write_chunk(write: *mut PyObject, chunk: &[u8]) -> bool {
+        unsafe {
+            let bytes =
+                PyBytes_FromStringAndSize(chunk.as_ptr().cast::<c_char>(), chunk.len() as isize);
+            if bytes.is_null() {
+                return false;
+            }
+            let res = pyobject_call_one_arg(write, bytes);
+            Py_DECREF(bytes);
+            if res.is_null() {
+                return false;
+            }
+            Py_DECREF(res);
+        }
+        true
+    }
+
+    pub fn flush_buffer(&mut self) -> Result<(), std::io::Error> {
+        if !self.buffer.is_empty() {
+            if !Self::write_chunk(self.write, &self.buffer) {
+                self.failed = true;
+                return Err(std::io::Error::from(std::io::ErrorKind::Other));
+            }
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    fn insert_slices<const N: usize>(&mut self, bufs: [&[u8]; N]) -> Result<(), std::io::Error> {
+        for buf in bufs {
+            if self.buffer.len() + buf.len() > FILE_CHUNK_LENGTH {
+                self.flush_buffer()?;
+            }
+            if buf.len() >= FILE_CHUNK_LENGTH {
+                if !Self::write_chunk(self.write, buf) {
+                    self.failed = true;
+                    return Err(std::io::Error::from(std::io::ErrorKind::Other));
+                }
+            } else {
+                self.buffer.extend_from_slice(buf);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::io::Write for FileWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        self.insert_slices([buf])?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), std::io::Error> {
+        self.insert_slices([buf])
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.flush_buffer()
+    }
+}
+
+impl WriteSlices for FileWriter {
+    fn write_slices<const N: usize>(&mut self, bufs: [&[u8]; N]) -> Result<(), std::io::Error> {
+        self.insert_slices(bufs)
+    }
+}
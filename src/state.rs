@@ -1,7 +1,11 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::deserialize::KeyMap;
+use crate::deserialize::{create_decode_error_kind, create_unpacker_type, KeyMap};
 use crate::ext::create_ext_type;
+use crate::ext_registry::create_ext_registry_type;
+use crate::ffi::WeakKeyDict;
+use crate::serialize::create_packer_type;
+use crate::timestamp::create_timestamp_type;
 use pyo3::ffi::*;
 use std::ffi::CStr;
 use std::ptr::null_mut;
@@ -22,6 +26,7 @@ pub struct NumpyTypes {
     pub uint8: *mut PyTypeObject,
     pub bool_: *mut PyTypeObject,
     pub datetime64: *mut PyTypeObject,
+    pub timedelta64: *mut PyTypeObject,
 }
 
 #[inline]
@@ -64,6 +69,7 @@ fn load_numpy_types() -> Option<NumpyTypes> {
             uint8: get_type(numpy_dict, c"uint8"),
             bool_: get_type(numpy_dict, c"bool_"),
             datetime64: get_type(numpy_dict, c"datetime64"),
+            timedelta64: get_type(numpy_dict, c"timedelta64"),
         };
         Py_DECREF(numpy_dict);
         Py_DECREF(numpy);
@@ -76,63 +82,212 @@ fn load_numpy_types() -> Option<NumpyTypes> {
 pub struct State {
     numpy_types: OnceLock<Option<NumpyTypes>>,
     pub dataclass_field_type: *mut PyTypeObject,
+    pub decimal_type: *mut PyTypeObject,
+    pub deque_type: *mut PyTypeObject,
     pub enum_type: *mut PyTypeObject,
     pub ext_type: *mut PyTypeObject,
+    pub ext_registry_type: *mut PyTypeObject,
+    pub mapping_type: *mut PyTypeObject,
+    pub packer_type: *mut PyTypeObject,
+    pub sequence_type: *mut PyTypeObject,
+    pub timestamp_type: *mut PyTypeObject,
+    pub unpacker_type: *mut PyTypeObject,
+    pub user_string_type: *mut PyTypeObject,
     pub uuid_type: *mut PyTypeObject,
     pub array_struct_str: *mut PyObject,
+    pub as_tuple_str: *mut PyObject,
+    pub astimezone_str: *mut PyObject,
     pub dataclass_fields_str: *mut PyObject,
+    pub datetime_keys_str: *mut PyObject,
     pub default_str: *mut PyObject,
     pub descr_str: *mut PyObject,
     pub dict_str: *mut PyObject,
     pub dtype_str: *mut PyObject,
+    pub encoders_str: *mut PyObject,
     pub ext_hook_str: *mut PyObject,
+    pub ext_registry_str: *mut PyObject,
     pub field_type_str: *mut PyObject,
     pub fields_str: *mut PyObject,
     pub int_str: *mut PyObject,
+    pub kind_str: *mut PyObject,
+    pub marker_str: *mut PyObject,
+    pub max_alloc_size_str: *mut PyObject,
+    pub max_array_len_str: *mut PyObject,
+    pub max_bin_len_str: *mut PyObject,
+    pub max_buffer_size_str: *mut PyObject,
+    pub max_depth_str: *mut PyObject,
+    pub max_ext_len_str: *mut PyObject,
+    pub max_map_len_str: *mut PyObject,
+    pub max_str_len_str: *mut PyObject,
+    pub model_str: *mut PyObject,
+    pub namedtuple_fields_str: *mut PyObject,
     pub normalize_str: *mut PyObject,
+    pub offset_str: *mut PyObject,
     pub option_str: *mut PyObject,
+    pub path_str: *mut PyObject,
+    pub pos_str: *mut PyObject,
     pub pydantic_extra_str: *mut PyObject,
+    pub pydantic_fields_str: *mut PyObject,
     pub pydantic_validator_str: *mut PyObject,
+    pub read_str: *mut PyObject,
     pub slots_str: *mut PyObject,
+    pub subclasses_str: *mut PyObject,
+    pub type_str: *mut PyObject,
+    pub tzinfo_str: *mut PyObject,
     pub utcoffset_str: *mut PyObject,
+    pub validate_python_str: *mut PyObject,
     pub value_str: *mut PyObject,
+    pub write_str: *mut PyObject,
     pub MsgpackEncodeError: *mut PyObject,
     pub MsgpackDecodeError: *mut PyObject,
+    pub MsgpackBufferTooSmallError: *mut PyObject,
+    pub MsgpackUnsupportedTypeError: *mut PyObject,
+    pub MsgpackIntegerOverflowError: *mut PyObject,
+    pub MsgpackTruncatedError: *mut PyObject,
+    pub MsgpackInvalidUtf8Error: *mut PyObject,
+    pub MsgpackDepthError: *mut PyObject,
+    pub MsgpackExtError: *mut PyObject,
+    pub MsgpackLimitError: *mut PyObject,
+    pub MsgpackDecodeErrorKind: *mut PyObject,
     pub key_map: KeyMap<512>,
+    /// The encoders registered with `register_encoder`, as tuples of the
+    /// encoder and whether it applies to subclasses, by class.
+    pub encoders: *mut PyObject,
+    /// The encoders resolved for the types serialized since the last
+    /// registration, or `None`, by type.
+    pub encoder_cache: WeakKeyDict,
+    /// The `_fields` of the tuple subclasses serialized with
+    /// `OPT_SERIALIZE_NAMEDTUPLE`, or `None` for the types that are not named
+    /// tuples.
+    pub namedtuple_fields_cache: WeakKeyDict,
+    /// The compiled schemas of the types passed to `unpackb`, by type.
+    pub schema_cache: WeakKeyDict,
+    /// The compiled schemas of the other annotations passed to `unpackb`,
+    /// such as `list[int]`, by annotation. The annotations are kept alive, so
+    /// it is cleared when it grows too large.
+    pub generic_schema_cache: *mut PyObject,
+    /// The compiled schemas of the classes in these types, by class.
+    pub class_schema_cache: WeakKeyDict,
 }
 
 impl State {
     #[cold]
-    pub fn new() -> Self {
+    pub fn new(module: *mut PyObject) -> Self {
         unsafe {
+            let encode_error = Py_NewRef(PyExc_TypeError);
+            let decode_error = Py_NewRef(PyExc_ValueError);
+            let limit_error = PyErr_NewException(
+                c"ormsgpack.MsgpackLimitError".as_ptr(),
+                decode_error,
+                null_mut(),
+            );
             Self {
                 numpy_types: OnceLock::new(),
                 dataclass_field_type: load_type(c"dataclasses", c"_FIELD"),
+                decimal_type: load_type(c"decimal", c"Decimal"),
+                deque_type: load_type(c"collections", c"deque"),
                 enum_type: load_type(c"enum", c"EnumMeta"),
                 ext_type: create_ext_type(),
+                ext_registry_type: create_ext_registry_type(module),
+                mapping_type: load_type(c"collections.abc", c"Mapping"),
+                packer_type: create_packer_type(module),
+                sequence_type: load_type(c"collections.abc", c"Sequence"),
+                timestamp_type: create_timestamp_type(module),
+                unpacker_type: create_unpacker_type(module),
+                user_string_type: load_type(c"collections", c"UserString"),
                 uuid_type: load_type(c"uuid", c"UUID"),
                 array_struct_str: PyUnicode_InternFromString(c"__array_struct__".as_ptr()),
+                as_tuple_str: PyUnicode_InternFromString(c"as_tuple".as_ptr()),
+                astimezone_str: PyUnicode_InternFromString(c"astimezone".as_ptr()),
                 dataclass_fields_str: PyUnicode_InternFromString(c"__dataclass_fields__".as_ptr()),
+                datetime_keys_str: PyUnicode_InternFromString(c"datetime_keys".as_ptr()),
                 default_str: PyUnicode_InternFromString(c"default".as_ptr()),
                 descr_str: PyUnicode_InternFromString(c"descr".as_ptr()),
                 dict_str: PyUnicode_InternFromString(c"__dict__".as_ptr()),
                 dtype_str: PyUnicode_InternFromString(c"dtype".as_ptr()),
+                encoders_str: PyUnicode_InternFromString(c"encoders".as_ptr()),
                 ext_hook_str: PyUnicode_InternFromString(c"ext_hook".as_ptr()),
+                ext_registry_str: PyUnicode_InternFromString(c"ext_registry".as_ptr()),
                 field_type_str: PyUnicode_InternFromString(c"_field_type".as_ptr()),
                 fields_str: PyUnicode_InternFromString(c"__fields__".as_ptr()),
                 int_str: PyUnicode_InternFromString(c"int".as_ptr()),
+                kind_str: PyUnicode_InternFromString(c"kind".as_ptr()),
+                marker_str: PyUnicode_InternFromString(c"marker".as_ptr()),
+                max_alloc_size_str: PyUnicode_InternFromString(c"max_alloc_size".as_ptr()),
+                max_array_len_str: PyUnicode_InternFromString(c"max_array_len".as_ptr()),
+                max_bin_len_str: PyUnicode_InternFromString(c"max_bin_len".as_ptr()),
+                max_buffer_size_str: PyUnicode_InternFromString(c"max_buffer_size".as_ptr()),
+                max_depth_str: PyUnicode_InternFromString(c"max_depth".as_ptr()),
+                max_ext_len_str: PyUnicode_InternFromString(c"max_ext_len".as_ptr()),
+                max_map_len_str: PyUnicode_InternFromString(c"max_map_len".as_ptr()),
+                max_str_len_str: PyUnicode_InternFromString(c"max_str_len".as_ptr()),
+                model_str: PyUnicode_InternFromString(c"model".as_ptr()),
+                namedtuple_fields_str: PyUnicode_InternFromString(c"_fields".as_ptr()),
                 normalize_str: PyUnicode_InternFromString(c"normalize".as_ptr()),
+                offset_str: PyUnicode_InternFromString(c"offset".as_ptr()),
                 option_str: PyUnicode_InternFromString(c"option".as_ptr()),
+                path_str: PyUnicode_InternFromString(c"path".as_ptr()),
+                pos_str: PyUnicode_InternFromString(c"pos".as_ptr()),
                 pydantic_extra_str: PyUnicode_InternFromString(c"__pydantic_extra__".as_ptr()),
+                pydantic_fields_str: PyUnicode_InternFromString(c"__pydantic_fields__".as_ptr()),
                 pydantic_validator_str: PyUnicode_InternFromString(
                     c"__pydantic_validator__".as_ptr(),
                 ),
+                read_str: PyUnicode_InternFromString(c"read".as_ptr()),
                 slots_str: PyUnicode_InternFromString(c"__slots__".as_ptr()),
+                subclasses_str: PyUnicode_InternFromString(c"subclasses".as_ptr()),
+                type_str: PyUnicode_InternFromString(c"type".as_ptr()),
+                tzinfo_str: PyUnicode_InternFromString(c"tzinfo".as_ptr()),
                 utcoffset_str: PyUnicode_InternFromString(c"utcoffset".as_ptr()),
+                validate_python_str: PyUnicode_InternFromString(c"validate_python".as_ptr()),
                 value_str: PyUnicode_InternFromString(c"value".as_ptr()),
-                MsgpackEncodeError: Py_NewRef(PyExc_TypeError),
-                MsgpackDecodeError: Py_NewRef(PyExc_ValueError),
+                write_str: PyUnicode_InternFromString(c"write".as_ptr()),
+                MsgpackEncodeError: encode_error,
+                MsgpackDecodeError: decode_error,
+                MsgpackBufferTooSmallError: PyErr_NewException(
+                    c"ormsgpack.MsgpackBufferTooSmallError".as_ptr(),
+                    encode_error,
+                    null_mut(),
+                ),
+                MsgpackUnsupportedTypeError: PyErr_NewException(
+                    c"ormsgpack.MsgpackUnsupportedTypeError".as_ptr(),
+                    encode_error,
+                    null_mut(),
+                ),
+                MsgpackIntegerOverflowError: PyErr_NewException(
+                    c"ormsgpack.MsgpackIntegerOverflowError".as_ptr(),
+                    encode_error,
+                    null_mut(),
+                ),
+                MsgpackTruncatedError: PyErr_NewException(
+                    c"ormsgpack.MsgpackTruncatedError".as_ptr(),
+                    decode_error,
+                    null_mut(),
+                ),
+                MsgpackInvalidUtf8Error: PyErr_NewException(
+                    c"ormsgpack.MsgpackInvalidUtf8Error".as_ptr(),
+                    decode_error,
+                    null_mut(),
+                ),
+                MsgpackDepthError: PyErr_NewException(
+                    c"ormsgpack.MsgpackDepthError".as_ptr(),
+                    limit_error,
+                    null_mut(),
+                ),
+                MsgpackExtError: PyErr_NewException(
+                    c"ormsgpack.MsgpackExtError".as_ptr(),
+                    decode_error,
+                    null_mut(),
+                ),
+                MsgpackLimitError: limit_error,
+                MsgpackDecodeErrorKind: create_decode_error_kind(),
                 key_map: KeyMap::new(),
+                encoders: PyDict_New(),
+                encoder_cache: WeakKeyDict::new(),
+                namedtuple_fields_cache: WeakKeyDict::new(),
+                schema_cache: WeakKeyDict::new(),
+                generic_schema_cache: PyDict_New(),
+                class_schema_cache: WeakKeyDict::new(),
             }
         }
     }
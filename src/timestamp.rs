@@ -0,0 +1,437 @@
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::ffi::*;
+use chrono::{Datelike, Timelike};
+use pyo3::ffi::*;
+use std::ffi::{CStr, CString};
+use std::mem::offset_of;
+use std::os::raw::{c_int, c_uint, c_void};
+use std::ptr::null_mut;
+
+const TIMESTAMP_DOC: &CStr =
+    c"Timestamp(seconds, nanoseconds=0)\n--\n\nA MessagePack timestamp with nanosecond precision.";
+
+const NANOSECONDS_PER_SECOND: i64 = 1_000_000_000;
+
+#[repr(C)]
+pub struct PyTimestamp {
+    pub ob_base: PyObject,
+    pub seconds: i64,
+    pub nanoseconds: u32,
+}
+
+/// Create a `Timestamp` instance. `nanoseconds` must be less than one
+/// billion.
+pub unsafe fn new_timestamp(
+    timestamp_type: *mut PyTypeObject,
+    seconds: i64,
+    nanoseconds: u32,
+) -> *mut PyObject {
+    let obj = (*timestamp_type).tp_alloc.unwrap()(timestamp_type, 0);
+    (*obj.cast::<PyTimestamp>()).seconds = seconds;
+    (*obj.cast::<PyTimestamp>()).nanoseconds = nanoseconds;
+    obj
+}
+
+#[cold]
+unsafe fn raise_value_error(msg: &CStr) -> *mut PyObject {
+    PyErr_SetString(PyExc_ValueError, msg.as_ptr());
+    null_mut()
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_new(
+    subtype: *mut PyTypeObject,
+    args: *mut PyObject,
+    kwds: *mut PyObject,
+) -> *mut PyObject {
+    let num_args = Py_SIZE(args);
+    if !(1..=2).contains(&num_args) || (!kwds.is_null() && pydict_size(kwds) != 0) {
+        PyErr_SetString(
+            PyExc_TypeError,
+            c"Timestamp.__new__() takes 1 or 2 positional arguments".as_ptr(),
+        );
+        return null_mut();
+    }
+    let seconds = pytuple_get_item(args, 0);
+    if PyLong_Check(seconds) == 0 {
+        PyErr_SetString(
+            PyExc_TypeError,
+            c"Timestamp.__new__() first argument must be int".as_ptr(),
+        );
+        return null_mut();
+    }
+    let seconds = PyLong_AsLongLong(seconds);
+    if seconds == -1 && !PyErr_Occurred().is_null() {
+        return null_mut();
+    }
+    let mut nanoseconds = 0;
+    if num_args == 2 {
+        let value = pytuple_get_item(args, 1);
+        if PyLong_Check(value) == 0 {
+            PyErr_SetString(
+                PyExc_TypeError,
+                c"Timestamp.__new__() second argument must be int".as_ptr(),
+            );
+            return null_mut();
+        }
+        nanoseconds = PyLong_AsLongLong(value);
+        if !(0..NANOSECONDS_PER_SECOND).contains(&nanoseconds) {
+            PyErr_Clear();
+            return raise_value_error(c"nanoseconds must be in the range [0, 999999999]");
+        }
+    }
+    new_timestamp(subtype, seconds, nanoseconds as u32)
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_dealloc(op: *mut PyObject) {
+    let tp = ob_type!(op);
+    (*tp).tp_free.unwrap()(op.cast::<c_void>());
+    Py_DECREF(tp.cast::<PyObject>());
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_richcompare(
+    op: *mut PyObject,
+    other: *mut PyObject,
+    compare_op: c_int,
+) -> *mut PyObject {
+    if ob_type!(other) != ob_type!(op) {
+        return Py_NewRef(Py_NotImplemented());
+    }
+    let op = op.cast::<PyTimestamp>();
+    let other = other.cast::<PyTimestamp>();
+    let a = ((*op).seconds, (*op).nanoseconds);
+    let b = ((*other).seconds, (*other).nanoseconds);
+    let ordering = a.cmp(&b);
+    let res = if compare_op == Py_LT {
+        ordering.is_lt()
+    } else if compare_op == Py_LE {
+        ordering.is_le()
+    } else if compare_op == Py_EQ {
+        ordering.is_eq()
+    } else if compare_op == Py_NE {
+        ordering.is_ne()
+    } else if compare_op == Py_GT {
+        ordering.is_gt()
+    } else {
+        ordering.is_ge()
+    };
+    Py_NewRef(if res { Py_True() } else { Py_False() })
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_hash(op: *mut PyObject) -> Py_hash_t {
+    let args = timestamp_args(op);
+    let hash = PyObject_Hash(args);
+    Py_DECREF(args);
+    hash
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_repr(op: *mut PyObject) -> *mut PyObject {
+    let timestamp = op.cast::<PyTimestamp>();
+    let repr = format!(
+        "Timestamp({}, {})",
+        (*timestamp).seconds,
+        (*timestamp).nanoseconds
+    );
+    unicode_from_str(&repr)
+}
+
+/// Return the `(seconds, nanoseconds)` tuple of a `Timestamp` instance.
+unsafe fn timestamp_args(op: *mut PyObject) -> *mut PyObject {
+    let timestamp = op.cast::<PyTimestamp>();
+    let args = PyTuple_New(2);
+    PyTuple_SET_ITEM(args, 0, PyLong_FromLongLong((*timestamp).seconds));
+    PyTuple_SET_ITEM(args, 1, PyLong_FromLong((*timestamp).nanoseconds.into()));
+    args
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_reduce(op: *mut PyObject, _args: *mut PyObject) -> *mut PyObject {
+    let reduced = PyTuple_New(2);
+    PyTuple_SET_ITEM(reduced, 0, Py_NewRef(ob_type!(op).cast::<PyObject>()));
+    PyTuple_SET_ITEM(reduced, 1, timestamp_args(op));
+    reduced
+}
+
+/// Return the number of nanoseconds since the epoch of a timestamp as an
+/// `int`.
+pub unsafe fn new_unix_nanos(seconds: i64, nanoseconds: u32) -> *mut PyObject {
+    let nanos = i128::from(seconds) * i128::from(NANOSECONDS_PER_SECOND) + i128::from(nanoseconds);
+    match i64::try_from(nanos) {
+        Ok(value) => PyLong_FromLongLong(value),
+        Err(_) => {
+            let repr = CString::new(nanos.to_string()).unwrap();
+            PyLong_FromString(repr.as_ptr(), null_mut(), 10)
+        }
+    }
+}
+
+unsafe fn unix_nanos(op: *mut PyObject) -> *mut PyObject {
+    let timestamp = op.cast::<PyTimestamp>();
+    new_unix_nanos((*timestamp).seconds, (*timestamp).nanoseconds)
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_to_unix_nanos(
+    op: *mut PyObject,
+    _args: *mut PyObject,
+) -> *mut PyObject {
+    unix_nanos(op)
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_to_datetime(
+    op: *mut PyObject,
+    _args: *mut PyObject,
+) -> *mut PyObject {
+    let timestamp = op.cast::<PyTimestamp>();
+    let datetime = match chrono::DateTime::<chrono::Utc>::from_timestamp(
+        (*timestamp).seconds,
+        (*timestamp).nanoseconds,
+    ) {
+        Some(value) => value,
+        None => return raise_value_error(c"Timestamp out of range for datetime"),
+    };
+    let datetime_api = *PyDateTimeAPI();
+    (datetime_api.DateTime_FromDateAndTime)(
+        datetime.year(),
+        datetime.month() as i32,
+        datetime.day() as i32,
+        datetime.hour() as i32,
+        datetime.minute() as i32,
+        datetime.second() as i32,
+        (datetime.nanosecond() / 1000) as i32,
+        datetime_api.TimeZone_UTC,
+        datetime_api.DateTimeType,
+    )
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_to_datetime64(
+    op: *mut PyObject,
+    _args: *mut PyObject,
+) -> *mut PyObject {
+    let numpy = PyImport_ImportModule(c"numpy".as_ptr());
+    if numpy.is_null() {
+        return null_mut();
+    }
+    let datetime64 = PyObject_GetAttrString(numpy, c"datetime64".as_ptr());
+    Py_DECREF(numpy);
+    if datetime64.is_null() {
+        return null_mut();
+    }
+    let nanos = unix_nanos(op);
+    let unit = PyUnicode_InternFromString(c"ns".as_ptr());
+    let obj = PyObject_CallFunctionObjArgs(datetime64, nanos, unit, null_mut::<PyObject>());
+    Py_DECREF(unit);
+    Py_DECREF(nanos);
+    Py_DECREF(datetime64);
+    obj
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_from_datetime(
+    cls: *mut PyObject,
+    datetime: *mut PyObject,
+) -> *mut PyObject {
+    if PyDateTime_Check(datetime) == 0 {
+        PyErr_SetString(
+            PyExc_TypeError,
+            c"from_datetime() argument must be a datetime.datetime".as_ptr(),
+        );
+        return null_mut();
+    }
+    if PyDateTime_DATE_GET_TZINFO(datetime) == Py_None() {
+        return raise_value_error(c"from_datetime() argument must be an aware datetime");
+    }
+    let datetime_api = *PyDateTimeAPI();
+    let epoch = (datetime_api.DateTime_FromDateAndTime)(
+        1970,
+        1,
+        1,
+        0,
+        0,
+        0,
+        0,
+        datetime_api.TimeZone_UTC,
+        datetime_api.DateTimeType,
+    );
+    let delta = PyNumber_Subtract(datetime, epoch);
+    Py_DECREF(epoch);
+    if delta.is_null() {
+        return null_mut();
+    }
+    let seconds = i64::from(PyDateTime_DELTA_GET_DAYS(delta)) * 86400
+        + i64::from(PyDateTime_DELTA_GET_SECONDS(delta));
+    let nanoseconds = PyDateTime_DELTA_GET_MICROSECONDS(delta) as u32 * 1000;
+    Py_DECREF(delta);
+    new_timestamp(cls.cast::<PyTypeObject>(), seconds, nanoseconds)
+}
+
+#[no_mangle]
+unsafe extern "C" fn timestamp_from_unix_nanos(
+    cls: *mut PyObject,
+    nanos: *mut PyObject,
+) -> *mut PyObject {
+    if PyLong_Check(nanos) == 0 {
+        PyErr_SetString(
+            PyExc_TypeError,
+            c"from_unix_nanos() argument must be int".as_ptr(),
+        );
+        return null_mut();
+    }
+    let divisor = PyLong_FromLongLong(NANOSECONDS_PER_SECOND);
+    let quotient_remainder = PyNumber_Divmod(nanos, divisor);
+    Py_DECREF(divisor);
+    if quotient_remainder.is_null() {
+        return null_mut();
+    }
+    let seconds = PyLong_AsLongLong(PyTuple_GET_ITEM(quotient_remainder, 0));
+    let nanoseconds = PyLong_AsLongLong(PyTuple_GET_ITEM(quotient_remainder, 1));
+    Py_DECREF(quotient_remainder);
+    if seconds == -1 && !PyErr_Occurred().is_null() {
+        return null_mut();
+    }
+    new_timestamp(cls.cast::<PyTypeObject>(), seconds, nanoseconds as u32)
+}
+
+pub unsafe fn create_timestamp_type(module: *mut PyObject) -> *mut PyTypeObject {
+    let members: Box<[PyMemberDef; 3]> = Box::new([
+        PyMemberDef {
+            name: c"seconds".as_ptr(),
+            type_code: Py_T_LONGLONG,
+            offset: offset_of!(PyTimestamp, seconds) as Py_ssize_t,
+            flags: Py_READONLY,
+            doc: c"The number of seconds since the epoch.".as_ptr(),
+        },
+        PyMemberDef {
+            name: c"nanoseconds".as_ptr(),
+            type_code: Py_T_UINT,
+            offset: offset_of!(PyTimestamp, nanoseconds) as Py_ssize_t,
+            flags: Py_READONLY,
+            doc: c"The number of nanoseconds in the second.".as_ptr(),
+        },
+        PyMemberDef {
+            name: std::ptr::null(),
+            type_code: 0,
+            offset: 0,
+            flags: 0,
+            doc: std::ptr::null(),
+        },
+    ]);
+    let methods: Box<[PyMethodDef; 7]> = Box::new([
+        PyMethodDef {
+            ml_name: c"__reduce__".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: timestamp_reduce,
+            },
+            ml_flags: METH_NOARGS,
+            ml_doc: null_mut(),
+        },
+        PyMethodDef {
+            ml_name: c"to_datetime".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: timestamp_to_datetime,
+            },
+            ml_flags: METH_NOARGS,
+            ml_doc: c"to_datetime()\n--\n\nReturn the timestamp as a UTC datetime.datetime, truncated to microseconds.".as_ptr(),
+        },
+        PyMethodDef {
+            ml_name: c"to_datetime64".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: timestamp_to_datetime64,
+            },
+            ml_flags: METH_NOARGS,
+            ml_doc: c"to_datetime64()\n--\n\nReturn the timestamp as a numpy.datetime64 in nanoseconds.".as_ptr(),
+        },
+        PyMethodDef {
+            ml_name: c"to_unix_nanos".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: timestamp_to_unix_nanos,
+            },
+            ml_flags: METH_NOARGS,
+            ml_doc: c"to_unix_nanos()\n--\n\nReturn the number of nanoseconds since the epoch.".as_ptr(),
+        },
+        PyMethodDef {
+            ml_name: c"from_datetime".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: timestamp_from_datetime,
+            },
+            ml_flags: METH_O | METH_CLASS,
+            ml_doc: c"from_datetime(datetime, /)\n--\n\nCreate a timestamp from an aware datetime.datetime.".as_ptr(),
+        },
+        PyMethodDef {
+            ml_name: c"from_unix_nanos".as_ptr(),
+            ml_meth: PyMethodDefPointer {
+                PyCFunction: timestamp_from_unix_nanos,
+            },
+            ml_flags: METH_O | METH_CLASS,
+            ml_doc: c"from_unix_nanos(nanos, /)\n--\n\nCreate a timestamp from a number of nanoseconds since the epoch.".as_ptr(),
+        },
+        PyMethodDef::zeroed(),
+    ]);
+    let mut slots: [PyType_Slot; 9] = [
+        PyType_Slot {
+            slot: Py_tp_doc,
+            pfunc: TIMESTAMP_DOC.as_ptr() as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_new,
+            pfunc: timestamp_new as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_dealloc,
+            pfunc: timestamp_dealloc as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_members,
+            pfunc: Box::into_raw(members).cast::<c_void>(),
+        },
+        PyType_Slot {
+            slot: Py_tp_methods,
+            pfunc: Box::into_raw(methods).cast::<c_void>(),
+        },
+        PyType_Slot {
+            slot: Py_tp_richcompare,
+            pfunc: timestamp_richcompare as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_hash,
+            pfunc: timestamp_hash as *mut c_void,
+        },
+        PyType_Slot {
+            slot: Py_tp_repr,
+            pfunc: timestamp_repr as *mut c_void,
+        },
+        PyType_Slot {
+            slot: 0,
+            pfunc: null_mut(),
+        },
+    ];
+    let mut spec = PyType_Spec {
+        name: c"ormsgpack.Timestamp".as_ptr(),
+        basicsize: std::mem::size_of::<PyTimestamp>() as c_int,
+        itemsize: 0,
+        flags: Py_TPFLAGS_DEFAULT as c_uint,
+        slots: slots.as_mut_ptr(),
+    };
+    let timestamp_type = PyType_FromModuleAndSpec(module, &mut spec, null_mut());
+    let match_args = PyTuple_New(2);
+    PyTuple_SET_ITEM(
+        match_args,
+        0,
+        PyUnicode_InternFromString(c"seconds".as_ptr()),
+    );
+    PyTuple_SET_ITEM(
+        match_args,
+        1,
+        PyUnicode_InternFromString(c"nanoseconds".as_ptr()),
+    );
+    PyObject_SetAttrString(timestamp_type, c"__match_args__".as_ptr(), match_args);
+    Py_DECREF(match_args);
+    timestamp_type.cast::<PyTypeObject>()
+}
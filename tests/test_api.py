@@ -5,6 +5,7 @@ import datetime
 import inspect
 import platform
 import re
+from typing import Any
 
 import msgpack
 import pytest
@@ -198,6 +199,7 @@ def test_unpackb_non_interned_kwarg() -> None:
     "option",
     (
         1 << 16,
+        1 << 34,
         True,
         -1,
         9223372036854775809,
@@ -211,15 +213,17 @@ def test_packb_invalid_option(option: int) -> None:
 @pytest.mark.parametrize(
     "option",
     (
-        ormsgpack.OPT_NAIVE_UTC,
         ormsgpack.OPT_OMIT_MICROSECONDS,
         ormsgpack.OPT_PASSTHROUGH_BIG_INT,
         ormsgpack.OPT_PASSTHROUGH_DATACLASS,
         ormsgpack.OPT_PASSTHROUGH_DATETIME,
         ormsgpack.OPT_PASSTHROUGH_SUBCLASS,
         ormsgpack.OPT_PASSTHROUGH_TUPLE,
+        ormsgpack.OPT_SERIALIZE_COLLECTIONS,
+        ormsgpack.OPT_SERIALIZE_NAMEDTUPLE,
         ormsgpack.OPT_SERIALIZE_NUMPY,
         ormsgpack.OPT_SERIALIZE_PYDANTIC,
+        ormsgpack.OPT_SERIALIZE_SET,
         ormsgpack.OPT_SORT_KEYS,
         ormsgpack.OPT_UTC_Z,
         True,
@@ -232,6 +236,47 @@ def test_unpackb_invalid_option(option: int) -> None:
         ormsgpack.unpackb(b"\x00", option=option)
 
 
+@pytest.mark.parametrize(
+    "option",
+    (
+        ormsgpack.OPT_SERIALIZE_SET | ormsgpack.OPT_SET_AS_EXT,
+        ormsgpack.OPT_BIG_INT_AS_BIN | ormsgpack.OPT_BIG_INT_AS_EXT,
+        ormsgpack.OPT_DECIMAL_AS_EXT | ormsgpack.OPT_DECIMAL_AS_STR,
+        ormsgpack.OPT_TIMEDELTA_AS_EXT | ormsgpack.OPT_TIMEDELTA_AS_SECONDS,
+    ),
+)
+def test_packb_exclusive_options(option: int) -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(True, option=option)
+
+
+@pytest.mark.parametrize(
+    "option",
+    (
+        ormsgpack.OPT_NATIVE_TIMESTAMP | ormsgpack.OPT_TIMESTAMP_AS_NANOS,
+        ormsgpack.OPT_NATIVE_TIMESTAMP | ormsgpack.OPT_TIMESTAMP_AS_SECONDS,
+        ormsgpack.OPT_TIMESTAMP_AS_NANOS | ormsgpack.OPT_TIMESTAMP_AS_SECONDS,
+    ),
+)
+def test_unpackb_exclusive_options(option: int) -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(b"\x00", option=option)
+
+
+@pytest.mark.parametrize(
+    "kwargs",
+    (
+        pytest.param({"type": 5}, id="type"),
+        pytest.param({"model": 5}, id="model"),
+        pytest.param({"ext_registry": {}}, id="ext_registry"),
+        pytest.param({"tzinfo": "x"}, id="tzinfo"),
+    ),
+)
+def test_unpackb_invalid_argument_type(kwargs: dict[str, Any]) -> None:
+    with pytest.raises(TypeError):
+        ormsgpack.unpackb(b"\x01", **kwargs)
+
+
 def test_opts_multiple() -> None:
     """
     packb() multiple option
@@ -251,10 +296,27 @@ def test_packb_signature() -> None:
     packb() valid __text_signature__
     """
     assert (
-        str(inspect.signature(ormsgpack.packb)) == "(obj, /, default=None, option=None)"
+        str(inspect.signature(ormsgpack.packb))
+        == "(obj, /, default=None, option=None, *, ext_registry=None)"
     )
     inspect.signature(ormsgpack.packb).bind("str")
     inspect.signature(ormsgpack.packb).bind("str", default=None, option=1)
+    inspect.signature(ormsgpack.packb).bind("str", ext_registry=None)
+
+
+@pytest.mark.skipif(
+    platform.python_implementation() == "GraalVM",
+    reason="function signatures are missing on GraalPy",
+)
+def test_packb_into_signature() -> None:
+    """
+    packb_into() valid __text_signature__
+    """
+    assert (
+        str(inspect.signature(ormsgpack.packb_into))
+        == "(obj, buffer, /, offset=0, *, default=None, option=None, ext_registry=None)"
+    )
+    inspect.signature(ormsgpack.packb_into).bind("str", bytearray(), 1)
 
 
 @pytest.mark.skipif(
@@ -267,9 +329,29 @@ def test_unpackb_signature() -> None:
     """
     assert (
         str(inspect.signature(ormsgpack.unpackb))
-        == "(obj, /, *, ext_hook=None, option=None)"
+        == "(obj, /, *, type=None, model=None, ext_hook=None, ext_registry=None, tzinfo=None, datetime_keys=None, option=None, max_depth=1023, max_str_len=None, max_bin_len=None, max_array_len=None, max_map_len=None, max_ext_len=None, max_alloc_size=None)"
     )
     inspect.signature(ormsgpack.unpackb).bind("[]")
+    inspect.signature(ormsgpack.unpackb).bind("[]", type=int)
+    inspect.signature(ormsgpack.unpackb).bind("[]", model=None)
+    inspect.signature(ormsgpack.unpackb).bind("[]", ext_registry=None)
+    inspect.signature(ormsgpack.unpackb).bind("[]", tzinfo=None)
+    inspect.signature(ormsgpack.unpackb).bind("[]", datetime_keys=None)
+
+
+@pytest.mark.skipif(
+    platform.python_implementation() == "GraalVM",
+    reason="function signatures are missing on GraalPy",
+)
+def test_unpackb_partial_signature() -> None:
+    """
+    unpackb_partial() valid __text_signature__
+    """
+    assert (
+        str(inspect.signature(ormsgpack.unpackb_partial))
+        == "(obj, /, offset=0, *, ext_hook=None, ext_registry=None, option=None, max_depth=1023, max_str_len=None, max_bin_len=None, max_array_len=None, max_map_len=None, max_ext_len=None, max_alloc_size=None)"
+    )
+    inspect.signature(ormsgpack.unpackb_partial).bind("[]", 1)
 
 
 def test_packb_module_str() -> None:
@@ -0,0 +1,220 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import array
+import collections
+import types
+from collections.abc import Iterator, Mapping, Sequence
+from typing import Any
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+
+class CustomMapping(Mapping[str, int]):
+    def __init__(self, data: dict[str, int]) -> None:
+        self._data = data
+
+    def __getitem__(self, key: str) -> int:
+        return self._data[key]
+
+    def __iter__(self) -> Iterator[str]:
+        return iter(self._data)
+
+    def __len__(self) -> int:
+        return len(self._data)
+
+
+class CustomSequence(Sequence[int]):
+    def __init__(self, data: list[int]) -> None:
+        self._data = data
+
+    def __getitem__(self, index: Any) -> Any:
+        return self._data[index]
+
+    def __len__(self) -> int:
+        return len(self._data)
+
+
+class FailingMapping(CustomMapping):
+    def __getitem__(self, key: str) -> int:
+        raise ValueError
+
+
+class FailingSequence(CustomSequence):
+    def __getitem__(self, index: Any) -> Any:
+        raise ValueError
+
+
+@pytest.mark.parametrize(
+    ("obj", "expected"),
+    (
+        pytest.param(
+            types.MappingProxyType({"a": 1}), {"a": 1}, id="MappingProxyType"
+        ),
+        pytest.param(CustomMapping({"a": 1, "b": 2}), {"a": 1, "b": 2}, id="Mapping"),
+        pytest.param(collections.ChainMap({"a": 1}, {"b": 2}), None, id="ChainMap"),
+        pytest.param({"a": 1, "b": 2}.keys(), ["a", "b"], id="dict_keys"),
+        pytest.param({"a": 1, "b": 2}.values(), [1, 2], id="dict_values"),
+        pytest.param({"a": 1}.items(), [["a", 1]], id="dict_items"),
+        pytest.param(
+            collections.OrderedDict(a=1, b=2).items(),
+            [["a", 1], ["b", 2]],
+            id="odict_items",
+        ),
+        pytest.param(collections.deque([1, "a", None]), [1, "a", None], id="deque"),
+        pytest.param(array.array("i", [1, 2]), [1, 2], id="array"),
+        pytest.param(range(3), [0, 1, 2], id="range"),
+        pytest.param(CustomSequence([1, 2]), [1, 2], id="Sequence"),
+    ),
+)
+def test_collection(obj: Any, expected: Any) -> None:
+    if expected is None:
+        expected = dict(obj)
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(obj)
+    packed = ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_COLLECTIONS)
+    assert packed == msgpack.packb(expected)
+
+
+def test_collection_nested() -> None:
+    obj = collections.deque([types.MappingProxyType({"a": range(2)})])
+    packed = ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_COLLECTIONS)
+    assert ormsgpack.unpackb(packed) == [{"a": [0, 1]}]
+
+
+def test_collection_sort_keys() -> None:
+    packed = ormsgpack.packb(
+        CustomMapping({"b": 1, "a": 2}),
+        option=ormsgpack.OPT_SERIALIZE_COLLECTIONS | ormsgpack.OPT_SORT_KEYS,
+    )
+    assert packed == msgpack.packb({"a": 2, "b": 1})
+
+
+def test_collection_non_str_keys() -> None:
+    obj = types.MappingProxyType({1: "a"})
+    option = ormsgpack.OPT_SERIALIZE_COLLECTIONS
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(obj, option=option)
+    packed = ormsgpack.packb(obj, option=option | ormsgpack.OPT_NON_STR_KEYS)
+    assert packed == msgpack.packb({1: "a"})
+
+
+@pytest.mark.parametrize(
+    ("obj", "kind"),
+    (
+        pytest.param(FailingMapping({"a": 1}), "Mapping", id="Mapping"),
+        pytest.param(FailingSequence([1]), "Sequence", id="Sequence"),
+    ),
+)
+def test_collection_error(obj: Any, kind: str) -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_COLLECTIONS)
+    assert str(exc_info.value).startswith(f"{kind} iteration failed (ValueError")
+    assert isinstance(exc_info.value.__cause__, ValueError)
+
+
+def test_collection_mapping_proxy_ordered_dict() -> None:
+    data = collections.OrderedDict(a=1, b=2)
+    data.move_to_end("a")
+    packed = ormsgpack.packb(
+        types.MappingProxyType(data), option=ormsgpack.OPT_SERIALIZE_COLLECTIONS
+    )
+    assert packed == msgpack.packb({"b": 2, "a": 1})
+
+
+def test_collection_deque_mutated() -> None:
+    obj = collections.deque([object(), 1])
+
+    def default(value: Any) -> Any:
+        obj.append(2)
+        return None
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb(
+            obj, option=ormsgpack.OPT_SERIALIZE_COLLECTIONS, default=default
+        )
+    assert str(exc_info.value).startswith("Sequence iteration failed (RuntimeError")
+    assert isinstance(exc_info.value.__cause__, RuntimeError)
+
+
+def test_collection_dict_view_mutated() -> None:
+    data: dict[Any, Any] = {"a": object(), "b": 1}
+
+    def default(value: Any) -> Any:
+        data["c"] = 2
+        return None
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb(
+            data.values(), option=ormsgpack.OPT_SERIALIZE_COLLECTIONS, default=default
+        )
+    assert isinstance(exc_info.value.__cause__, RuntimeError)
+
+
+def test_collection_recursive() -> None:
+    obj: collections.deque[Any] = collections.deque()
+    obj.append(obj)
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_COLLECTIONS)
+
+
+def test_collection_default_precedence() -> None:
+    packed = ormsgpack.packb(
+        collections.deque([1]),
+        option=ormsgpack.OPT_SERIALIZE_COLLECTIONS,
+        default=lambda x: "default",
+    )
+    assert packed == msgpack.packb([1])
+
+
+@pytest.mark.parametrize(
+    "obj",
+    (
+        pytest.param(type("MyStr", (str,), {})("ab"), id="str"),
+        pytest.param(type("MyBytes", (bytes,), {})(b"ab"), id="bytes"),
+        pytest.param(type("MyByteArray", (bytearray,), {})(b"ab"), id="bytearray"),
+    ),
+)
+def test_collection_str_subclass(obj: Any) -> None:
+    packed = ormsgpack.packb(
+        obj,
+        option=ormsgpack.OPT_SERIALIZE_COLLECTIONS | ormsgpack.OPT_PASSTHROUGH_SUBCLASS,
+        default=lambda x: "default",
+    )
+    assert packed == msgpack.packb("default")
+
+
+def test_collection_user_string() -> None:
+    packed = ormsgpack.packb(
+        collections.UserString("ab"),
+        option=ormsgpack.OPT_SERIALIZE_COLLECTIONS,
+        default=lambda x: "default",
+    )
+    assert packed == msgpack.packb("default")
+
+
+@pytest.mark.parametrize(
+    ("obj", "option"),
+    (
+        pytest.param([1], ormsgpack.OPT_PASSTHROUGH_SUBCLASS, id="list"),
+        pytest.param({"a": 1}, ormsgpack.OPT_PASSTHROUGH_SUBCLASS, id="dict"),
+        pytest.param((1,), ormsgpack.OPT_PASSTHROUGH_TUPLE, id="tuple"),
+    ),
+)
+def test_collection_passthrough(obj: Any, option: int) -> None:
+    if type(obj) is not tuple:
+        obj = type("Subclass", (type(obj),), {})(obj)
+    packed = ormsgpack.packb(
+        obj,
+        option=ormsgpack.OPT_SERIALIZE_COLLECTIONS | option,
+        default=lambda x: "default",
+    )
+    assert packed == msgpack.packb("default")
+
+
+def test_collection_namedtuple() -> None:
+    Point = collections.namedtuple("Point", ("x", "y"))
+    packed = ormsgpack.packb(Point(1, 2), option=ormsgpack.OPT_SERIALIZE_COLLECTIONS)
+    assert packed == msgpack.packb([1, 2])
@@ -487,6 +487,12 @@ def test_passthrough_datetime_default() -> None:
             datetime.datetime(2106, 2, 7, 6, 28, 16, tzinfo=datetime.timezone.utc),
             b"\xd7\xff\x00\x00\x00\x01\x00\x00\x00\x00",
         ),
+        (
+            datetime.datetime(
+                2020, 1, 1, 0, 0, 0, 600000, tzinfo=datetime.timezone.utc
+            ),
+            b"\xd7\xff\x8f\r\x18\x00^\x0b\xe1\x00",
+        ),
         (
             datetime.datetime(2514, 5, 30, 1, 53, 3, tzinfo=datetime.timezone.utc),
             b"\xd7\xff\x00\x00\x00\x03\xff\xff\xff\xff",
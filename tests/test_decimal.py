@@ -0,0 +1,123 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import decimal
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+VALUES = (
+    "0",
+    "-0",
+    "1",
+    "-1.25",
+    "1E+3",
+    "1.000",
+    "0E-30",
+    "123456789012345678901234567890.123456789",
+    "9" * 100 + "E-50",
+    "Infinity",
+    "-Infinity",
+    "NaN",
+    "NaN123",
+    "-sNaN",
+)
+
+
+@pytest.mark.parametrize("value", VALUES)
+def test_decimal_str(value: str) -> None:
+    packed = ormsgpack.packb(
+        decimal.Decimal(value), option=ormsgpack.OPT_DECIMAL_AS_STR
+    )
+    assert packed == msgpack.packb(str(decimal.Decimal(value)))
+
+
+@pytest.mark.parametrize("value", VALUES)
+def test_decimal_ext_roundtrip(value: str) -> None:
+    obj = decimal.Decimal(value)
+    option = ormsgpack.OPT_DECIMAL_AS_EXT
+    unpacked = ormsgpack.unpackb(ormsgpack.packb([obj], option=option), option=option)
+    assert type(unpacked[0]) is decimal.Decimal
+    assert str(unpacked[0]) == str(obj)
+
+
+@pytest.mark.parametrize(
+    ("value", "data"),
+    (
+        ("-1.25", b"\x01\xff\xff\xff\xfe\x01\x25"),
+        ("1E+3", b"\x00\x00\x00\x00\x03\x01"),
+        ("12", b"\x00\x00\x00\x00\x00\x12"),
+        ("Infinity", b"\x02\x00\x00\x00\x00"),
+        ("-NaN", b"\x05\x00\x00\x00\x00"),
+        ("sNaN123", b"\x06\x00\x00\x00\x00\x01\x23"),
+    ),
+)
+def test_decimal_ext_format(value: str, data: bytes) -> None:
+    packed = ormsgpack.packb(
+        decimal.Decimal(value), option=ormsgpack.OPT_DECIMAL_AS_EXT
+    )
+    assert packed == msgpack.packb(msgpack.ExtType(126, data))
+
+
+def test_decimal_ext_map_key() -> None:
+    option = ormsgpack.OPT_DECIMAL_AS_EXT | ormsgpack.OPT_NON_STR_KEYS
+    obj = {decimal.Decimal("1.5"): 1, decimal.Decimal("-2"): 2}
+    packed = ormsgpack.packb(obj, option=option)
+    assert ormsgpack.unpackb(packed, option=option) == obj
+
+
+def test_decimal_ext_not_enabled() -> None:
+    packed = ormsgpack.packb(
+        decimal.Decimal("1.5"), option=ormsgpack.OPT_DECIMAL_AS_EXT
+    )
+    with pytest.raises(ormsgpack.MsgpackExtError):
+        ormsgpack.unpackb(packed)
+
+
+@pytest.mark.parametrize(
+    "data",
+    (
+        pytest.param(b"\x00\x00\x00\x00", id="length"),
+        pytest.param(b"\x00\x00\x00\x00\x00", id="no digits"),
+        pytest.param(b"\x08\x00\x00\x00\x00\x01", id="kind"),
+        pytest.param(b"\x00\x00\x00\x00\x00\x1a", id="digit"),
+        pytest.param(b"\x02\x00\x00\x00\x00\x01", id="infinity digits"),
+        pytest.param(b"\x02\x00\x00\x00\x01", id="infinity exponent"),
+        pytest.param(b"\x04\x00\x00\x00\x01", id="nan exponent"),
+    ),
+)
+def test_decimal_ext_invalid(data: bytes) -> None:
+    packed = msgpack.packb(msgpack.ExtType(126, data))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, option=ormsgpack.OPT_DECIMAL_AS_EXT)
+
+
+def test_decimal_ext_exponent_out_of_range() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(
+            decimal.Decimal("1E+3000000000"), option=ormsgpack.OPT_DECIMAL_AS_EXT
+        )
+
+
+def test_decimal_options_exclusive() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError, match="Invalid opts"):
+        ormsgpack.packb(
+            decimal.Decimal("1"),
+            option=ormsgpack.OPT_DECIMAL_AS_EXT | ormsgpack.OPT_DECIMAL_AS_STR,
+        )
+
+
+def test_decimal_not_enabled() -> None:
+    value = decimal.Decimal("1.5")
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(value)
+    assert ormsgpack.packb(value, default=str) == msgpack.packb("1.5")
+
+
+def test_decimal_subclass() -> None:
+    class Money(decimal.Decimal):
+        pass
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(Money("1"), option=ormsgpack.OPT_DECIMAL_AS_STR)
@@ -0,0 +1,213 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import io
+from typing import Any, Callable
+
+import msgpack
+import pytest
+
+import ormsgpack
+from ormsgpack import MsgpackDecodeErrorKind
+
+
+def decode_error(func: Callable[[], Any]) -> ormsgpack.MsgpackDecodeError:
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        func()
+    return exc_info.value
+
+
+def test_unexpected_eof() -> None:
+    data = msgpack.packb({"users": [{"name": "a"}, {"name": "b"}]})
+    exc = decode_error(lambda: ormsgpack.unpackb(data[:-1]))
+    assert exc.kind is MsgpackDecodeErrorKind.UNEXPECTED_EOF
+    assert exc.pos == len(data) - 2
+    assert exc.path == "$.users[1].name"
+    assert exc.marker == 0xA1
+
+
+def test_unexpected_eof_marker() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(b"\x81\xa1a"))
+    assert exc.kind is MsgpackDecodeErrorKind.UNEXPECTED_EOF
+    assert exc.pos == 3
+    assert exc.path == "$.a"
+    assert exc.marker is None
+
+
+def test_unexpected_eof_container() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(b"\x91\x92\x01"))
+    assert exc.kind is MsgpackDecodeErrorKind.UNEXPECTED_EOF
+    assert exc.pos == 1
+    assert exc.path == "$[0]"
+    assert exc.marker == 0x92
+
+
+def test_invalid_str() -> None:
+    data = b"\x81\xa3a b\x93\x01\x02\xa1\xff"
+    exc = decode_error(lambda: ormsgpack.unpackb(data))
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_STR
+    assert exc.pos == 8
+    assert exc.path == "$['a b'][2]"
+    assert exc.marker == 0xA1
+
+
+def test_invalid_type() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(b"\x91\xc1"))
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_TYPE
+    assert exc.pos == 1
+    assert exc.path == "$[0]"
+    assert exc.marker == 0xC1
+
+
+def test_invalid_map_key() -> None:
+    data = msgpack.packb({"a": {1: 2}})
+    exc = decode_error(lambda: ormsgpack.unpackb(data))
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_TYPE
+    assert exc.pos == 4
+    assert exc.path == "$.a"
+    assert exc.marker == 0x01
+
+
+def test_non_str_key_path() -> None:
+    data = msgpack.packb({(1, "a"): [msgpack.ExtType(1, b"")]}, use_bin_type=True)
+    exc = decode_error(
+        lambda: ormsgpack.unpackb(data, option=ormsgpack.OPT_NON_STR_KEYS)
+    )
+    assert exc.kind is MsgpackDecodeErrorKind.EXT_HOOK_MISSING
+    assert exc.path == "$[(1, 'a')][0]"
+
+
+def test_ext_hook_failed() -> None:
+    def ext_hook(tag: int, data: bytes) -> Any:
+        raise ValueError
+
+    data = msgpack.packb([msgpack.ExtType(1, b"a")])
+    exc = decode_error(lambda: ormsgpack.unpackb(data, ext_hook=ext_hook))
+    assert exc.kind is MsgpackDecodeErrorKind.EXT_HOOK_FAILED
+    assert exc.pos == 1
+    assert exc.path == "$[0]"
+    assert exc.marker == 0xD4
+
+
+def test_limit_exceeded() -> None:
+    data = msgpack.packb({"a": "bc"})
+    exc = decode_error(lambda: ormsgpack.unpackb(data, max_str_len=1))
+    assert exc.kind is MsgpackDecodeErrorKind.LIMIT_EXCEEDED
+    assert exc.pos == 3
+    assert exc.path == "$.a"
+
+
+def test_recursion_limit_reached() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(b"\x91\x91\x91\xc0", max_depth=3))
+    assert exc.kind is MsgpackDecodeErrorKind.RECURSION_LIMIT_REACHED
+    assert exc.pos == 3
+    assert exc.path == "$[0][0][0]"
+
+
+def test_recursion_limit_reached_default() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(b"\x91" * 1023 + b"\xc0"))
+    assert exc.kind is MsgpackDecodeErrorKind.RECURSION_LIMIT_REACHED
+    assert exc.path == "$" + "[0]" * 8 + "..." + "[0]" * 8
+
+
+def test_path_not_elided() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(b"\x91" * 17 + b"\xc1"))
+    assert exc.path == "$" + "[0]" * 17
+
+
+def test_trailing_data() -> None:
+    exc = decode_error(
+        lambda: ormsgpack.unpackb(
+            b"\x01\x02", option=ormsgpack.OPT_REJECT_TRAILING_DATA
+        )
+    )
+    assert exc.kind is MsgpackDecodeErrorKind.TRAILING_DATA
+    assert exc.pos == 1
+    assert exc.path is None
+    assert exc.marker == 0x02
+
+
+def test_invalid_argument() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb("abc"))  # type: ignore[arg-type]
+    assert exc.kind is None
+    assert exc.pos is None
+    assert exc.path is None
+    assert exc.marker is None
+
+
+def test_unpackb_partial_pos() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb_partial(b"\x01\x92\x01\xc1", 1))
+    assert exc.pos == 3
+    assert exc.path == "$[1]"
+
+
+def test_unpacker_pos() -> None:
+    unpacker = ormsgpack.Unpacker()
+    unpacker.feed(b"\x01\x02")
+    assert list(unpacker) == [1, 2]
+    unpacker.feed(b"\x92\x01\xc1\x03")
+    exc = decode_error(lambda: next(unpacker))
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_TYPE
+    assert exc.pos == 4
+    assert exc.path == "$[1]"
+    assert list(unpacker) == [3]
+
+
+def test_unpacker_limit_pos() -> None:
+    unpacker = ormsgpack.Unpacker(max_str_len=1)
+    unpacker.feed(b"\x01\x91\xa2ab")
+    assert next(unpacker) == 1
+    exc = decode_error(lambda: next(unpacker))
+    assert exc.kind is MsgpackDecodeErrorKind.LIMIT_EXCEEDED
+    assert exc.pos == 2
+    assert exc.path is None
+    assert exc.marker == 0xA2
+
+
+def test_load_iter_eof_pos() -> None:
+    unpacker = ormsgpack.load_iter(io.BytesIO(b"\x01\x92\x01"))
+    assert next(unpacker) == 1
+    exc = decode_error(lambda: next(unpacker))
+    assert exc.kind is MsgpackDecodeErrorKind.UNEXPECTED_EOF
+    assert exc.pos == 1
+    assert exc.marker == 0x92
+
+
+def test_kind_module() -> None:
+    assert MsgpackDecodeErrorKind.__module__ == "ormsgpack"
+    assert MsgpackDecodeErrorKind["UNEXPECTED_EOF"].name == "UNEXPECTED_EOF"
+
+
+@pytest.mark.parametrize(
+    "data,kwargs,exception",
+    (
+        pytest.param(b"\x92\x01", {}, ormsgpack.MsgpackTruncatedError, id="truncated"),
+        pytest.param(b"\xa1\xff", {}, ormsgpack.MsgpackInvalidUtf8Error, id="utf8"),
+        pytest.param(
+            b"\x91\x91\xc0", {"max_depth": 2}, ormsgpack.MsgpackDepthError, id="depth"
+        ),
+        pytest.param(b"\xd4\x01\x00", {}, ormsgpack.MsgpackExtError, id="ext"),
+        pytest.param(
+            b"\xa2ab", {"max_str_len": 1}, ormsgpack.MsgpackLimitError, id="limit"
+        ),
+    ),
+)
+def test_exception_class(data: bytes, kwargs: Any, exception: type) -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(data, **kwargs))
+    assert type(exc) is exception
+    assert isinstance(exc, ValueError)
+
+
+def test_depth_error_is_limit_error() -> None:
+    assert issubclass(ormsgpack.MsgpackDepthError, ormsgpack.MsgpackLimitError)
+
+
+def test_exception_class_base() -> None:
+    exc = decode_error(lambda: ormsgpack.unpackb(b"\xc1"))
+    assert type(exc) is ormsgpack.MsgpackDecodeError
+
+
+def test_load_iter_truncated() -> None:
+    unpacker = ormsgpack.load_iter(io.BytesIO(b"\x01\x92\x01"))
+    assert next(unpacker) == 1
+    with pytest.raises(ormsgpack.MsgpackTruncatedError):
+        next(unpacker)
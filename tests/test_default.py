@@ -39,7 +39,28 @@ def test_default_raises_exception() -> None:
 
     with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
         ormsgpack.packb(object(), default=default)
+    assert type(exc_info.value) is ormsgpack.MsgpackUnsupportedTypeError
     assert str(exc_info.value) == "Type is not msgpack serializable: object"
+    assert isinstance(exc_info.value.__cause__, NotImplementedError)
+
+
+def test_default_raises_unexpected_exception() -> None:
+    """
+    packb() default function raises an exception other than TypeError
+    """
+
+    def default(obj: object) -> object:
+        raise ValueError("invalid value")
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb({"a": object()}, default=default)
+    assert type(exc_info.value) is ormsgpack.MsgpackEncodeError
+    assert str(exc_info.value) == (
+        "Type is not msgpack serializable: object"
+        " (default raised ValueError: invalid value) at $.a"
+    )
+    assert isinstance(exc_info.value.__cause__, ValueError)
+    assert exc_info.value.__cause__.__traceback__ is not None
 
 
 def test_default_returns_invalid_string() -> None:
@@ -0,0 +1,184 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import dataclasses
+import io
+from typing import Any, Callable
+
+import pytest
+
+import ormsgpack
+
+
+class Unsupported:
+    pass
+
+
+@dataclasses.dataclass
+class Item:
+    price: Any
+
+
+def encode_error(func: Callable[[], Any]) -> ormsgpack.MsgpackEncodeError:
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        func()
+    return exc_info.value
+
+
+def test_path() -> None:
+    obj = {"orders": [{}] * 12 + [{"items": [Item(Unsupported())]}]}
+    exc = encode_error(lambda: ormsgpack.packb(obj))
+    assert exc.path == "$.orders[12].items[0].price"
+    assert str(exc) == (
+        "Type is not msgpack serializable: Unsupported"
+        " at $.orders[12].items[0].price"
+    )
+
+
+def test_path_root() -> None:
+    exc = encode_error(lambda: ormsgpack.packb(Unsupported()))
+    assert exc.path == "$"
+    assert str(exc) == "Type is not msgpack serializable: Unsupported"
+
+
+def test_path_tuple() -> None:
+    exc = encode_error(lambda: ormsgpack.packb((1, (2, Unsupported()))))
+    assert exc.path == "$[1][1]"
+
+
+def test_path_key() -> None:
+    exc = encode_error(lambda: ormsgpack.packb({"a b": [Unsupported()]}))
+    assert exc.path == "$['a b'][0]"
+
+
+def test_path_sorted_keys() -> None:
+    obj = {"b": 1, "a": {"c d": Unsupported()}}
+    exc = encode_error(
+        lambda: ormsgpack.packb(obj, option=ormsgpack.OPT_SORT_KEYS)
+    )
+    assert exc.path == "$.a['c d']"
+
+
+def test_path_non_str_keys() -> None:
+    obj = {1: {(2, "a"): Unsupported()}, "b": 1}
+    exc = encode_error(
+        lambda: ormsgpack.packb(obj, option=ormsgpack.OPT_NON_STR_KEYS)
+    )
+    assert exc.path == "$[1][(2, 'a')]"
+
+
+def test_path_elided() -> None:
+    obj: Any = Unsupported()
+    for _ in range(20):
+        obj = [obj]
+    exc = encode_error(lambda: ormsgpack.packb(obj))
+    assert exc.path == "$" + "[0]" * 8 + "..." + "[0]" * 8
+
+
+def test_path_invalid_key() -> None:
+    exc = encode_error(lambda: ormsgpack.packb({"a": {1: 2}}))
+    assert exc.path == "$.a"
+    assert str(exc) == "Dict key must be str at $.a"
+
+
+def test_path_default() -> None:
+    class Wrapper:
+        def __init__(self, value: Any) -> None:
+            self.value = value
+
+    def default(obj: Any) -> Any:
+        if isinstance(obj, Wrapper):
+            return [obj.value]
+        raise TypeError
+
+    obj = [Wrapper(Unsupported())]
+    exc = encode_error(lambda: ormsgpack.packb(obj, default=default))
+    assert exc.path == "$[0][0]"
+
+
+def test_path_reset() -> None:
+    packer = ormsgpack.Packer()
+    exc = encode_error(lambda: packer.pack([Unsupported()]))
+    assert exc.path == "$[0]"
+    exc = encode_error(lambda: packer.pack(Unsupported()))
+    assert exc.path == "$"
+
+
+def test_path_packb_into() -> None:
+    obj = {"a": [Unsupported()]}
+    exc = encode_error(lambda: ormsgpack.packb_into(obj, bytearray()))
+    assert exc.path == "$.a[0]"
+
+
+def test_path_dump() -> None:
+    exc = encode_error(lambda: ormsgpack.dump({"a": [Unsupported()]}, io.BytesIO()))
+    assert exc.path == "$.a[0]"
+
+
+def test_path_invalid_argument() -> None:
+    exc = encode_error(lambda: ormsgpack.packb(1, option=1 << 34))
+    assert exc.path is None
+    buffer = memoryview(bytearray(1))
+    exc = encode_error(lambda: ormsgpack.packb_into([1, 2], buffer))
+    assert isinstance(exc, ormsgpack.MsgpackBufferTooSmallError)
+    assert exc.path is None
+
+
+def test_unsupported_type() -> None:
+    exc = encode_error(lambda: ormsgpack.packb([Unsupported()]))
+    assert type(exc) is ormsgpack.MsgpackUnsupportedTypeError
+    assert isinstance(exc, TypeError)
+
+
+@pytest.mark.parametrize(
+    "exception,expected",
+    (
+        pytest.param(TypeError, ormsgpack.MsgpackUnsupportedTypeError, id="TypeError"),
+        pytest.param(
+            NotImplementedError,
+            ormsgpack.MsgpackUnsupportedTypeError,
+            id="NotImplementedError",
+        ),
+        pytest.param(ValueError, ormsgpack.MsgpackEncodeError, id="ValueError"),
+    ),
+)
+def test_default_failed(exception: type, expected: type) -> None:
+    def default(obj: Any) -> Any:
+        raise exception
+
+    exc = encode_error(lambda: ormsgpack.packb(Unsupported(), default=default))
+    assert type(exc) is expected
+    assert type(exc.__cause__) is exception
+
+
+def test_unsupported_type_non_str_key() -> None:
+    obj = {Unsupported(): 1}
+    exc = encode_error(
+        lambda: ormsgpack.packb(obj, option=ormsgpack.OPT_NON_STR_KEYS)
+    )
+    assert type(exc) is ormsgpack.MsgpackUnsupportedTypeError
+
+
+@pytest.mark.parametrize(
+    "obj,option",
+    (
+        pytest.param(2**64, None, id="value"),
+        pytest.param({2**64: 1}, ormsgpack.OPT_NON_STR_KEYS, id="key"),
+        pytest.param(
+            {(1, -(2**63) - 1): 1}, ormsgpack.OPT_NON_STR_KEYS, id="tuple key"
+        ),
+    ),
+)
+def test_integer_overflow(obj: Any, option: int | None) -> None:
+    exc = encode_error(lambda: ormsgpack.packb(obj, option=option))
+    assert type(exc) is ormsgpack.MsgpackIntegerOverflowError
+    assert isinstance(exc, TypeError)
+
+
+def test_exception_class_base() -> None:
+    exc = encode_error(lambda: ormsgpack.packb({1: 2}))
+    assert type(exc) is ormsgpack.MsgpackEncodeError
+    packer = ormsgpack.Packer()
+    exc = encode_error(lambda: packer.pack(2**64))
+    assert type(exc) is ormsgpack.MsgpackIntegerOverflowError
+    exc = encode_error(lambda: packer.pack({1: 2}))
+    assert type(exc) is ormsgpack.MsgpackEncodeError
@@ -0,0 +1,156 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import datetime
+import decimal
+import fractions
+import gc
+import weakref
+from typing import Any
+
+import pytest
+
+import ormsgpack
+
+
+class Point:
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+
+class Point3D(Point):
+    def __init__(self, x: int, y: int, z: int) -> None:
+        super().__init__(x, y)
+        self.z = z
+
+
+def encode_point(point: Point) -> list[int]:
+    return [point.x, point.y]
+
+
+def test_register_encoder() -> None:
+    ormsgpack.register_encoder(Point, encode_point)
+    try:
+        packed = ormsgpack.packb({"a": [Point(1, 2)]})
+        assert ormsgpack.unpackb(packed) == {"a": [[1, 2]]}
+        with pytest.raises(ormsgpack.MsgpackUnsupportedTypeError):
+            ormsgpack.packb(Point3D(1, 2, 3))
+    finally:
+        ormsgpack.register_encoder(Point, None)
+    with pytest.raises(ormsgpack.MsgpackUnsupportedTypeError):
+        ormsgpack.packb(Point(1, 2))
+
+
+def test_register_encoder_subclasses() -> None:
+    ormsgpack.register_encoder(Point, encode_point, subclasses=True)
+    try:
+        assert ormsgpack.unpackb(ormsgpack.packb(Point3D(1, 2, 3))) == [1, 2]
+        ormsgpack.register_encoder(Point3D, lambda point: point.z)
+        assert ormsgpack.unpackb(ormsgpack.packb(Point3D(1, 2, 3))) == 3
+    finally:
+        ormsgpack.register_encoder(Point, None)
+        ormsgpack.register_encoder(Point3D, None)
+
+
+def test_register_encoder_subclasses_gc() -> None:
+    class Point2D(Point):
+        pass
+
+    ormsgpack.register_encoder(Point, encode_point, subclasses=True)
+    try:
+        assert ormsgpack.unpackb(ormsgpack.packb(Point2D(1, 2))) == [1, 2]
+        ref = weakref.ref(Point2D)
+        del Point2D
+        gc.collect()
+        assert ref() is None
+    finally:
+        ormsgpack.register_encoder(Point, None)
+
+
+def test_register_encoder_before_default() -> None:
+    def default(obj: Any) -> Any:
+        raise AssertionError
+
+    ormsgpack.register_encoder(decimal.Decimal, str)
+    try:
+        packed = ormsgpack.packb(decimal.Decimal("1.5"), default=default)
+        assert ormsgpack.unpackb(packed) == "1.5"
+    finally:
+        ormsgpack.register_encoder(decimal.Decimal, None)
+
+
+def test_register_encoder_builtin_type() -> None:
+    date = datetime.date(2020, 1, 1)
+    ormsgpack.register_encoder(datetime.date, datetime.date.toordinal)
+    try:
+        assert ormsgpack.unpackb(ormsgpack.packb(date)) == date.toordinal()
+    finally:
+        ormsgpack.register_encoder(datetime.date, None)
+    assert ormsgpack.unpackb(ormsgpack.packb(date)) == "2020-01-01"
+
+
+def test_register_encoder_raises() -> None:
+    def encoder(obj: Any) -> Any:
+        raise ValueError("invalid point")
+
+    ormsgpack.register_encoder(Point, encoder)
+    try:
+        with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+            ormsgpack.packb({"a": Point(1, 2)})
+    finally:
+        ormsgpack.register_encoder(Point, None)
+    assert str(exc_info.value) == (
+        "Type is not msgpack serializable: Point "
+        "(encoder raised ValueError: invalid point) at $.a"
+    )
+    assert exc_info.value.path == "$.a"
+    assert isinstance(exc_info.value.__cause__, ValueError)
+
+
+def test_register_encoder_recursion() -> None:
+    ormsgpack.register_encoder(Point, lambda point: point)
+    try:
+        with pytest.raises(ormsgpack.MsgpackEncodeError):
+            ormsgpack.packb(Point(1, 2))
+    finally:
+        ormsgpack.register_encoder(Point, None)
+
+
+@pytest.mark.parametrize(
+    "args",
+    (
+        pytest.param((1, str), id="not a type"),
+        pytest.param((Point, 1), id="not callable"),
+    ),
+)
+def test_register_encoder_invalid(args: tuple[Any, Any]) -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.register_encoder(*args)
+
+
+def test_packer_encoders() -> None:
+    encoders = {
+        fractions.Fraction: lambda f: [f.numerator, f.denominator],
+        Point: encode_point,
+    }
+    packer = ormsgpack.Packer(encoders=encoders)
+    encoders.clear()
+    packed = packer.pack([fractions.Fraction(1, 3), Point(1, 2)])
+    assert ormsgpack.unpackb(packed) == [[1, 3], [1, 2]]
+    with pytest.raises(ormsgpack.MsgpackUnsupportedTypeError):
+        packer.pack(Point3D(1, 2, 3))
+
+
+def test_packer_encoders_precedence() -> None:
+    packer = ormsgpack.Packer(encoders={Point: lambda point: "packer"})
+    ormsgpack.register_encoder(Point, encode_point, subclasses=True)
+    try:
+        assert ormsgpack.unpackb(packer.pack(Point(1, 2))) == "packer"
+        assert ormsgpack.unpackb(packer.pack(Point3D(1, 2, 3))) == [1, 2]
+    finally:
+        ormsgpack.register_encoder(Point, None)
+
+
+def test_packer_encoders_invalid() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.Packer(encoders={1: str})
@@ -0,0 +1,322 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import dataclasses
+import gc
+import io
+import weakref
+from typing import Any
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+
+@dataclasses.dataclass
+class Point:
+    x: int
+    y: int
+
+
+class Token:
+    def __init__(self, value: bytes) -> None:
+        self.value = value
+
+
+def point_registry() -> ormsgpack.ExtRegistry:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(
+        Point,
+        1,
+        lambda point: [point.x, point.y],
+        lambda payload: Point(*payload),
+        recursive=True,
+    )
+    return registry
+
+
+def test_ext_registry_bytes() -> None:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(Token, 2, lambda token: token.value, Token)
+    packed = ormsgpack.packb([Token(b"abc")], ext_registry=registry)
+    assert packed == msgpack.packb([msgpack.ExtType(2, b"abc")])
+    obj = ormsgpack.unpackb(packed, ext_registry=registry)
+    assert isinstance(obj[0], Token)
+    assert obj[0].value == b"abc"
+
+
+def test_ext_registry_recursive() -> None:
+    registry = point_registry()
+    packed = ormsgpack.packb({"a": Point(1, 2)}, ext_registry=registry)
+    assert packed == msgpack.packb({"a": msgpack.ExtType(1, msgpack.packb([1, 2]))})
+    assert ormsgpack.unpackb(packed, ext_registry=registry) == {"a": Point(1, 2)}
+
+
+@dataclasses.dataclass
+class Line:
+    start: Point
+    end: Point
+
+
+def test_ext_registry_recursive_nested() -> None:
+    registry = point_registry()
+    registry.register(
+        Line,
+        3,
+        lambda line: [line.start, line.end],
+        lambda payload: Line(*payload),
+        recursive=True,
+    )
+    obj = [Line(Point(1, 2), Point(3, 4))]
+    packed = ormsgpack.packb(obj, ext_registry=registry)
+    assert ormsgpack.unpackb(packed, ext_registry=registry) == obj
+
+
+def test_ext_registry_recursive_option() -> None:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(
+        Token,
+        2,
+        lambda token: {"b": 1, "a": 2},
+        lambda payload: payload,
+        recursive=True,
+    )
+    packed = ormsgpack.packb(
+        Token(b""), option=ormsgpack.OPT_SORT_KEYS, ext_registry=registry
+    )
+    assert packed == msgpack.packb(
+        msgpack.ExtType(2, msgpack.packb({"a": 2, "b": 1}))
+    )
+
+
+def test_ext_registry_exact_type() -> None:
+    class SubPoint(Point):
+        pass
+
+    with pytest.raises(ormsgpack.MsgpackUnsupportedTypeError):
+        ormsgpack.packb(
+            SubPoint(1, 2),
+            ext_registry=point_registry(),
+            option=ormsgpack.OPT_PASSTHROUGH_DATACLASS,
+        )
+
+
+def test_ext_registry_ext_hook_fallback() -> None:
+    def ext_hook(tag: int, data: bytes) -> Any:
+        return (tag, data)
+
+    obj = [ormsgpack.Ext(5, b"x"), Point(1, 2)]
+    packed = ormsgpack.packb(obj, ext_registry=point_registry())
+    obj = ormsgpack.unpackb(packed, ext_hook=ext_hook, ext_registry=point_registry())
+    assert obj == [(5, b"x"), Point(1, 2)]
+    with pytest.raises(ormsgpack.MsgpackExtError, match="ext_hook missing"):
+        ormsgpack.unpackb(packed, ext_registry=point_registry())
+
+
+@pytest.mark.parametrize(
+    "tag,option",
+    (
+        (127, ormsgpack.OPT_TIMEDELTA_AS_EXT),
+        (126, ormsgpack.OPT_DECIMAL_AS_EXT),
+        (125, ormsgpack.OPT_BIG_INT_AS_EXT),
+        (124, ormsgpack.OPT_SET_AS_EXT),
+        (123, ormsgpack.OPT_SET_AS_EXT),
+    ),
+)
+def test_ext_registry_option_tag(tag: int, option: int) -> None:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(Token, tag, lambda token: token.value, Token)
+    packed = ormsgpack.packb(ormsgpack.Ext(tag, b"hello"))
+    obj = ormsgpack.unpackb(packed, option=option, ext_registry=registry)
+    assert isinstance(obj, Token)
+    assert obj.value == b"hello"
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, option=option, ext_hook=lambda tag, data: data)
+
+
+def test_ext_registry_not_set() -> None:
+    registry = point_registry()
+    packed = ormsgpack.packb(Point(1, 2), ext_registry=registry)
+    assert ormsgpack.unpackb(ormsgpack.packb(Point(1, 2))) == {"x": 1, "y": 2}
+    assert ormsgpack.unpackb(packed, ext_hook=lambda tag, data: tag) == 1
+
+
+def test_ext_registry_register_again() -> None:
+    registry = point_registry()
+    registry.register(Point, 4, lambda point: b"", lambda payload: "point")
+    packed = ormsgpack.packb(Point(1, 2), ext_registry=registry)
+    assert packed == msgpack.packb(msgpack.ExtType(4, b""))
+    assert ormsgpack.unpackb(packed, ext_registry=registry) == "point"
+    registry.register(Token, 4, lambda token: token.value, Token)
+    assert ormsgpack.unpackb(packed, ext_registry=registry).value == b""
+    with pytest.raises(ormsgpack.MsgpackUnsupportedTypeError):
+        ormsgpack.packb(
+            Point(1, 2),
+            ext_registry=registry,
+            option=ormsgpack.OPT_PASSTHROUGH_DATACLASS,
+        )
+
+
+def test_ext_registry_to_payload_exception() -> None:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(Token, 2, lambda token: 1 / 0, Token)
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb(Token(b""), ext_registry=registry)
+    assert isinstance(exc_info.value.__cause__, ZeroDivisionError)
+
+
+def test_ext_registry_to_payload_not_bytes() -> None:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(Token, 2, lambda token: "abc", Token)
+    with pytest.raises(ormsgpack.MsgpackEncodeError, match="must return bytes"):
+        ormsgpack.packb(Token(b""), ext_registry=registry)
+
+
+def test_ext_registry_from_payload_exception() -> None:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(Token, 2, lambda token: token.value, lambda payload: 1 / 0)
+    packed = ormsgpack.packb(Token(b""), ext_registry=registry)
+    with pytest.raises(ormsgpack.MsgpackExtError) as exc_info:
+        ormsgpack.unpackb(packed, ext_registry=registry)
+    assert exc_info.value.kind == ormsgpack.MsgpackDecodeErrorKind.EXT_HOOK_FAILED
+    assert str(exc_info.value).startswith("from_payload of extension type 2 failed")
+    assert isinstance(exc_info.value.__cause__, ZeroDivisionError)
+
+
+def test_ext_registry_recursive_invalid_payload() -> None:
+    registry = point_registry()
+    packed = msgpack.packb([msgpack.ExtType(1, b"\x92\x01\x02\x03")])
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, ext_registry=registry)
+    packed = msgpack.packb([msgpack.ExtType(1, b"\x93\x01\x02")])
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(packed, ext_registry=registry)
+    assert type(exc_info.value) is ormsgpack.MsgpackDecodeError
+    assert exc_info.value.kind == ormsgpack.MsgpackDecodeErrorKind.INVALID_VALUE
+    assert exc_info.value.pos == 4
+    assert exc_info.value.path == "$[0]"
+
+
+def test_ext_registry_recursive_depth() -> None:
+    registry = point_registry()
+    packed = ormsgpack.packb([[Point(1, 2)]], ext_registry=registry)
+    assert ormsgpack.unpackb(packed, ext_registry=registry, max_depth=5) == [
+        [Point(1, 2)]
+    ]
+    with pytest.raises(ormsgpack.MsgpackDepthError):
+        ormsgpack.unpackb(packed, ext_registry=registry, max_depth=4)
+
+
+def test_ext_registry_invalid_argument() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(1, ext_registry={})
+    with pytest.raises(TypeError):
+        ormsgpack.unpackb(b"\x01", ext_registry={})
+    assert ormsgpack.packb(1, ext_registry=None) == b"\x01"
+    assert ormsgpack.unpackb(b"\x01", ext_registry=None) == 1
+
+
+def test_ext_registry_packb_into() -> None:
+    registry = point_registry()
+    buffer = bytearray(b"\x00")
+    length = ormsgpack.packb_into([Point(1, 2)], buffer, 1, ext_registry=registry)
+    assert buffer[1:] == ormsgpack.packb([Point(1, 2)], ext_registry=registry)
+    assert length == len(buffer) - 1
+    assert ormsgpack.unpackb(buffer[1:], ext_registry=registry) == [Point(1, 2)]
+
+
+def test_ext_registry_dump_load() -> None:
+    registry = point_registry()
+    fp = io.BytesIO()
+    ormsgpack.dump([Point(1, 2)], fp, ext_registry=registry)
+    assert fp.getvalue() == ormsgpack.packb([Point(1, 2)], ext_registry=registry)
+    fp.seek(0)
+    assert ormsgpack.load(fp, ext_registry=registry) == [Point(1, 2)]
+
+
+def test_ext_registry_load_iter() -> None:
+    registry = point_registry()
+    fp = io.BytesIO()
+    ormsgpack.dump(Point(1, 2), fp, ext_registry=registry)
+    ormsgpack.dump([Point(3, 4)], fp, ext_registry=registry)
+    fp.seek(0)
+    assert list(ormsgpack.load_iter(fp, ext_registry=registry)) == [
+        Point(1, 2),
+        [Point(3, 4)],
+    ]
+
+
+def test_ext_registry_unpackb_partial() -> None:
+    registry = point_registry()
+    packed = ormsgpack.packb(Point(1, 2), ext_registry=registry)
+    data = packed + packed
+    assert ormsgpack.unpackb_partial(data, len(packed), ext_registry=registry) == (
+        Point(1, 2),
+        len(data),
+    )
+
+
+def test_ext_registry_unpacker() -> None:
+    registry = point_registry()
+    packed = ormsgpack.packb([Point(1, 2)], ext_registry=registry)
+    unpacker = ormsgpack.Unpacker(ext_registry=registry)
+    unpacker.feed(packed[:3])
+    assert list(unpacker) == []
+    unpacker.feed(packed[3:])
+    assert list(unpacker) == [[Point(1, 2)]]
+
+
+def test_ext_registry_invalid_argument_entry_points() -> None:
+    registry: Any = {}
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(1, bytearray(), ext_registry=registry)
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.dump(1, io.BytesIO(), ext_registry=registry)
+    with pytest.raises(TypeError):
+        ormsgpack.load(io.BytesIO(b"\x01"), ext_registry=registry)
+    with pytest.raises(TypeError):
+        ormsgpack.load_iter(io.BytesIO(b"\x01"), ext_registry=registry)
+    with pytest.raises(TypeError):
+        ormsgpack.unpackb_partial(b"\x01", ext_registry=registry)
+    with pytest.raises(TypeError):
+        ormsgpack.Unpacker(ext_registry=registry)
+
+
+def test_ext_registry_register_invalid() -> None:
+    registry = ormsgpack.ExtRegistry()
+    with pytest.raises(TypeError):
+        registry.register(Token(b""), 1, bytes, Token)  # type: ignore[arg-type]
+    with pytest.raises(TypeError):
+        registry.register(Token, "1", bytes, Token)  # type: ignore[arg-type]
+    with pytest.raises(TypeError):
+        registry.register(Token, True, bytes, Token)
+    with pytest.raises(ValueError):
+        registry.register(Token, 128, bytes, Token)
+    with pytest.raises(ValueError):
+        registry.register(Token, -1, bytes, Token)
+    with pytest.raises(TypeError):
+        registry.register(Token, 1, None, Token)  # type: ignore[arg-type]
+    with pytest.raises(TypeError):
+        registry.register(Token, 1, bytes, Token, recursive=1)  # type: ignore[arg-type]
+    with pytest.raises(TypeError):
+        registry.register(Token, 1, bytes)  # type: ignore[call-arg]
+    with pytest.raises(TypeError):
+        ormsgpack.ExtRegistry(1)  # type: ignore[call-arg]
+
+
+def test_ext_registry_gc_cycle() -> None:
+    class Owner:
+        pass
+
+    owner = Owner()
+    owner.registry = ormsgpack.ExtRegistry()  # type: ignore[attr-defined]
+    owner.registry.register(  # type: ignore[attr-defined]
+        Token,
+        1,
+        lambda token: owner and token.value,
+        lambda payload: Token(payload),
+    )
+    ref = weakref.ref(owner)
+    del owner
+    gc.collect()
+    assert ref() is None
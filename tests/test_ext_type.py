@@ -1,3 +1,7 @@
+import copy
+import datetime
+import pickle
+
 import msgpack
 import pytest
 
@@ -34,3 +38,169 @@ def test_ext_type(data: bytes) -> None:
 
     with pytest.raises(ormsgpack.MsgpackEncodeError):
         ormsgpack.packb({value: True}, option=ormsgpack.OPT_NON_STR_KEYS)
+
+
+def test_ext_hook_raises() -> None:
+    def ext_hook(tag: int, data: bytes) -> object:
+        raise ValueError("invalid data")
+
+    packed = msgpack.packb({(1, 2): msgpack.ExtType(1, b"")})
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(
+            packed, ext_hook=ext_hook, option=ormsgpack.OPT_NON_STR_KEYS
+        )
+    assert str(exc_info.value) == "ext_hook failed: ValueError: invalid data"
+    assert isinstance(exc_info.value.__cause__, ValueError)
+    assert exc_info.value.__cause__.__traceback__ is not None
+
+
+@pytest.mark.parametrize("tag", (-128, -1, 0, 127))
+def test_keep_unknown_ext(tag: int) -> None:
+    packed = ormsgpack.packb([ormsgpack.Ext(tag, b"abc"), 1])
+    unpacked = ormsgpack.unpackb(packed, option=ormsgpack.OPT_KEEP_UNKNOWN_EXT)
+    assert isinstance(unpacked[0], ormsgpack.Ext)
+    assert ormsgpack.packb(unpacked[0]) == ormsgpack.packb(ormsgpack.Ext(tag, b"abc"))
+    assert unpacked[1] == 1
+    assert ormsgpack.packb(unpacked) == packed
+
+
+@pytest.mark.parametrize("length", (1, 2, 3, 4, 8, 16, 32, 256, 65536))
+def test_keep_unknown_ext_roundtrip(length: int) -> None:
+    packed = msgpack.packb({"a": msgpack.ExtType(5, b"a" * length)})
+    unpacked = ormsgpack.unpackb(packed, option=ormsgpack.OPT_KEEP_UNKNOWN_EXT)
+    assert ormsgpack.packb(unpacked) == packed
+
+
+def test_keep_unknown_ext_timestamp() -> None:
+    packed = ormsgpack.packb(
+        datetime.datetime(2020, 1, 1, tzinfo=datetime.timezone.utc),
+        option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+    )
+    unpacked = ormsgpack.unpackb(packed, option=ormsgpack.OPT_KEEP_UNKNOWN_EXT)
+    assert isinstance(unpacked, ormsgpack.Ext)
+    assert ormsgpack.packb(unpacked) == packed
+    unpacked = ormsgpack.unpackb(
+        packed,
+        option=ormsgpack.OPT_KEEP_UNKNOWN_EXT
+        | ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+    )
+    assert unpacked == datetime.datetime(2020, 1, 1, tzinfo=datetime.timezone.utc)
+
+
+def test_keep_unknown_ext_ext_hook() -> None:
+    packed = ormsgpack.packb(ormsgpack.Ext(1, b"a"))
+    unpacked = ormsgpack.unpackb(
+        packed,
+        ext_hook=lambda x, y: (x, y),
+        option=ormsgpack.OPT_KEEP_UNKNOWN_EXT,
+    )
+    assert unpacked == (1, b"a")
+
+
+def test_keep_unknown_ext_ext_hook_none() -> None:
+    packed = b"\xd4\x01a"
+    option = ormsgpack.OPT_KEEP_UNKNOWN_EXT
+    unpacked = ormsgpack.unpackb(packed, ext_hook=None, option=option)
+    assert unpacked == ormsgpack.Ext(1, b"a")
+    unpacked, offset = ormsgpack.unpackb_partial(packed, ext_hook=None, option=option)
+    assert (unpacked, offset) == (ormsgpack.Ext(1, b"a"), 3)
+
+
+def test_ext_map_key() -> None:
+    packed = b"\x81\xd5\x05ab\x01"
+    option = ormsgpack.OPT_NON_STR_KEYS
+    unpacked = ormsgpack.unpackb(packed, option=option | ormsgpack.OPT_KEEP_UNKNOWN_EXT)
+    assert unpacked == {ormsgpack.Ext(5, b"ab"): 1}
+    unpacked = ormsgpack.unpackb(packed, ext_hook=lambda x, y: (x, y), option=option)
+    assert unpacked == {(5, b"ab"): 1}
+    registry = ormsgpack.ExtRegistry()
+    registry.register(str, 5, str.encode, bytes.decode)
+    unpacked = ormsgpack.unpackb(packed, ext_registry=registry, option=option)
+    assert unpacked == {"ab": 1}
+    with pytest.raises(ormsgpack.MsgpackExtError):
+        ormsgpack.unpackb(packed, option=option)
+
+
+def test_keep_unknown_ext_partial() -> None:
+    packed = ormsgpack.packb(ormsgpack.Ext(1, b"a")) + b"\x01"
+    unpacked, offset = ormsgpack.unpackb_partial(
+        packed, option=ormsgpack.OPT_KEEP_UNKNOWN_EXT
+    )
+    assert ormsgpack.packb(unpacked) == packed[:3]
+    assert offset == 3
+
+
+@pytest.mark.parametrize("tag", (-129, 128, 2**70))
+def test_ext_tag_out_of_range(tag: int) -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(ormsgpack.Ext(tag, b""))
+
+
+@pytest.mark.parametrize(
+    "data",
+    (
+        pytest.param(b"ab", id="bytes"),
+        pytest.param(bytearray(b"ab"), id="bytearray"),
+        pytest.param(memoryview(b"ab"), id="memoryview"),
+        pytest.param(memoryview(b"aXbX")[::2], id="non-contiguous memoryview"),
+    ),
+)
+def test_ext_bytes_like(data: bytes | bytearray | memoryview) -> None:
+    value = ormsgpack.Ext(1, data)
+    assert type(value.data) is bytes
+    assert value.data == b"ab"
+    assert ormsgpack.packb(value) == msgpack.packb(msgpack.ExtType(1, b"ab"))
+
+
+def test_ext_invalid_data() -> None:
+    with pytest.raises(TypeError):
+        ormsgpack.Ext(1, "ab")  # type: ignore[arg-type]
+    with pytest.raises(TypeError):
+        ormsgpack.Ext(1, [1, 2])  # type: ignore[arg-type]
+
+
+def test_ext_equality() -> None:
+    value = ormsgpack.Ext(1, b"ab")
+    assert value == ormsgpack.Ext(1, b"ab")
+    assert value != ormsgpack.Ext(2, b"ab")
+    assert value != ormsgpack.Ext(1, b"a")
+    assert value != (1, b"ab")
+    with pytest.raises(TypeError):
+        value < ormsgpack.Ext(1, b"ab")  # type: ignore[operator]
+
+
+def test_ext_hash() -> None:
+    assert hash(ormsgpack.Ext(1, b"ab")) == hash(ormsgpack.Ext(1, bytearray(b"ab")))
+    assert {ormsgpack.Ext(1, b"ab"): True}[ormsgpack.Ext(1, b"ab")]
+
+
+def test_ext_repr() -> None:
+    assert repr(ormsgpack.Ext(1, b"ab")) == "Ext(1, b'ab')"
+
+
+def test_ext_pickle() -> None:
+    value = ormsgpack.Ext(-1, b"ab")
+    assert pickle.loads(pickle.dumps(value)) == value
+    assert copy.deepcopy(value) == value
+
+
+def test_ext_match() -> None:
+    match ormsgpack.Ext(1, b"ab"):
+        case ormsgpack.Ext(tag, data):
+            assert (tag, data) == (1, b"ab")
+        case _:
+            raise AssertionError
+
+
+def test_ext_hook_ext_values() -> None:
+    packed = msgpack.packb([msgpack.ExtType(1, b"a"), msgpack.ExtType(2, b"b")])
+    unpacked = ormsgpack.unpackb(packed, ext_hook=ormsgpack.Ext)
+    assert unpacked == [ormsgpack.Ext(1, b"a"), ormsgpack.Ext(2, b"b")]
+
+
+def test_ext_attributes() -> None:
+    value = ormsgpack.Ext(1, b"ab")
+    assert value.tag == 1
+    assert value.data == b"ab"
+    with pytest.raises(AttributeError):
+        value.tag = 2  # type: ignore[misc]
@@ -0,0 +1,216 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import io
+from typing import Any
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+OBJ = {"a": [1, 2.5, None], "b": "c" * 300, "d": b"e" * 200000}
+
+
+class Writer:
+    def __init__(self) -> None:
+        self.chunks: list[bytes] = []
+
+    def write(self, data: bytes) -> None:
+        self.chunks.append(data)
+
+
+class Reader:
+    def __init__(self, data: bytes) -> None:
+        self.data = data
+        self.sizes: list[int] = []
+
+    def read(self, size: int) -> bytes:
+        self.sizes.append(size)
+        chunk = self.data[:size]
+        self.data = self.data[size:]
+        return chunk
+
+
+def test_dump() -> None:
+    fp = io.BytesIO()
+    ormsgpack.dump(OBJ, fp)
+    assert fp.getvalue() == ormsgpack.packb(OBJ)
+
+
+def test_dump_chunks() -> None:
+    obj = [["a" * 100] * 1000] * 10
+    fp = Writer()
+    ormsgpack.dump(obj, fp)
+    assert b"".join(fp.chunks) == ormsgpack.packb(obj)
+    assert len(fp.chunks) > 1
+    assert max(len(chunk) for chunk in fp.chunks) <= 65536
+
+
+def test_dump_large_value() -> None:
+    fp = Writer()
+    ormsgpack.dump(OBJ, fp)
+    assert b"".join(fp.chunks) == ormsgpack.packb(OBJ)
+
+
+def test_dump_args() -> None:
+    obj = {"b": object(), "a": 1}
+    fp = io.BytesIO()
+    ormsgpack.dump(obj, fp, str, ormsgpack.OPT_SORT_KEYS)
+    assert fp.getvalue() == ormsgpack.packb(
+        obj, default=str, option=ormsgpack.OPT_SORT_KEYS
+    )
+
+
+def test_dump_error() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.dump(object(), io.BytesIO())
+
+
+def test_dump_write_error() -> None:
+    class Error(Exception):
+        pass
+
+    class FailingWriter:
+        def write(self, data: bytes) -> None:
+            raise Error
+
+    with pytest.raises(Error):
+        ormsgpack.dump(OBJ, FailingWriter())
+    with pytest.raises(Error):
+        ormsgpack.dump(1, FailingWriter())
+
+
+def test_dump_no_write() -> None:
+    with pytest.raises(AttributeError):
+        ormsgpack.dump(1, object())
+
+
+def test_load() -> None:
+    fp = io.BytesIO(ormsgpack.packb(OBJ))
+    assert ormsgpack.load(fp) == OBJ
+
+
+def test_load_chunks() -> None:
+    fp = Reader(ormsgpack.packb(OBJ))
+    assert ormsgpack.load(fp) == OBJ
+    assert fp.sizes == [65536] * 4
+
+
+def test_load_trailing_data() -> None:
+    data = ormsgpack.packb(1) + ormsgpack.packb(2)
+    assert ormsgpack.load(io.BytesIO(data)) == 1
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.load(
+            io.BytesIO(data), option=ormsgpack.OPT_REJECT_TRAILING_DATA
+        )
+    fp = Reader(ormsgpack.packb(OBJ) + ormsgpack.packb(1))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.load(fp, option=ormsgpack.OPT_REJECT_TRAILING_DATA)
+    fp = Reader(ormsgpack.packb(OBJ))
+    assert ormsgpack.load(fp, option=ormsgpack.OPT_REJECT_TRAILING_DATA) == OBJ
+    assert fp.sizes == [65536] * 5
+
+
+def test_load_bounded_reads() -> None:
+    fp = Reader(ormsgpack.packb(1) + ormsgpack.packb(OBJ) * 10)
+    assert ormsgpack.load(fp) == 1
+    assert fp.sizes == [65536]
+
+
+def test_load_options() -> None:
+    data = msgpack.packb({1: msgpack.ExtType(1, b"a")})
+    assert ormsgpack.load(
+        io.BytesIO(data),
+        ext_hook=lambda tag, data: (tag, data),
+        option=ormsgpack.OPT_NON_STR_KEYS,
+    ) == {1: (1, b"a")}
+
+
+def test_load_error() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.load(io.BytesIO(b"\x92\x01"))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.load(io.StringIO("abc"))  # type: ignore[arg-type]
+
+
+def test_load_read_error() -> None:
+    class FailingReader:
+        def read(self, size: int) -> bytes:
+            raise OSError
+
+    with pytest.raises(OSError):
+        ormsgpack.load(FailingReader())
+    with pytest.raises(OSError):
+        next(ormsgpack.load_iter(FailingReader()))
+
+
+def test_load_iter() -> None:
+    objs = [OBJ, 1, [OBJ, None]] * 3
+    fp = io.BytesIO()
+    for obj in objs:
+        ormsgpack.dump(obj, fp)
+    fp.seek(0)
+    unpacker = ormsgpack.load_iter(fp)
+    assert isinstance(unpacker, ormsgpack.Unpacker)
+    assert list(unpacker) == objs
+
+
+def test_load_iter_bounded_reads() -> None:
+    data = ormsgpack.packb(list(range(100))) * 10000
+    fp = Reader(data)
+    unpacker = ormsgpack.load_iter(fp)
+    assert next(unpacker) == list(range(100))
+    assert fp.sizes == [65536]
+    assert sum(1 for _ in unpacker) == 9999
+
+
+def test_load_iter_empty() -> None:
+    assert list(ormsgpack.load_iter(io.BytesIO())) == []
+
+
+def test_load_iter_truncated() -> None:
+    unpacker = ormsgpack.load_iter(io.BytesIO(b"\x01\x92\x01"))
+    assert next(unpacker) == 1
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="unexpected end of file"):
+        next(unpacker)
+    assert list(unpacker) == []
+
+
+def test_load_iter_options() -> None:
+    data = msgpack.packb({1: msgpack.ExtType(1, b"a")})
+    unpacker = ormsgpack.load_iter(
+        io.BytesIO(data),
+        ext_hook=lambda tag, data: (tag, data),
+        option=ormsgpack.OPT_NON_STR_KEYS,
+    )
+    assert list(unpacker) == [{1: (1, b"a")}]
+
+
+def test_load_iter_max_buffer_size() -> None:
+    data = msgpack.packb("a" * 100)
+    unpacker = ormsgpack.load_iter(io.BytesIO(data), max_buffer_size=16)
+    with pytest.raises(ormsgpack.MsgpackLimitError, match="max_buffer_size exceeded"):
+        next(unpacker)
+    unpacker = ormsgpack.load_iter(io.BytesIO(data), max_buffer_size=None)
+    assert list(unpacker) == ["a" * 100]
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Invalid max_buffer_size"):
+        ormsgpack.load_iter(io.BytesIO(data), max_buffer_size=-1)
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.load(io.BytesIO(data), max_buffer_size=16)  # type: ignore[call-arg]
+
+
+def test_load_iter_reject_trailing_data() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.load_iter(io.BytesIO(), option=ormsgpack.OPT_REJECT_TRAILING_DATA)
+
+
+@pytest.mark.parametrize("func", (ormsgpack.load, ormsgpack.load_iter))
+def test_load_args(func: Any) -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        func()
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        func(io.BytesIO(), io.BytesIO())
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        func(io.BytesIO(), default=None)
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        func(io.BytesIO(), option=ormsgpack.OPT_SORT_KEYS)
@@ -84,3 +84,109 @@ def test_int_128_passthrough(value: int) -> None:
     )
     assert list(result.keys()) == ["int"]
     assert int.from_bytes(result["int"], "little", signed=True) == value
+
+
+@pytest.mark.parametrize(
+    "value",
+    (
+        -9223372036854775809,
+        18446744073709551616,
+        2**128 - 1,
+        -(2**1000),
+    ),
+)
+def test_int_big_ext(value: int) -> None:
+    option = ormsgpack.OPT_BIG_INT_AS_EXT
+    packed = ormsgpack.packb([value], option=option)
+    assert ormsgpack.unpackb(packed, option=option) == [value]
+
+
+@pytest.mark.parametrize(
+    ("value", "data"),
+    (
+        (18446744073709551616, b"\x00\x01" + b"\x00" * 8),
+        (-9223372036854775809, b"\x01\x80\x00\x00\x00\x00\x00\x00\x01"),
+    ),
+)
+def test_int_big_ext_format(value: int, data: bytes) -> None:
+    packed = ormsgpack.packb(value, option=ormsgpack.OPT_BIG_INT_AS_EXT)
+    assert packed == msgpack.packb(msgpack.ExtType(125, data))
+    packed = ormsgpack.packb(value, option=ormsgpack.OPT_BIG_INT_AS_BIN)
+    assert packed == msgpack.packb(data)
+
+
+def test_int_big_ext_64() -> None:
+    value = [18446744073709551615, -9223372036854775808]
+    packed = ormsgpack.packb(value, option=ormsgpack.OPT_BIG_INT_AS_EXT)
+    assert packed == msgpack.packb(value)
+
+
+def test_int_big_ext_map_key() -> None:
+    option = ormsgpack.OPT_BIG_INT_AS_EXT | ormsgpack.OPT_NON_STR_KEYS
+    obj = {2**64: 1, -(2**64): 2}
+    packed = ormsgpack.packb(obj, option=option)
+    assert ormsgpack.unpackb(packed, option=option) == obj
+
+
+def test_int_big_ext_subclass() -> None:
+    class Big(int):
+        pass
+
+    option = ormsgpack.OPT_BIG_INT_AS_EXT
+    packed = ormsgpack.packb(Big(2**64), option=option)
+    assert ormsgpack.unpackb(packed, option=option) == 2**64
+
+
+@pytest.mark.parametrize(
+    "option",
+    (ormsgpack.OPT_BIG_INT_AS_EXT, ormsgpack.OPT_BIG_INT_AS_BIN),
+)
+@pytest.mark.parametrize("value", (2**70, -(2**70)))
+def test_int_big_ext_subclass_abs(option: int, value: int) -> None:
+    class StrAbs(int):
+        def __abs__(self) -> str:  # type: ignore[override]
+            return "x"
+
+    class FailingAbs(int):
+        def __abs__(self) -> int:
+            raise ValueError
+
+    expected = ormsgpack.packb(value, option=option)
+    assert ormsgpack.packb(StrAbs(value), option=option) == expected
+    assert ormsgpack.packb(FailingAbs(value), option=option) == expected
+
+
+def test_int_big_ext_passthrough() -> None:
+    packed = ormsgpack.packb(
+        2**64,
+        option=ormsgpack.OPT_BIG_INT_AS_EXT | ormsgpack.OPT_PASSTHROUGH_BIG_INT,
+        default=str,
+    )
+    assert packed == msgpack.packb(str(2**64))
+
+
+def test_int_big_options_exclusive() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError, match="Invalid opts"):
+        ormsgpack.packb(
+            2**64,
+            option=ormsgpack.OPT_BIG_INT_AS_EXT | ormsgpack.OPT_BIG_INT_AS_BIN,
+        )
+
+
+def test_int_big_ext_not_enabled() -> None:
+    packed = ormsgpack.packb(2**64, option=ormsgpack.OPT_BIG_INT_AS_EXT)
+    with pytest.raises(ormsgpack.MsgpackExtError):
+        ormsgpack.unpackb(packed)
+
+
+@pytest.mark.parametrize(
+    "data",
+    (
+        pytest.param(b"", id="empty"),
+        pytest.param(b"\x02\x01", id="sign"),
+    ),
+)
+def test_int_big_ext_invalid(data: bytes) -> None:
+    packed = msgpack.packb(msgpack.ExtType(125, data))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, option=ormsgpack.OPT_BIG_INT_AS_EXT)
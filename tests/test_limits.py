@@ -0,0 +1,192 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import io
+from typing import Any
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+CASES = (
+    pytest.param("max_str_len", "a" * 10, id="str"),
+    pytest.param("max_bin_len", b"a" * 10, id="bin"),
+    pytest.param("max_array_len", list(range(10)), id="array"),
+    pytest.param("max_map_len", {str(i): i for i in range(10)}, id="map"),
+    pytest.param("max_ext_len", msgpack.ExtType(1, b"a" * 10), id="ext"),
+)
+
+
+def ext_hook(tag: int, data: bytes) -> Any:
+    return msgpack.ExtType(tag, data)
+
+
+@pytest.mark.parametrize("name,obj", CASES)
+def test_unpackb_limit(name: str, obj: Any) -> None:
+    data = msgpack.packb(obj)
+    assert ormsgpack.unpackb(data, ext_hook=ext_hook, **{name: 10}) == obj
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"{name} exceeded"):
+        ormsgpack.unpackb(data, ext_hook=ext_hook, **{name: 9})
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"{name} exceeded"):
+        ormsgpack.unpackb(msgpack.packb([[obj]]), ext_hook=ext_hook, **{name: 9})
+
+
+@pytest.mark.parametrize("name,obj", CASES)
+def test_unpackb_partial_limit(name: str, obj: Any) -> None:
+    data = msgpack.packb(obj)
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"{name} exceeded"):
+        ormsgpack.unpackb_partial(data, ext_hook=ext_hook, **{name: 9})
+
+
+def test_unpackb_map_key_limits() -> None:
+    data = msgpack.packb({"a" * 10: 1})
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_str_len exceeded"):
+        ormsgpack.unpackb(data, max_str_len=9)
+    data = msgpack.packb({(1, 2, 3): 1})
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_array_len exceeded"):
+        ormsgpack.unpackb(data, option=ormsgpack.OPT_NON_STR_KEYS, max_array_len=2)
+
+
+def test_unpackb_max_depth() -> None:
+    data = msgpack.packb([[[1]]])
+    assert ormsgpack.unpackb(data, max_depth=4) == [[[1]]]
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Recursion limit reached"):
+        ormsgpack.unpackb(data, max_depth=3)
+    assert ormsgpack.unpackb(b"\x90", max_depth=1) == []
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Recursion limit reached"):
+        ormsgpack.unpackb(b"\x01", max_depth=0)
+
+
+@pytest.mark.parametrize("max_depth", (1, 2, 10, 1023))
+def test_unpackb_max_depth_boundary(max_depth: int) -> None:
+    data = b"\x91" * (max_depth - 1) + b"\x01"
+    assert ormsgpack.unpackb(data, max_depth=max_depth) is not None
+    with pytest.raises(ormsgpack.MsgpackLimitError):
+        ormsgpack.unpackb(b"\x91" + data, max_depth=max_depth)
+    data = b"\x81\xa1a" * (max_depth - 1) + b"\x01"
+    assert ormsgpack.unpackb(data, max_depth=max_depth) is not None
+    with pytest.raises(ormsgpack.MsgpackLimitError):
+        ormsgpack.unpackb(b"\x81\xa1a" + data, max_depth=max_depth)
+
+
+def test_unpackb_default_max_depth() -> None:
+    assert ormsgpack.unpackb(b"\x91" * 1022 + b"\x90") is not None
+    assert ormsgpack.unpackb(b"\x91" * 1022 + b"\x01") is not None
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Recursion limit reached"):
+        ormsgpack.unpackb(b"\x91" * 1023 + b"\x90")
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Recursion limit reached"):
+        ormsgpack.unpackb(b"\x91" * 1023 + b"\x01")
+
+
+def test_unpackb_max_alloc_size() -> None:
+    data = msgpack.packb(["a" * 10, b"b" * 10])
+    assert ormsgpack.unpackb(data, max_alloc_size=36) == ["a" * 10, b"b" * 10]
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_alloc_size exceeded"):
+        ormsgpack.unpackb(data, max_alloc_size=35)
+    data = msgpack.packb({"a": None})
+    assert ormsgpack.unpackb(data, max_alloc_size=17) == {"a": None}
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_alloc_size exceeded"):
+        ormsgpack.unpackb(data, max_alloc_size=16)
+
+
+@pytest.mark.parametrize(
+    "data",
+    (
+        pytest.param(b"\xdd\xff\xff\xff\xff", id="array32"),
+        pytest.param(b"\xdf\xff\xff\xff\xff", id="map32"),
+        pytest.param(b"\x91\xdd\xff\xff\xff\xff", id="nested array32"),
+        pytest.param(b"\xdb\xff\xff\xff\xff", id="str32"),
+    ),
+)
+def test_unpackb_truncated_header(data: bytes) -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(data)
+
+
+def test_unpackb_limits_none() -> None:
+    data = msgpack.packb(["a" * 10] * 10)
+    assert ormsgpack.unpackb(
+        data,
+        max_depth=None,
+        max_str_len=None,
+        max_bin_len=None,
+        max_array_len=None,
+        max_map_len=None,
+        max_ext_len=None,
+        max_alloc_size=None,
+    ) == ["a" * 10] * 10
+
+
+@pytest.mark.parametrize(
+    "name",
+    (
+        "max_depth",
+        "max_str_len",
+        "max_bin_len",
+        "max_array_len",
+        "max_map_len",
+        "max_ext_len",
+        "max_alloc_size",
+    ),
+)
+@pytest.mark.parametrize("value", (-1, 2**64, 1.0, "1", True))
+def test_invalid_limit(name: str, value: Any) -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"Invalid {name}"):
+        ormsgpack.unpackb(b"\xc0", **{name: value})
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"Invalid {name}"):
+        ormsgpack.unpackb_partial(b"\xc0", **{name: value})
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"Invalid {name}"):
+        ormsgpack.Unpacker(**{name: value})
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"Invalid {name}"):
+        ormsgpack.load(io.BytesIO(b"\xc0"), **{name: value})
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"Invalid {name}"):
+        ormsgpack.load_iter(io.BytesIO(b"\xc0"), **{name: value})
+
+
+def test_invalid_max_depth() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Invalid max_depth"):
+        ormsgpack.unpackb(b"\xc0", max_depth=1024)
+
+
+@pytest.mark.parametrize("name,obj", CASES)
+def test_unpacker_limit(name: str, obj: Any) -> None:
+    unpacker = ormsgpack.Unpacker(ext_hook=ext_hook, **{name: 9})
+    unpacker.feed(msgpack.packb(1) + msgpack.packb(obj))
+    assert next(unpacker) == 1
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match=f"{name} exceeded"):
+        next(unpacker)
+    unpacker.feed(msgpack.packb(2))
+    assert list(unpacker) == [2]
+
+
+def test_unpacker_limit_header() -> None:
+    unpacker = ormsgpack.Unpacker(max_array_len=100)
+    unpacker.feed(b"\xdd\xff\xff\xff\xff")
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_array_len exceeded"):
+        next(unpacker)
+    unpacker = ormsgpack.Unpacker(max_bin_len=100)
+    unpacker.feed(b"\xc6\xff\xff\xff\xff")
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_bin_len exceeded"):
+        next(unpacker)
+
+
+def test_unpacker_max_depth() -> None:
+    unpacker = ormsgpack.Unpacker(max_depth=3)
+    unpacker.feed(msgpack.packb([[1]]) + msgpack.packb([[[1]]]))
+    assert next(unpacker) == [[1]]
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Recursion limit reached"):
+        next(unpacker)
+
+
+def test_load_limit() -> None:
+    data = msgpack.packb("a" * 10)
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_str_len exceeded"):
+        ormsgpack.load(io.BytesIO(data), max_str_len=9)
+
+
+def test_load_iter_limit() -> None:
+    data = msgpack.packb(1) + msgpack.packb("a" * 10)
+    unpacker = ormsgpack.load_iter(io.BytesIO(data), max_str_len=9)
+    assert next(unpacker) == 1
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="max_str_len exceeded"):
+        next(unpacker)
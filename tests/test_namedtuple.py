@@ -0,0 +1,124 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import collections
+import gc
+import weakref
+from typing import Any, NamedTuple, Optional
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+
+class Point(NamedTuple):
+    x: int
+    y: int
+
+
+class Node(NamedTuple):
+    name: str
+    child: Optional["Node"] = None
+
+
+def test_namedtuple() -> None:
+    packed = ormsgpack.packb(Point(1, 2), option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE)
+    assert packed == msgpack.packb({"x": 1, "y": 2})
+
+
+def test_namedtuple_collections() -> None:
+    Pair = collections.namedtuple("Pair", ("first", "second"))
+    packed = ormsgpack.packb(
+        [Pair("a", [1]), Pair(None, Pair(1, 2))],
+        option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE,
+    )
+    assert packed == msgpack.packb(
+        [
+            {"first": "a", "second": [1]},
+            {"first": None, "second": {"first": 1, "second": 2}},
+        ]
+    )
+
+
+def test_namedtuple_typed_roundtrip() -> None:
+    obj = Node("a", Node("b"))
+    packed = ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE)
+    unpacked = ormsgpack.unpackb(packed, type=Node)
+    assert type(unpacked) is Node
+    assert unpacked == obj
+
+
+def test_namedtuple_not_enabled() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(Point(1, 2))
+    assert ormsgpack.packb(Point(1, 2), default=tuple) == msgpack.packb([1, 2])
+
+
+def test_namedtuple_collections_precedence() -> None:
+    packed = ormsgpack.packb(
+        Point(1, 2),
+        option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE
+        | ormsgpack.OPT_SERIALIZE_COLLECTIONS,
+    )
+    assert packed == msgpack.packb({"x": 1, "y": 2})
+
+
+def test_namedtuple_tuple_subclass() -> None:
+    class SubTuple(tuple):  # type: ignore[type-arg]
+        pass
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(SubTuple((1, 2)), option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE)
+
+
+@pytest.mark.parametrize(
+    "fields",
+    (
+        pytest.param(["x"], id="list"),
+        pytest.param((1,), id="not str"),
+    ),
+)
+def test_namedtuple_invalid_fields(fields: Any) -> None:
+    cls = type("Invalid", (tuple,), {"_fields": fields})
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(cls((1,)), option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE)
+
+
+def test_namedtuple_fields_cache_gc() -> None:
+    Pair = collections.namedtuple("Pair", ("first", "second"))
+    for _ in range(2):
+        packed = ormsgpack.packb(
+            Pair(1, 2), option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE
+        )
+        assert packed == msgpack.packb({"first": 1, "second": 2})
+    ref = weakref.ref(Pair)
+    del Pair
+    gc.collect()
+    assert ref() is None
+
+
+def test_namedtuple_cache_gc() -> None:
+    class SubTuple(tuple):  # type: ignore[type-arg]
+        pass
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(SubTuple((1, 2)), option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE)
+    ref = weakref.ref(SubTuple)
+    del SubTuple
+    gc.collect()
+    assert ref() is None
+
+
+def test_namedtuple_length_mismatch() -> None:
+    obj = tuple.__new__(Point, (1, 2, 3))
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE)
+
+
+def test_namedtuple_error_path() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb(
+            Point(1, object()),  # type: ignore[arg-type]
+            option=ormsgpack.OPT_SERIALIZE_NAMEDTUPLE,
+        )
+    assert exc_info.value.path == "$.y"
@@ -1,5 +1,7 @@
 # SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+import datetime
+
 import msgpack
 import pytest
 
@@ -632,3 +634,80 @@ def test_numpy_datetime64_unsupported_unit() -> None:
             numpy.datetime64("NaT"),
             option=ormsgpack.OPT_SERIALIZE_NUMPY,
         )
+
+
+def test_timestamp_to_datetime64() -> None:
+    value = ormsgpack.Timestamp(1577836800, 123456789)
+    assert value.to_datetime64() == numpy.datetime64(
+        "2020-01-01T00:00:00.123456789", "ns"
+    )
+
+
+@pytest.mark.parametrize(
+    ("value", "expected"),
+    (
+        pytest.param(numpy.timedelta64(2, "W"), "P14D", id="weeks"),
+        pytest.param(numpy.timedelta64(-1, "D"), "-P1D", id="days"),
+        pytest.param(numpy.timedelta64(25, "h"), "P1DT1H", id="hours"),
+        pytest.param(numpy.timedelta64(90, "m"), "PT1H30M", id="minutes"),
+        pytest.param(numpy.timedelta64(61, "s"), "PT1M1S", id="seconds"),
+        pytest.param(numpy.timedelta64(1500, "ms"), "PT1.5S", id="milliseconds"),
+        pytest.param(numpy.timedelta64(-1, "us"), "-PT0.000001S", id="microseconds"),
+        pytest.param(numpy.timedelta64(1, "ns"), "PT0.000000001S", id="nanoseconds"),
+    ),
+)
+def test_numpy_timedelta64(value: object, expected: str) -> None:
+    option = ormsgpack.OPT_SERIALIZE_NUMPY | ormsgpack.OPT_TIMEDELTA_AS_ISO8601
+    assert ormsgpack.unpackb(ormsgpack.packb(value, option=option)) == expected
+    array = numpy.array([value, value])
+    assert ormsgpack.unpackb(ormsgpack.packb(array, option=option)) == [
+        expected,
+        expected,
+    ]
+
+
+def test_numpy_timedelta64_ext() -> None:
+    option = ormsgpack.OPT_SERIALIZE_NUMPY | ormsgpack.OPT_TIMEDELTA_AS_EXT
+    array = numpy.array([1500, -1], "timedelta64[ms]")
+    packed = ormsgpack.packb(array, option=option)
+    assert ormsgpack.unpackb(packed, option=ormsgpack.OPT_TIMEDELTA_AS_EXT) == [
+        datetime.timedelta(seconds=1.5),
+        datetime.timedelta(milliseconds=-1),
+    ]
+    value = numpy.timedelta64(1001, "ns")
+    packed = ormsgpack.packb(value, option=option)
+    assert ormsgpack.unpackb(packed, option=ormsgpack.OPT_TIMEDELTA_AS_EXT) == (
+        datetime.timedelta(microseconds=1)
+    )
+    packed = ormsgpack.packb(
+        value, option=ormsgpack.OPT_SERIALIZE_NUMPY | ormsgpack.OPT_TIMEDELTA_AS_SECONDS
+    )
+    assert ormsgpack.unpackb(packed) == 1.001e-6
+
+
+def test_numpy_timedelta64_not_enabled() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(
+            numpy.timedelta64(1, "s"), option=ormsgpack.OPT_SERIALIZE_NUMPY
+        )
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(
+            numpy.array([1], "timedelta64[s]"), option=ormsgpack.OPT_SERIALIZE_NUMPY
+        )
+
+
+@pytest.mark.parametrize(
+    "value",
+    (
+        pytest.param(numpy.timedelta64("NaT", "s"), id="NaT"),
+        pytest.param(numpy.timedelta64(1, "Y"), id="years"),
+        pytest.param(numpy.timedelta64(1, "ps"), id="picoseconds"),
+        pytest.param(numpy.timedelta64(2**62, "W"), id="overflow"),
+    ),
+)
+def test_numpy_timedelta64_unrepresentable(value: object) -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(
+            value,
+            option=ormsgpack.OPT_SERIALIZE_NUMPY | ormsgpack.OPT_TIMEDELTA_AS_ISO8601,
+        )
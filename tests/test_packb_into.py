@@ -0,0 +1,174 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import mmap
+from typing import Any
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+OBJ = {"a": [1, 2.5, None], "b": "c" * 300}
+
+
+def test_packb_into_bytearray() -> None:
+    packed = ormsgpack.packb(OBJ)
+    buffer = bytearray()
+    assert ormsgpack.packb_into(OBJ, buffer) == len(packed)
+    assert buffer == packed
+
+
+def test_packb_into_bytearray_offset() -> None:
+    packed = ormsgpack.packb(OBJ)
+    buffer = bytearray(b"xyz")
+    assert ormsgpack.packb_into(OBJ, buffer, 3) == len(packed)
+    assert buffer == b"xyz" + packed
+    assert ormsgpack.packb_into(1, buffer, offset=1) == 1
+    assert buffer == b"x\x01z" + packed
+
+
+def test_packb_into_bytearray_exported() -> None:
+    buffer = bytearray(2)
+    view = memoryview(buffer)
+    assert ormsgpack.packb_into(1, buffer, 1) == 1
+    assert buffer == b"\x00\x01"
+    with pytest.raises(ormsgpack.MsgpackBufferTooSmallError):
+        ormsgpack.packb_into([1, 2], buffer, 1)
+    view.release()
+
+
+def test_packb_into_bytearray_default() -> None:
+    buffer = bytearray()
+
+    def default(obj: Any) -> Any:
+        buffer.extend(b"\x00" * 1000)
+        return None
+
+    assert ormsgpack.packb_into([object(), 1], buffer, default=default) == 3
+    assert buffer[:3] == b"\x92\xc0\x01"
+
+
+def test_packb_into_bytearray_cleared() -> None:
+    buffer = bytearray()
+
+    def default(obj: Any) -> Any:
+        buffer.clear()
+        return None
+
+    obj = ["a" * 50, object(), "b"]
+    packed = msgpack.packb(["a" * 50, None, "b"])
+    assert ormsgpack.packb_into(obj, buffer, default=default) == len(packed)
+    assert buffer == packed
+
+
+@pytest.mark.parametrize(
+    ("length", "offset"),
+    (
+        pytest.param(10, 10, id="end"),
+        pytest.param(100000, 0, id="start"),
+    ),
+)
+def test_packb_into_bytearray_self(length: int, offset: int) -> None:
+    buffer = bytearray(b"y" * length)
+    packed = msgpack.packb(bytes(buffer))
+    assert ormsgpack.packb_into(buffer, buffer, offset) == len(packed)
+    assert buffer == b"y" * offset + packed
+
+
+def test_packb_into_bytearray_error() -> None:
+    buffer = bytearray(b"qqq")
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(["x" * 10, object()], buffer)
+    assert buffer == b"qqq"
+
+
+def test_packb_into_memoryview() -> None:
+    packed = ormsgpack.packb(OBJ)
+    buffer = bytearray(len(packed) + 2)
+    assert ormsgpack.packb_into(OBJ, memoryview(buffer), 2) == len(packed)
+    assert buffer == b"\x00\x00" + packed
+
+
+def test_packb_into_mmap() -> None:
+    packed = ormsgpack.packb(OBJ)
+    with mmap.mmap(-1, 4096) as buffer:
+        assert ormsgpack.packb_into(OBJ, buffer, 100) == len(packed)
+        assert buffer[100 : 100 + len(packed)] == packed
+
+
+def test_packb_into_memoryview_self() -> None:
+    buffer = bytearray(b"y" * 100)
+    packed = msgpack.packb(b"y" * 10)
+    assert ormsgpack.packb_into(memoryview(buffer)[:10], memoryview(buffer)) == 12
+    assert buffer == packed + b"y" * 88
+    with pytest.raises(ormsgpack.MsgpackBufferTooSmallError):
+        ormsgpack.packb_into(buffer, memoryview(buffer), 50)
+    assert buffer == packed + b"y" * 88
+
+
+def test_packb_into_too_small() -> None:
+    buffer = bytearray(b"abcd")
+    with pytest.raises(ormsgpack.MsgpackBufferTooSmallError):
+        ormsgpack.packb_into([1, 2, 3, 4, 5], memoryview(buffer))
+    assert buffer == b"abcd"
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(OBJ, memoryview(buffer))
+    assert buffer == b"abcd"
+    assert ormsgpack.packb_into([1, 2, 3], memoryview(buffer)) == 4
+
+
+def test_packb_into_mmap_too_small() -> None:
+    with mmap.mmap(-1, 4096) as buffer:
+        with pytest.raises(ormsgpack.MsgpackBufferTooSmallError):
+            ormsgpack.packb_into("a" * 100, buffer, 4000)
+        assert buffer[:] == b"\x00" * 4096
+
+
+def test_packb_into_error() -> None:
+    buffer = bytearray(10)
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb_into(["x", object()], memoryview(buffer))
+    assert not isinstance(exc_info.value, ormsgpack.MsgpackBufferTooSmallError)
+    assert buffer == bytearray(10)
+
+
+def test_packb_into_option() -> None:
+    obj = {"b": 1, "a": 2}
+    buffer = bytearray()
+    ormsgpack.packb_into(obj, buffer, option=ormsgpack.OPT_SORT_KEYS)
+    assert buffer == ormsgpack.packb(obj, option=ormsgpack.OPT_SORT_KEYS)
+
+
+@pytest.mark.parametrize(
+    "buffer",
+    (
+        pytest.param(b"abc", id="bytes"),
+        pytest.param(memoryview(b"abc"), id="readonly memoryview"),
+        pytest.param(memoryview(bytearray(b"abcd"))[::2], id="non contiguous"),
+        pytest.param("abc", id="str"),
+    ),
+)
+def test_packb_into_invalid_buffer(buffer: Any) -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(1, buffer)
+
+
+@pytest.mark.parametrize("offset", (-1, 4, 2**64))
+def test_packb_into_invalid_offset(offset: int) -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError, match="offset out of range"):
+        ormsgpack.packb_into(1, bytearray(3), offset)
+    with pytest.raises(ormsgpack.MsgpackEncodeError, match="offset out of range"):
+        ormsgpack.packb_into(1, memoryview(bytearray(3)), offset)
+
+
+def test_packb_into_args() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(1)  # type: ignore[call-arg]
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(1, bytearray(), 0, 0)  # type: ignore[call-arg]
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(1, bytearray(), 0, offset=0)  # type: ignore[misc]
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(1, bytearray(), "0")  # type: ignore[arg-type]
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb_into(1, bytearray(), ext_hook=None)  # type: ignore[call-arg]
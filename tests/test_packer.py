@@ -0,0 +1,162 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import dataclasses
+import datetime
+import gc
+import weakref
+from typing import Any
+
+import pytest
+
+import ormsgpack
+
+
+@dataclasses.dataclass
+class Dataclass:
+    a: int
+    b: str
+
+
+OBJECTS = (
+    None,
+    1,
+    "a" * 300,
+    b"b" * 70000,
+    [1, [2, [3, {}]]],
+    {"b": 1, "a": {"d": None, "c": 2}},
+    Dataclass(1, "b"),
+    datetime.datetime(2020, 1, 1, 12, 30, 15, 123456),
+)
+
+
+@pytest.mark.parametrize(
+    "option",
+    (
+        None,
+        ormsgpack.OPT_SORT_KEYS,
+        ormsgpack.OPT_NAIVE_UTC | ormsgpack.OPT_OMIT_MICROSECONDS,
+        ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+    ),
+)
+def test_packer(option: int | None) -> None:
+    packer = ormsgpack.Packer(option=option)
+    for obj in OBJECTS:
+        assert packer.pack(obj) == ormsgpack.packb(obj, option=option)
+
+
+def test_packer_reuse() -> None:
+    packer = ormsgpack.Packer()
+    assert packer.pack("a" * 100000) == ormsgpack.packb("a" * 100000)
+    assert packer.pack(1) == b"\x01"
+    assert packer.pack([]) == b"\x90"
+
+
+def test_packer_default() -> None:
+    packer = ormsgpack.Packer(default=str)
+    obj = object()
+    assert packer.pack(obj) == ormsgpack.packb(obj, default=str)
+    packer = ormsgpack.Packer(str, ormsgpack.OPT_PASSTHROUGH_DATACLASS)
+    dataclass_obj = Dataclass(1, "b")
+    assert packer.pack(dataclass_obj) == ormsgpack.packb(str(dataclass_obj))
+
+
+def test_packer_default_none() -> None:
+    packer = ormsgpack.Packer(default=None)
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        packer.pack(object())
+
+
+def test_packer_default_raises() -> None:
+    def default(obj: Any) -> Any:
+        raise NotImplementedError
+
+    packer = ormsgpack.Packer(default=default)
+    for _ in range(300):
+        with pytest.raises(ormsgpack.MsgpackEncodeError):
+            packer.pack(object())
+    assert packer.pack(1) == b"\x01"
+
+
+def test_packer_default_reentrant() -> None:
+    packer: ormsgpack.Packer
+
+    def default(obj: Any) -> Any:
+        return packer.pack(str(obj))
+
+    packer = ormsgpack.Packer(default=default)
+    obj = object()
+    assert packer.pack([obj]) == ormsgpack.packb(
+        [ormsgpack.packb(str(obj))],
+    )
+
+
+def test_packer_error() -> None:
+    packer = ormsgpack.Packer()
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        packer.pack({1: True})
+    assert packer.pack(1) == b"\x01"
+
+
+def test_packer_invalid_option() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.Packer(option=1 << 16)
+
+
+def test_packer_invalid_args() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.Packer(None, None, None)  # type: ignore[call-arg]
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.Packer(None, default=None)  # type: ignore[misc]
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.Packer(ext_hook=None)  # type: ignore[call-arg]
+
+
+def test_packer_gc_cycle() -> None:
+    class Default:
+        def __call__(self, obj: object) -> object:
+            return None
+
+    default = Default()
+    packer = ormsgpack.Packer(default=default)
+    default.packer = packer  # type: ignore[attr-defined]
+    ref = weakref.ref(default)
+    del default, packer
+    gc.collect()
+    assert ref() is None
+
+
+def test_packer_ext_registry() -> None:
+    registry = ormsgpack.ExtRegistry()
+    registry.register(
+        Dataclass,
+        1,
+        lambda obj: [obj.a, obj.b],
+        lambda payload: Dataclass(*payload),
+        recursive=True,
+    )
+    packer = ormsgpack.Packer(ext_registry=registry)
+    obj = [Dataclass(1, "b")]
+    packed = packer.pack(obj)
+    assert packed == ormsgpack.packb(obj, ext_registry=registry)
+    assert ormsgpack.unpackb(packed, ext_registry=registry) == obj
+
+
+def test_packer_ext_registry_invalid() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.Packer(ext_registry={})  # type: ignore[arg-type]
+
+
+def test_packer_ext_registry_gc_cycle() -> None:
+    class ToPayload:
+        def __call__(self, obj: Dataclass) -> bytes:
+            return obj.b.encode()
+
+    to_payload = ToPayload()
+    registry = ormsgpack.ExtRegistry()
+    registry.register(Dataclass, 1, to_payload, Dataclass)
+    packer = ormsgpack.Packer(ext_registry=registry)
+    to_payload.packer = packer  # type: ignore[attr-defined]
+    ref = weakref.ref(to_payload)
+    del to_payload, registry, packer
+    gc.collect()
+    assert ref() is None
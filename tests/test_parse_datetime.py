@@ -0,0 +1,225 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import dataclasses
+import datetime
+from collections.abc import Iterator
+from typing import Any, Literal
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+OFFSET = datetime.timezone(datetime.timedelta(hours=5, minutes=30))
+
+
+@pytest.mark.parametrize(
+    ("value", "option"),
+    (
+        pytest.param(datetime.date(2024, 2, 29), None, id="date"),
+        pytest.param(datetime.date(1, 1, 1), None, id="date min"),
+        pytest.param(datetime.time(12, 34, 56), None, id="time"),
+        pytest.param(datetime.time(12, 34, 56, 789), None, id="time microseconds"),
+        pytest.param(datetime.datetime(2024, 1, 2, 3, 4, 5), None, id="naive"),
+        pytest.param(
+            datetime.datetime(2024, 1, 2, 3, 4, 5, 6), None, id="naive microseconds"
+        ),
+        pytest.param(
+            datetime.datetime(2024, 1, 2, 3, 4, 5, tzinfo=datetime.timezone.utc),
+            None,
+            id="utc",
+        ),
+        pytest.param(
+            datetime.datetime(2024, 1, 2, 3, 4, 5, tzinfo=datetime.timezone.utc),
+            ormsgpack.OPT_UTC_Z,
+            id="utc z",
+        ),
+        pytest.param(
+            datetime.datetime(2024, 1, 2, 3, 4, 5, 6, tzinfo=OFFSET),
+            None,
+            id="offset",
+        ),
+        pytest.param(
+            datetime.datetime(
+                2024,
+                1,
+                2,
+                tzinfo=datetime.timezone(-datetime.timedelta(hours=23, minutes=59)),
+            ),
+            None,
+            id="negative offset",
+        ),
+    ),
+)
+def test_parse_datetime_roundtrip(value: Any, option: int | None) -> None:
+    packed = ormsgpack.packb([value], option=option)
+    unpacked = ormsgpack.unpackb(packed, option=ormsgpack.OPT_PARSE_DATETIME)
+    assert unpacked == [value]
+    assert type(unpacked[0]) is type(value)
+    if isinstance(value, datetime.datetime):
+        assert unpacked[0].utcoffset() == value.utcoffset()
+
+
+@pytest.mark.parametrize(
+    ("value", "expected"),
+    (
+        ("2024-01-02T03:04:05Z", datetime.timezone.utc),
+        ("2024-01-02T03:04:05+00:00", datetime.timezone.utc),
+        ("2024-01-02T03:04:05-00:00", datetime.timezone.utc),
+        ("2024-01-02T03:04:05+05:30", OFFSET),
+        ("2024-01-02T03:04:05", None),
+    ),
+)
+def test_parse_datetime_offset(value: str, expected: datetime.tzinfo | None) -> None:
+    unpacked = ormsgpack.unpackb(
+        msgpack.packb(value), option=ormsgpack.OPT_PARSE_DATETIME
+    )
+    assert unpacked.tzinfo == expected
+
+
+@pytest.mark.parametrize(
+    "value",
+    (
+        "",
+        "hello",
+        "2024-1-02",
+        "2024-01-02 ",
+        "0000-01-01",
+        "2023-02-29",
+        "2024-13-01",
+        "24:00:00",
+        "12:60:00",
+        "12:00:60",
+        "12:00:00.5",
+        "12:00:00.1234567",
+        "12:00:00Z",
+        "2024-01-02 03:04:05",
+        "2024-01-02t03:04:05",
+        "2024-01-02T03:04",
+        "2024-01-02T03:04:05.5Z",
+        "2024-01-02T03:04:05z",
+        "2024-01-02T03:04:05+0530",
+        "2024-01-02T03:04:05+05",
+        "2024-01-02T03:04:05+24:00",
+        "2024-01-02T03:04:05+05:60",
+        "2024-01-02T03:04:05+05:30:00",
+        "2024-01-02T03:04:05.123456+05:30Z",
+        "１２３４-01-02",
+    ),
+)
+def test_parse_datetime_other(value: str) -> None:
+    packed = msgpack.packb(value)
+    assert ormsgpack.unpackb(packed, option=ormsgpack.OPT_PARSE_DATETIME) == value
+
+
+def test_parse_datetime_not_enabled() -> None:
+    packed = ormsgpack.packb(datetime.date(2024, 1, 2))
+    assert ormsgpack.unpackb(packed) == "2024-01-02"
+
+
+def test_parse_datetime_map_keys() -> None:
+    obj = {"2024-01-02": "2024-01-02"}
+    unpacked = ormsgpack.unpackb(
+        ormsgpack.packb(obj), option=ormsgpack.OPT_PARSE_DATETIME
+    )
+    assert unpacked == {"2024-01-02": datetime.date(2024, 1, 2)}
+
+
+def test_parse_datetime_keys() -> None:
+    obj = {
+        "created": "2024-01-02T03:04:05Z",
+        "dates": ["2024-01-02", "2024-01-03"],
+        "name": "2024-01-02",
+        "nested": {"created": "12:00:00", "name": "12:00:00"},
+    }
+    unpacked = ormsgpack.unpackb(
+        ormsgpack.packb([obj, "2024-01-02"]),
+        option=ormsgpack.OPT_PARSE_DATETIME,
+        datetime_keys=["created", "dates"],
+    )
+    assert unpacked == [
+        {
+            "created": datetime.datetime(
+                2024, 1, 2, 3, 4, 5, tzinfo=datetime.timezone.utc
+            ),
+            "dates": [datetime.date(2024, 1, 2), datetime.date(2024, 1, 3)],
+            "name": "2024-01-02",
+            "nested": {"created": datetime.time(12), "name": "12:00:00"},
+        },
+        "2024-01-02",
+    ]
+
+
+def test_parse_datetime_keys_nested_map() -> None:
+    obj = {"created": {"at": "2024-01-02"}}
+    unpacked = ormsgpack.unpackb(
+        ormsgpack.packb(obj),
+        option=ormsgpack.OPT_PARSE_DATETIME,
+        datetime_keys={"created"},
+    )
+    assert unpacked == obj
+
+
+def test_parse_datetime_keys_non_str_keys() -> None:
+    option = ormsgpack.OPT_PARSE_DATETIME | ormsgpack.OPT_NON_STR_KEYS
+    obj = {1: "2024-01-02", "created": "2024-01-02", (1, 2): "2024-01-02"}
+    unpacked = ormsgpack.unpackb(
+        ormsgpack.packb(obj, option=ormsgpack.OPT_NON_STR_KEYS),
+        option=option,
+        datetime_keys={"created", 1},
+    )
+    assert unpacked == {
+        1: datetime.date(2024, 1, 2),
+        "created": datetime.date(2024, 1, 2),
+        (1, 2): "2024-01-02",
+    }
+
+
+def test_parse_datetime_keys_without_option() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(msgpack.packb({}), datetime_keys={"created"})
+
+
+def test_parse_datetime_keys_invalid() -> None:
+    with pytest.raises(TypeError) as exc_info:
+        ormsgpack.unpackb(
+            msgpack.packb({}),
+            option=ormsgpack.OPT_PARSE_DATETIME,
+            datetime_keys=1,
+        )
+    assert str(exc_info.value) == "datetime_keys must be an iterable"
+    assert isinstance(exc_info.value.__cause__, TypeError)
+
+
+def test_parse_datetime_keys_iteration_error() -> None:
+    def keys() -> Iterator[str]:
+        yield "created"
+        raise ValueError("keys")
+
+    with pytest.raises(TypeError) as exc_info:
+        ormsgpack.unpackb(
+            msgpack.packb({}),
+            option=ormsgpack.OPT_PARSE_DATETIME,
+            datetime_keys=keys(),
+        )
+    assert isinstance(exc_info.value.__cause__, ValueError)
+
+
+@dataclasses.dataclass
+class Event:
+    name: str
+    kind: Literal["2020-01-01"]
+    date: Any
+
+
+def test_parse_datetime_typed_str() -> None:
+    packed = msgpack.packb(
+        {"name": "2020-01-01", "kind": "2020-01-01", "date": "2020-01-01"}
+    )
+    event = ormsgpack.unpackb(packed, type=Event, option=ormsgpack.OPT_PARSE_DATETIME)
+    assert event == Event("2020-01-01", "2020-01-01", datetime.date(2020, 1, 1))
+    assert ormsgpack.unpackb(
+        msgpack.packb(["2020-01-01"]),
+        type=list[str],
+        option=ormsgpack.OPT_PARSE_DATETIME,
+    ) == ["2020-01-01"]
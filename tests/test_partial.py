@@ -0,0 +1,91 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+OBJECTS = (None, 1, "a" * 40, [1, [2, {}]], {"a": b"b" * 300})
+
+
+def test_unpackb_trailing_data() -> None:
+    data = msgpack.packb([1, 2]) + b"\xc0"
+    assert ormsgpack.unpackb(data) == [1, 2]
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="trailing data"):
+        ormsgpack.unpackb(data, option=ormsgpack.OPT_REJECT_TRAILING_DATA)
+    assert (
+        ormsgpack.unpackb(data[:-1], option=ormsgpack.OPT_REJECT_TRAILING_DATA)
+        == [1, 2]
+    )
+
+
+def test_unpacker_reject_trailing_data() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Invalid opts"):
+        ormsgpack.Unpacker(option=ormsgpack.OPT_REJECT_TRAILING_DATA)
+
+
+@pytest.mark.parametrize(
+    "container",
+    (bytes, bytearray, memoryview),
+)
+def test_unpackb_partial(container: type) -> None:
+    data = container(b"".join(msgpack.packb(obj) for obj in OBJECTS))
+    offset = 0
+    result = []
+    while offset < len(data):
+        obj, offset = ormsgpack.unpackb_partial(data, offset)
+        result.append(obj)
+    assert result == list(OBJECTS)
+    assert offset == len(data)
+
+
+def test_unpackb_partial_default_offset() -> None:
+    assert ormsgpack.unpackb_partial(b"\x01\x02") == (1, 1)
+    assert ormsgpack.unpackb_partial(b"\x01\x02", offset=1) == (2, 2)
+
+
+@pytest.mark.parametrize("offset", (-1, 3, 2**64))
+def test_unpackb_partial_invalid_offset(offset: int) -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="offset out of range"):
+        ormsgpack.unpackb_partial(b"\x01\x02", offset)
+
+
+def test_unpackb_partial_offset_type() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial(b"\x01\x02", "1")  # type: ignore[arg-type]
+
+
+def test_unpackb_partial_eof() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial(b"\x01\x02", 2)
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial(b"\x01\x92\x01", 1)
+
+
+def test_unpackb_partial_ext_hook() -> None:
+    data = msgpack.packb(msgpack.ExtType(1, b"a")) + b"\xc0"
+    assert ormsgpack.unpackb_partial(data, ext_hook=msgpack.ExtType) == (
+        msgpack.ExtType(1, b"a"),
+        3,
+    )
+
+
+def test_unpackb_partial_option() -> None:
+    data = msgpack.packb({1: 2})
+    assert ormsgpack.unpackb_partial(data, option=ormsgpack.OPT_NON_STR_KEYS) == (
+        {1: 2},
+        len(data),
+    )
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial(data, option=ormsgpack.OPT_REJECT_TRAILING_DATA)
+
+
+def test_unpackb_partial_args() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial()  # type: ignore[call-arg]
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial(b"\xc0", 0, 0)  # type: ignore[call-arg]
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial(b"\xc0", 0, offset=0)  # type: ignore[misc]
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb_partial(b"\xc0", default=None)  # type: ignore[call-arg]
@@ -1,6 +1,8 @@
 # SPDX-License-Identifier: (Apache-2.0 OR MIT)
 import sys
+from typing import Any, Optional
 
+import msgpack
 import pydantic
 import pytest
 
@@ -107,3 +109,125 @@ def test_pydantic_model_with_extra() -> None:
         ("b", 1),
         ("c", 2),
     ]
+
+
+def test_pydantic_model_error_path() -> None:
+    class Model(pydantic.BaseModel):
+        model_config = pydantic.ConfigDict(arbitrary_types_allowed=True)
+
+        a: list[object]
+
+    obj = Model(a=[1, object()])
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_PYDANTIC)
+    assert exc_info.value.path == "$.a[1]"
+
+
+class Point(pydantic.BaseModel):
+    x: int
+    y: int = 0
+
+
+class Shape(pydantic.BaseModel):
+    name: str = pydantic.Field(alias="Name")
+    points: list[Point]
+
+
+def test_unpackb_model() -> None:
+    packed = msgpack.packb(
+        {"Name": "a", "points": [{"x": 1}, {"x": 2, "y": 3}], "unknown": [1]}
+    )
+    assert ormsgpack.unpackb(packed, model=Shape) == Shape(
+        Name="a", points=[Point(x=1), Point(x=2, y=3)]
+    )
+
+
+def test_unpackb_model_roundtrip() -> None:
+    obj = Shape(Name="a", points=[Point(x=1, y=2)])
+    packed = ormsgpack.packb(
+        obj.model_dump(by_alias=True), option=ormsgpack.OPT_SERIALIZE_PYDANTIC
+    )
+    assert ormsgpack.unpackb(packed, model=Shape) == obj
+
+
+def test_unpackb_model_type() -> None:
+    packed = msgpack.packb([{"x": 1}, None])
+    assert ormsgpack.unpackb(packed, type=list[Optional[Point]]) == [
+        Point(x=1),
+        None,
+    ]
+
+
+def test_unpackb_model_extra_allow() -> None:
+    class Model(pydantic.BaseModel):
+        model_config = pydantic.ConfigDict(extra="allow")
+
+        a: int
+
+    packed = msgpack.packb({"a": 1, "b": 2})
+    obj = ormsgpack.unpackb(packed, model=Model)
+    assert obj == Model(a=1, b=2)  # type: ignore[call-arg]
+    assert obj.model_extra == {"b": 2}
+
+
+def test_unpackb_model_extra_forbid() -> None:
+    class Model(pydantic.BaseModel):
+        model_config = pydantic.ConfigDict(extra="forbid")
+
+        a: int
+
+    packed = msgpack.packb({"a": 1, "b": 2})
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(packed, model=Model)
+    assert isinstance(exc_info.value.__cause__, pydantic.ValidationError)
+
+
+def test_unpackb_model_validator() -> None:
+    class Model(pydantic.BaseModel):
+        a: int
+
+        @pydantic.model_validator(mode="before")
+        @classmethod
+        def validate(cls, data: Any) -> Any:
+            return {"a": data["b"]}
+
+    packed = msgpack.packb({"b": 1})
+    assert ormsgpack.unpackb(packed, model=Model).a == 1
+
+
+def test_unpackb_model_validation_alias() -> None:
+    class Model(pydantic.BaseModel):
+        a: int = pydantic.Field(
+            validation_alias=pydantic.AliasChoices("b", pydantic.AliasPath("c", 0))
+        )
+
+    packed = msgpack.packb({"c": [1]})
+    assert ormsgpack.unpackb(packed, model=Model) == Model(b=1)
+
+
+def test_unpackb_root_model() -> None:
+    class Model(pydantic.RootModel[list[int]]):
+        pass
+
+    packed = msgpack.packb([1, 2])
+    assert ormsgpack.unpackb(packed, model=Model) == Model([1, 2])
+
+
+def test_unpackb_model_validation_error() -> None:
+    packed = msgpack.packb([{"Name": "a", "points": [{"x": "b"}]}])
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(packed, type=list[Shape])
+    assert str(exc_info.value).startswith("failed to validate Shape: ")
+    assert exc_info.value.kind is ormsgpack.MsgpackDecodeErrorKind.INVALID_VALUE
+    assert exc_info.value.path == "$[0]"
+    assert isinstance(exc_info.value.__cause__, pydantic.ValidationError)
+
+
+def test_unpackb_model_invalid() -> None:
+    with pytest.raises(TypeError):
+        ormsgpack.unpackb(msgpack.packb({"x": 1}), model=dict)
+
+
+def test_unpackb_model_and_type() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(msgpack.packb({"x": 1}), model=Point, type=Point)
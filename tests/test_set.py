@@ -0,0 +1,186 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+from typing import Any
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+
+@pytest.mark.parametrize("cls", (set, frozenset))
+def test_set_array(cls: Any) -> None:
+    obj = cls([1, "a", None])
+    packed = ormsgpack.packb(obj, option=ormsgpack.OPT_SERIALIZE_SET)
+    unpacked = ormsgpack.unpackb(packed)
+    assert type(unpacked) is list
+    assert cls(unpacked) == obj
+
+
+@pytest.mark.parametrize("cls", (set, frozenset))
+def test_set_array_sorted(cls: Any) -> None:
+    obj = cls(range(100, 0, -7))
+    packed = ormsgpack.packb(
+        obj, option=ormsgpack.OPT_SERIALIZE_SET | ormsgpack.OPT_SORT_KEYS
+    )
+    assert packed == msgpack.packb(sorted(obj))
+
+
+def test_set_array_sorted_unorderable() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError) as exc_info:
+        ormsgpack.packb(
+            {1, "a"}, option=ormsgpack.OPT_SERIALIZE_SET | ormsgpack.OPT_SORT_KEYS
+        )
+    assert str(exc_info.value).startswith(
+        "Set elements must be orderable with OPT_SORT_KEYS (TypeError: "
+    )
+    assert isinstance(exc_info.value.__cause__, TypeError)
+
+
+@pytest.mark.parametrize(
+    "obj",
+    (
+        {frozenset([1]), frozenset([2])},
+        {frozenset([1]), frozenset([1, 2]), frozenset([3])},
+        {float("nan"), 1.0},
+    ),
+)
+def test_set_array_sorted_partial_order(obj: Any) -> None:
+    for option in (ormsgpack.OPT_SERIALIZE_SET, ormsgpack.OPT_SET_AS_EXT):
+        with pytest.raises(
+            ormsgpack.MsgpackEncodeError,
+            match="Set elements must be totally ordered with OPT_SORT_KEYS",
+        ):
+            ormsgpack.packb(obj, option=option | ormsgpack.OPT_SORT_KEYS)
+
+
+def test_set_array_sorted_frozensets_chain() -> None:
+    obj = {frozenset([1, 2]), frozenset(), frozenset([1])}
+    packed = ormsgpack.packb(
+        obj, option=ormsgpack.OPT_SERIALIZE_SET | ormsgpack.OPT_SORT_KEYS
+    )
+    assert packed == msgpack.packb([[], [1], [1, 2]])
+
+
+def test_set_array_nested() -> None:
+    obj = {"a": {2, 1}, "b": [frozenset(["c"])]}
+    packed = ormsgpack.packb(
+        obj, option=ormsgpack.OPT_SERIALIZE_SET | ormsgpack.OPT_SORT_KEYS
+    )
+    assert packed == msgpack.packb({"a": [1, 2], "b": [["c"]]})
+
+
+def test_set_array_default() -> None:
+    packed = ormsgpack.packb(
+        {1}, option=ormsgpack.OPT_SERIALIZE_SET, default=lambda x: "default"
+    )
+    assert packed == msgpack.packb([1])
+
+
+def test_set_array_element_error() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb({object()}, option=ormsgpack.OPT_SERIALIZE_SET)
+
+
+@pytest.mark.parametrize(
+    "obj",
+    (
+        set(),
+        {1, 2, 3},
+        {"a", (1, 2), None},
+        frozenset(),
+        frozenset([1.5, b"a"]),
+        frozenset([frozenset([1]), frozenset()]),
+        {frozenset([(1, (2, 3))])},
+    ),
+)
+def test_set_ext_roundtrip(obj: Any) -> None:
+    option = ormsgpack.OPT_SET_AS_EXT
+    unpacked = ormsgpack.unpackb(ormsgpack.packb([obj], option=option), option=option)
+    assert type(unpacked[0]) is type(obj)
+    assert unpacked[0] == obj
+
+
+@pytest.mark.parametrize(
+    ("obj", "code"),
+    (
+        ({3, 1, 2}, 124),
+        (frozenset([3, 1, 2]), 123),
+    ),
+)
+def test_set_ext_format(obj: Any, code: int) -> None:
+    packed = ormsgpack.packb(
+        obj, option=ormsgpack.OPT_SET_AS_EXT | ormsgpack.OPT_SORT_KEYS
+    )
+    assert packed == msgpack.packb(msgpack.ExtType(code, b"\x93\x01\x02\x03"))
+
+
+def test_set_ext_map_key() -> None:
+    option = ormsgpack.OPT_SET_AS_EXT | ormsgpack.OPT_NON_STR_KEYS
+    obj = {frozenset(): 1, frozenset([1]): 2, frozenset(range(20)): 3}
+    packed = ormsgpack.packb(obj, option=option)
+    assert ormsgpack.unpackb(packed, option=option) == obj
+
+
+def test_set_ext_map_key_set() -> None:
+    packed = msgpack.packb({msgpack.ExtType(124, b"\x90"): 1})
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(
+            packed, option=ormsgpack.OPT_SET_AS_EXT | ormsgpack.OPT_NON_STR_KEYS
+        )
+
+
+def test_set_ext_not_enabled() -> None:
+    packed = ormsgpack.packb({1}, option=ormsgpack.OPT_SET_AS_EXT)
+    with pytest.raises(ormsgpack.MsgpackExtError):
+        ormsgpack.unpackb(packed)
+
+
+@pytest.mark.parametrize(
+    "data",
+    (
+        pytest.param(b"", id="empty"),
+        pytest.param(b"\x01", id="not an array"),
+        pytest.param(b"\x92\x01", id="truncated"),
+        pytest.param(b"\x91\x01\x01", id="trailing data"),
+        pytest.param(b"\x91\x81\x01\x02", id="unhashable"),
+    ),
+)
+def test_set_ext_invalid(data: bytes) -> None:
+    packed = msgpack.packb(msgpack.ExtType(124, data))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, option=ormsgpack.OPT_SET_AS_EXT)
+
+
+def test_set_ext_truncated_payload() -> None:
+    packed = b"\x91\xc7\x03|\x93\x01\x02"
+    option = ormsgpack.OPT_SET_AS_EXT
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(packed, option=option)
+    assert type(exc_info.value) is ormsgpack.MsgpackDecodeError
+    assert exc_info.value.kind == ormsgpack.MsgpackDecodeErrorKind.INVALID_VALUE
+    assert exc_info.value.pos == 4
+    assert exc_info.value.path == "$[0]"
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(b"\xc7\x03|\x92\x01\xcd", option=option)
+    assert exc_info.value.kind == ormsgpack.MsgpackDecodeErrorKind.INVALID_VALUE
+    assert exc_info.value.pos == 5
+    assert exc_info.value.path == "$[1]"
+    unpacker = ormsgpack.Unpacker(option=option)
+    unpacker.feed(packed)
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        next(unpacker)
+
+
+def test_set_not_enabled() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb({1})
+    assert ormsgpack.packb({1}, default=list) == msgpack.packb([1])
+
+
+def test_set_subclass() -> None:
+    class MySet(set):  # type: ignore[type-arg]
+        pass
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(MySet([1]), option=ormsgpack.OPT_SERIALIZE_SET)
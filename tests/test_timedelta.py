@@ -0,0 +1,165 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import datetime
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+
+@pytest.mark.parametrize(
+    ("value", "expected"),
+    (
+        pytest.param(datetime.timedelta(0), "PT0S", id="zero"),
+        pytest.param(datetime.timedelta(days=1), "P1D", id="days"),
+        pytest.param(
+            datetime.timedelta(days=1, hours=2, minutes=3, seconds=4),
+            "P1DT2H3M4S",
+            id="days and time",
+        ),
+        pytest.param(datetime.timedelta(hours=1, seconds=1), "PT1H1S", id="time"),
+        pytest.param(datetime.timedelta(seconds=1.5), "PT1.5S", id="fraction"),
+        pytest.param(
+            datetime.timedelta(microseconds=1), "PT0.000001S", id="microseconds"
+        ),
+        pytest.param(datetime.timedelta(days=-1), "-P1D", id="negative days"),
+        pytest.param(
+            datetime.timedelta(microseconds=-1), "-PT0.000001S", id="negative"
+        ),
+        pytest.param(
+            datetime.timedelta.max, "P999999999DT23H59M59.999999S", id="max"
+        ),
+        pytest.param(datetime.timedelta.min, "-P999999999D", id="min"),
+    ),
+)
+def test_timedelta_iso8601(value: datetime.timedelta, expected: str) -> None:
+    assert ormsgpack.packb(
+        value, option=ormsgpack.OPT_TIMEDELTA_AS_ISO8601
+    ) == msgpack.packb(expected)
+
+
+@pytest.mark.parametrize(
+    "value",
+    (
+        datetime.timedelta(0),
+        datetime.timedelta(days=1, seconds=2, microseconds=3),
+        datetime.timedelta(microseconds=-1),
+        datetime.timedelta(days=-5, microseconds=999999),
+        datetime.timedelta.max,
+    ),
+)
+def test_timedelta_seconds(value: datetime.timedelta) -> None:
+    packed = ormsgpack.packb(value, option=ormsgpack.OPT_TIMEDELTA_AS_SECONDS)
+    unpacked = ormsgpack.unpackb(packed)
+    assert type(unpacked) is float
+    assert unpacked == value.total_seconds()
+
+
+@pytest.mark.parametrize(
+    "value",
+    (
+        datetime.timedelta(0),
+        datetime.timedelta(days=1, seconds=2, microseconds=3),
+        datetime.timedelta(microseconds=-1),
+        datetime.timedelta(days=-100000),
+    ),
+)
+def test_timedelta_microseconds(value: datetime.timedelta) -> None:
+    packed = ormsgpack.packb(value, option=ormsgpack.OPT_TIMEDELTA_AS_MICROSECONDS)
+    assert ormsgpack.unpackb(packed) == value // datetime.timedelta(microseconds=1)
+
+
+def test_timedelta_microseconds_out_of_range() -> None:
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(
+            datetime.timedelta.max, option=ormsgpack.OPT_TIMEDELTA_AS_MICROSECONDS
+        )
+
+
+@pytest.mark.parametrize(
+    "value",
+    (
+        datetime.timedelta(0),
+        datetime.timedelta(seconds=1, microseconds=5),
+        datetime.timedelta(microseconds=-1),
+        datetime.timedelta.max,
+        datetime.timedelta.min,
+    ),
+)
+def test_timedelta_ext_roundtrip(value: datetime.timedelta) -> None:
+    option = ormsgpack.OPT_TIMEDELTA_AS_EXT
+    packed = ormsgpack.packb([value], option=option)
+    assert ormsgpack.unpackb(packed, option=option) == [value]
+
+
+def test_timedelta_ext_format() -> None:
+    value = datetime.timedelta(seconds=-1, microseconds=5)
+    packed = ormsgpack.packb(value, option=ormsgpack.OPT_TIMEDELTA_AS_EXT)
+    data = (5000).to_bytes(4, "big") + (-1).to_bytes(8, "big", signed=True)
+    assert packed == msgpack.packb(msgpack.ExtType(127, data))
+
+
+def test_timedelta_ext_map_key() -> None:
+    option = ormsgpack.OPT_TIMEDELTA_AS_EXT | ormsgpack.OPT_NON_STR_KEYS
+    obj = {datetime.timedelta(days=1): 1, datetime.timedelta(seconds=1): 2}
+    packed = ormsgpack.packb(obj, option=option)
+    assert ormsgpack.unpackb(packed, option=option) == obj
+
+
+def test_timedelta_ext_not_enabled() -> None:
+    packed = ormsgpack.packb(
+        datetime.timedelta(seconds=1), option=ormsgpack.OPT_TIMEDELTA_AS_EXT
+    )
+    with pytest.raises(ormsgpack.MsgpackExtError):
+        ormsgpack.unpackb(packed)
+    assert ormsgpack.unpackb(
+        packed, option=ormsgpack.OPT_KEEP_UNKNOWN_EXT
+    ) == ormsgpack.Ext(127, packed[3:])
+
+
+@pytest.mark.parametrize(
+    "data",
+    (
+        pytest.param(b"\x00" * 8, id="length"),
+        pytest.param(b"\x3b\x9a\xca\x00" + b"\x00" * 8, id="nanoseconds"),
+        pytest.param(b"\x00" * 4 + (2**62).to_bytes(8, "big"), id="overflow"),
+    ),
+)
+def test_timedelta_ext_invalid(data: bytes) -> None:
+    packed = msgpack.packb(msgpack.ExtType(127, data))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, option=ormsgpack.OPT_TIMEDELTA_AS_EXT)
+
+
+@pytest.mark.parametrize(
+    "option",
+    (
+        ormsgpack.OPT_TIMEDELTA_AS_ISO8601 | ormsgpack.OPT_TIMEDELTA_AS_SECONDS,
+        ormsgpack.OPT_TIMEDELTA_AS_SECONDS | ormsgpack.OPT_TIMEDELTA_AS_MICROSECONDS,
+        ormsgpack.OPT_TIMEDELTA_AS_MICROSECONDS | ormsgpack.OPT_TIMEDELTA_AS_EXT,
+        ormsgpack.OPT_TIMEDELTA_AS_ISO8601
+        | ormsgpack.OPT_TIMEDELTA_AS_SECONDS
+        | ormsgpack.OPT_TIMEDELTA_AS_MICROSECONDS
+        | ormsgpack.OPT_TIMEDELTA_AS_EXT,
+    ),
+)
+def test_timedelta_options_exclusive(option: int) -> None:
+    value = datetime.timedelta(seconds=1)
+    with pytest.raises(ormsgpack.MsgpackEncodeError, match="Invalid opts"):
+        ormsgpack.packb(value, option=option)
+
+
+def test_timedelta_not_enabled() -> None:
+    value = datetime.timedelta(seconds=1)
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(value)
+    assert ormsgpack.packb(value, default=str) == msgpack.packb("0:00:01")
+
+
+def test_timedelta_subclass() -> None:
+    class Delta(datetime.timedelta):
+        pass
+
+    with pytest.raises(ormsgpack.MsgpackEncodeError):
+        ormsgpack.packb(Delta(1), option=ormsgpack.OPT_TIMEDELTA_AS_ISO8601)
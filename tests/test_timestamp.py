@@ -0,0 +1,280 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import copy
+import datetime
+import pickle
+import struct
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+
+@pytest.mark.parametrize(
+    ("seconds", "nanoseconds", "length"),
+    (
+        pytest.param(0, 0, 4, id="timestamp 32"),
+        pytest.param(2**32 - 1, 0, 4, id="timestamp 32 max"),
+        pytest.param(1, 1, 8, id="timestamp 64"),
+        pytest.param(2**34 - 1, 999999999, 8, id="timestamp 64 max"),
+        pytest.param(2**34, 0, 12, id="timestamp 96"),
+        pytest.param(-1, 999999999, 12, id="timestamp 96 negative"),
+        pytest.param(-(2**63), 0, 12, id="timestamp 96 min"),
+        pytest.param(2**63 - 1, 999999999, 12, id="timestamp 96 max"),
+    ),
+)
+def test_timestamp_roundtrip(seconds: int, nanoseconds: int, length: int) -> None:
+    value = ormsgpack.Timestamp(seconds, nanoseconds)
+    packed = ormsgpack.packb(value)
+    assert packed == msgpack.packb(msgpack.Timestamp(seconds, nanoseconds))
+    assert len(packed) == length + (2 if length != 12 else 3)
+    unpacked = ormsgpack.unpackb(packed, option=ormsgpack.OPT_NATIVE_TIMESTAMP)
+    assert type(unpacked) is ormsgpack.Timestamp
+    assert (unpacked.seconds, unpacked.nanoseconds) == (seconds, nanoseconds)
+
+
+def test_timestamp_precedence() -> None:
+    packed = ormsgpack.packb(ormsgpack.Timestamp(1, 5))
+    unpacked = ormsgpack.unpackb(
+        packed,
+        option=ormsgpack.OPT_NATIVE_TIMESTAMP
+        | ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+    )
+    assert unpacked == ormsgpack.Timestamp(1, 5)
+    unpacked = ormsgpack.unpackb(
+        packed, option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT
+    )
+    assert unpacked == datetime.datetime(
+        1970, 1, 1, 0, 0, 1, tzinfo=datetime.timezone.utc
+    )
+
+
+def test_timestamp_invalid_nanoseconds() -> None:
+    packed = b"\xd7\xff" + ((1_000_000_000 << 34) | 1).to_bytes(8, "big")
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, option=ormsgpack.OPT_NATIVE_TIMESTAMP)
+
+
+def test_timestamp_invalid_length() -> None:
+    packed = b"\xd5\xff\x00\x00"
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(packed, option=ormsgpack.OPT_NATIVE_TIMESTAMP)
+
+
+def test_timestamp_constructor() -> None:
+    assert ormsgpack.Timestamp(1) == ormsgpack.Timestamp(1, 0)
+    with pytest.raises(ValueError):
+        ormsgpack.Timestamp(0, -1)
+    with pytest.raises(ValueError):
+        ormsgpack.Timestamp(0, 1_000_000_000)
+    with pytest.raises(OverflowError):
+        ormsgpack.Timestamp(2**63)
+    with pytest.raises(TypeError):
+        ormsgpack.Timestamp(1.5)  # type: ignore[arg-type]
+    with pytest.raises(TypeError):
+        ormsgpack.Timestamp()  # type: ignore[call-arg]
+
+
+def test_timestamp_attributes() -> None:
+    value = ormsgpack.Timestamp(1, 2)
+    assert (value.seconds, value.nanoseconds) == (1, 2)
+    with pytest.raises(AttributeError):
+        value.seconds = 2  # type: ignore[misc]
+
+
+def test_timestamp_comparison() -> None:
+    assert ormsgpack.Timestamp(1, 2) == ormsgpack.Timestamp(1, 2)
+    assert ormsgpack.Timestamp(1, 2) != ormsgpack.Timestamp(1, 3)
+    assert ormsgpack.Timestamp(-1, 999999999) < ormsgpack.Timestamp(0)
+    assert ormsgpack.Timestamp(1) <= ormsgpack.Timestamp(1)
+    assert ormsgpack.Timestamp(2) > ormsgpack.Timestamp(1, 999999999)
+    assert ormsgpack.Timestamp(1, 1) >= ormsgpack.Timestamp(1)
+    assert ormsgpack.Timestamp(1) != (1, 0)
+
+
+def test_timestamp_hash_repr_pickle() -> None:
+    value = ormsgpack.Timestamp(-5, 7)
+    assert hash(value) == hash(ormsgpack.Timestamp(-5, 7))
+    assert repr(value) == "Timestamp(-5, 7)"
+    assert pickle.loads(pickle.dumps(value)) == value
+    assert copy.deepcopy(value) == value
+
+
+def test_timestamp_match() -> None:
+    match ormsgpack.Timestamp(1, 2):
+        case ormsgpack.Timestamp(seconds, nanoseconds):
+            assert (seconds, nanoseconds) == (1, 2)
+        case _:
+            raise AssertionError
+
+
+def test_timestamp_to_datetime() -> None:
+    value = ormsgpack.Timestamp(1577836800, 123456789)
+    assert value.to_datetime() == datetime.datetime(
+        2020, 1, 1, 0, 0, 0, 123456, tzinfo=datetime.timezone.utc
+    )
+    with pytest.raises(ValueError):
+        ormsgpack.Timestamp(-(2**62)).to_datetime()
+
+
+def test_timestamp_from_datetime() -> None:
+    tz = datetime.timezone(datetime.timedelta(hours=1))
+    value = ormsgpack.Timestamp.from_datetime(
+        datetime.datetime(2020, 1, 1, 1, 0, 0, 5, tzinfo=tz)
+    )
+    assert value == ormsgpack.Timestamp(1577836800, 5000)
+    value = ormsgpack.Timestamp.from_datetime(
+        datetime.datetime(1969, 12, 31, 23, 59, 59, 1, tzinfo=datetime.timezone.utc)
+    )
+    assert value == ormsgpack.Timestamp(-1, 1000)
+    with pytest.raises(ValueError):
+        ormsgpack.Timestamp.from_datetime(datetime.datetime(2020, 1, 1))
+    date = datetime.date(2020, 1, 1)
+    with pytest.raises(TypeError):
+        ormsgpack.Timestamp.from_datetime(date)  # type: ignore[arg-type]
+
+
+def test_timestamp_unix_nanos() -> None:
+    assert ormsgpack.Timestamp(1, 5).to_unix_nanos() == 1_000_000_005
+    assert ormsgpack.Timestamp(-1, 5).to_unix_nanos() == -999_999_995
+    value = ormsgpack.Timestamp(2**63 - 1, 999999999)
+    assert value.to_unix_nanos() == (2**63 - 1) * 10**9 + 999999999
+    assert ormsgpack.Timestamp.from_unix_nanos(value.to_unix_nanos()) == value
+    assert ormsgpack.Timestamp.from_unix_nanos(-1) == ormsgpack.Timestamp(
+        -1, 999999999
+    )
+    with pytest.raises(OverflowError):
+        ormsgpack.Timestamp.from_unix_nanos(2**63 * 10**9)
+    with pytest.raises(TypeError):
+        ormsgpack.Timestamp.from_unix_nanos(1.0)  # type: ignore[arg-type]
+
+
+@pytest.mark.parametrize(
+    ("option", "expected"),
+    (
+        pytest.param(
+            ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+            datetime.datetime(
+                2020, 1, 1, 0, 0, 0, 123456, tzinfo=datetime.timezone.utc
+            ),
+            id="datetime",
+        ),
+        pytest.param(
+            ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT | ormsgpack.OPT_NAIVE_UTC,
+            datetime.datetime(2020, 1, 1, 0, 0, 0, 123456),
+            id="naive datetime",
+        ),
+        pytest.param(
+            ormsgpack.OPT_TIMESTAMP_AS_NANOS,
+            1577836800123456789,
+            id="nanoseconds",
+        ),
+        pytest.param(
+            ormsgpack.OPT_TIMESTAMP_AS_SECONDS,
+            1577836800.123456789,
+            id="seconds",
+        ),
+        pytest.param(
+            ormsgpack.OPT_TIMESTAMP_AS_NANOS
+            | ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+            1577836800123456789,
+            id="nanoseconds precedence",
+        ),
+    ),
+)
+def test_timestamp_decode_option(option: int, expected: object) -> None:
+    value = msgpack.Timestamp(1577836800, 123456789)
+    packed = msgpack.packb([value, {value: 1}])
+    unpacked = ormsgpack.unpackb(packed, option=option | ormsgpack.OPT_NON_STR_KEYS)
+    assert unpacked == [expected, {expected: 1}]
+    assert type(unpacked[0]) is type(expected)
+    if isinstance(expected, datetime.datetime):
+        assert unpacked[0].tzinfo is expected.tzinfo
+
+
+def test_timestamp_as_nanos_range() -> None:
+    value = ormsgpack.Timestamp(-(2**63), 0)
+    unpacked = ormsgpack.unpackb(
+        ormsgpack.packb(value), option=ormsgpack.OPT_TIMESTAMP_AS_NANOS
+    )
+    assert unpacked == value.to_unix_nanos()
+
+
+def test_timestamp_datetime_out_of_range() -> None:
+    packed = b"\x91\xc7\x0c\xff" + struct.pack(">Iq", 0, 253402300800)
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(packed, option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT)
+    assert exc_info.value.kind == ormsgpack.MsgpackDecodeErrorKind.INVALID_VALUE
+    assert exc_info.value.path == "$[0]"
+    assert isinstance(exc_info.value.__cause__, ValueError)
+    unpacked = ormsgpack.unpackb(packed, option=ormsgpack.OPT_NATIVE_TIMESTAMP)
+    assert unpacked == [ormsgpack.Timestamp(253402300800)]
+
+
+def test_timestamp_tzinfo() -> None:
+    tz = datetime.timezone(datetime.timedelta(hours=-5))
+    packed = ormsgpack.packb([ormsgpack.Timestamp(0)])
+    unpacked = ormsgpack.unpackb(
+        packed, option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT, tzinfo=tz
+    )
+    assert unpacked == [datetime.datetime(1969, 12, 31, 19, tzinfo=tz)]
+    assert unpacked[0].tzinfo is tz
+    unpacked = ormsgpack.unpackb(
+        packed, option=ormsgpack.OPT_TIMESTAMP_AS_NANOS, tzinfo=tz
+    )
+    assert unpacked == [0]
+
+
+def test_timestamp_tzinfo_map_key() -> None:
+    tz = datetime.timezone(datetime.timedelta(hours=2))
+    packed = msgpack.packb({msgpack.Timestamp(0): 1})
+    unpacked = ormsgpack.unpackb(
+        packed,
+        option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT | ormsgpack.OPT_NON_STR_KEYS,
+        tzinfo=tz,
+    )
+    key = next(iter(unpacked))
+    assert key == datetime.datetime(1970, 1, 1, 2, tzinfo=tz)
+    assert key.tzinfo is tz
+
+
+def test_timestamp_tzinfo_fails() -> None:
+    class FailingTimezone(datetime.tzinfo):
+        def utcoffset(self, dt: datetime.datetime | None) -> datetime.timedelta:
+            raise ValueError("invalid offset")
+
+    packed = ormsgpack.packb(ormsgpack.Timestamp(0))
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(
+            packed,
+            option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+            tzinfo=FailingTimezone(),
+        )
+    assert exc_info.value.kind == ormsgpack.MsgpackDecodeErrorKind.INVALID_VALUE
+    assert isinstance(exc_info.value.__cause__, ValueError)
+
+
+def test_timestamp_tzinfo_out_of_range() -> None:
+    packed = b"\xc7\x0c\xff" + struct.pack(">Iq", 0, 253402300800)
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(
+            packed,
+            option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT,
+            tzinfo=datetime.timezone.utc,
+        )
+    assert exc_info.value.kind == ormsgpack.MsgpackDecodeErrorKind.INVALID_VALUE
+    assert isinstance(exc_info.value.__cause__, ValueError)
+
+
+def test_timestamp_tzinfo_invalid() -> None:
+    packed = ormsgpack.packb(ormsgpack.Timestamp(0))
+    with pytest.raises(TypeError):
+        ormsgpack.unpackb(packed, tzinfo="UTC")  # type: ignore[arg-type]
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.unpackb(
+            packed,
+            option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT | ormsgpack.OPT_NAIVE_UTC,
+            tzinfo=datetime.timezone.utc,
+        )
+    assert ormsgpack.unpackb(b"\x01", tzinfo=None) == 1
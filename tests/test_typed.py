@@ -0,0 +1,230 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import dataclasses
+import gc
+import weakref
+from typing import Any, Literal, NamedTuple, Optional, TypedDict, Union
+
+import msgpack
+import pytest
+
+import ormsgpack
+from ormsgpack import MsgpackDecodeErrorKind
+
+
+@dataclasses.dataclass
+class Item:
+    name: str
+    price: float
+    tags: list[str] = dataclasses.field(default_factory=list)
+
+
+@dataclasses.dataclass
+class Order:
+    id: int
+    items: list[Item]
+    status: Literal["open", "closed"]
+    note: Optional[str] = None
+
+
+@dataclasses.dataclass
+class Node:
+    value: int
+    children: "list[Node]"
+
+
+@dataclasses.dataclass
+class Positive:
+    value: int
+
+    def __post_init__(self) -> None:
+        if self.value <= 0:
+            raise ValueError("not positive")
+
+
+class Point(NamedTuple):
+    x: int
+    y: int = 0
+
+
+class Movie(TypedDict):
+    title: str
+    year: int
+
+
+def decode_error(obj: Any, type: Any) -> ormsgpack.MsgpackDecodeError:
+    with pytest.raises(ormsgpack.MsgpackDecodeError) as exc_info:
+        ormsgpack.unpackb(msgpack.packb(obj), type=type)
+    return exc_info.value
+
+
+@pytest.mark.parametrize(
+    "obj,type,expected",
+    (
+        pytest.param(None, type(None), None, id="None"),
+        pytest.param(True, bool, True, id="bool"),
+        pytest.param(1, int, 1, id="int"),
+        pytest.param(1.5, float, 1.5, id="float"),
+        pytest.param("a", str, "a", id="str"),
+        pytest.param(b"a", bytes, b"a", id="bytes"),
+        pytest.param({"a": [1]}, Any, {"a": [1]}, id="Any"),
+        pytest.param({"a": [1]}, object, {"a": [1]}, id="object"),
+        pytest.param([1, 2], list[int], [1, 2], id="list"),
+        pytest.param({"a": 1}, dict[str, int], {"a": 1}, id="dict"),
+        pytest.param([1, None], list[Optional[int]], [1, None], id="Optional"),
+        pytest.param([1, "a"], list[Union[int, str]], [1, "a"], id="Union"),
+        pytest.param([1, "a"], list[int | str], [1, "a"], id="UnionType"),
+        pytest.param("b", Literal["a", "b"], "b", id="Literal"),
+    ),
+)
+def test_type(obj: Any, type: Any, expected: Any) -> None:
+    assert ormsgpack.unpackb(msgpack.packb(obj), type=type) == expected
+
+
+def test_float_from_int() -> None:
+    value = ormsgpack.unpackb(msgpack.packb(1), type=float)
+    assert value == 1.0
+    assert isinstance(value, float)
+
+
+def test_dataclass() -> None:
+    obj = {
+        "id": 1,
+        "items": [{"name": "a", "price": 2}],
+        "status": "open",
+        "unknown": [1, 2],
+    }
+    assert ormsgpack.unpackb(msgpack.packb(obj), type=Order) == Order(
+        id=1,
+        items=[Item(name="a", price=2.0)],
+        status="open",
+    )
+
+
+def test_dataclass_roundtrip() -> None:
+    order = Order(
+        id=1,
+        items=[Item(name="a", price=1.5, tags=["b"])],
+        status="closed",
+        note="c",
+    )
+    assert ormsgpack.unpackb(ormsgpack.packb(order), type=Order) == order
+
+
+def test_dataclass_recursive() -> None:
+    obj = {"value": 1, "children": [{"value": 2, "children": []}]}
+    assert ormsgpack.unpackb(msgpack.packb(obj), type=Node) == Node(
+        value=1, children=[Node(value=2, children=[])]
+    )
+
+
+def test_typeddict() -> None:
+    obj = {"title": "a", "year": 1}
+    assert ormsgpack.unpackb(msgpack.packb(obj), type=Movie) == obj
+
+
+@pytest.mark.parametrize(
+    "obj,expected",
+    (
+        pytest.param([1, 2], Point(1, 2), id="array"),
+        pytest.param([1], Point(1, 0), id="array default"),
+        pytest.param({"y": 2, "x": 1}, Point(1, 2), id="map"),
+    ),
+)
+def test_namedtuple(obj: Any, expected: Point) -> None:
+    assert ormsgpack.unpackb(msgpack.packb(obj), type=Point) == expected
+
+
+def test_union_of_classes() -> None:
+    type = Union[Movie, Item]
+    assert ormsgpack.unpackb(
+        msgpack.packb({"name": "a", "price": 1.0}), type=type
+    ) == Item(name="a", price=1.0)
+
+
+def test_type_mismatch() -> None:
+    obj = {"id": 1, "items": [{"name": "a", "price": "b"}], "status": "open"}
+    exc = decode_error(obj, Order)
+    assert str(exc) == "invalid type FixStr(1), expected float"
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_TYPE
+    assert exc.path == "$.items[0].price"
+
+
+def test_type_mismatch_bool() -> None:
+    exc = decode_error(True, int)
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_TYPE
+
+
+def test_type_mismatch_union() -> None:
+    exc = decode_error([1.5], list[Optional[int]])
+    assert str(exc) == "invalid type F64, expected int | None"
+    assert exc.path == "$[0]"
+
+
+def test_literal_mismatch() -> None:
+    exc = decode_error({"id": 1, "items": [], "status": "a"}, Order)
+    assert str(exc) == "invalid value, expected Literal['open', 'closed']"
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_VALUE
+    assert exc.path == "$.status"
+
+
+def test_missing_field() -> None:
+    exc = decode_error([{"title": "a"}], list[Movie])
+    assert str(exc) == "missing required field 'year'"
+    assert exc.kind is MsgpackDecodeErrorKind.INVALID_VALUE
+    assert exc.path == "$[0]"
+
+
+def test_namedtuple_too_many_items() -> None:
+    exc = decode_error([1, 2, 3], Point)
+    assert str(exc) == "too many items for Point"
+
+
+def test_class_failed() -> None:
+    exc = decode_error([{"value": 0}], list[Positive])
+    assert str(exc) == "failed to create Positive: ValueError: not positive"
+    assert exc.path == "$[0]"
+    assert isinstance(exc.__cause__, ValueError)
+
+
+def test_max_depth() -> None:
+    data = msgpack.packb([[1]])
+    assert ormsgpack.unpackb(data, type=list[list[int]], max_depth=3) == [[1]]
+    with pytest.raises(ormsgpack.MsgpackDepthError):
+        ormsgpack.unpackb(data, type=list[list[int]], max_depth=2)
+
+
+def test_schema_cache_gc() -> None:
+    @dataclasses.dataclass
+    class Leaf:
+        value: int
+
+    @dataclasses.dataclass
+    class Tree:
+        leaf: Leaf
+        children: list[Any]
+
+    Tree.__annotations__["children"] = list[Tree]
+    data = msgpack.packb({"leaf": {"value": 1}, "children": []})
+    assert ormsgpack.unpackb(data, type=Tree) == Tree(Leaf(1), [])
+    refs = [weakref.ref(Leaf), weakref.ref(Tree)]
+    del Leaf, Tree
+    gc.collect()
+    assert [ref() for ref in refs] == [None, None]
+
+
+def test_type_none() -> None:
+    assert ormsgpack.unpackb(msgpack.packb([1]), type=None) == [1]
+
+
+@pytest.mark.parametrize(
+    "type",
+    (
+        pytest.param(set, id="set"),
+        pytest.param(dict[int, str], id="dict with int keys"),
+        pytest.param(Literal[1.5], id="float Literal"),
+    ),
+)
+def test_unsupported_type(type: Any) -> None:
+    with pytest.raises(TypeError, match="Unsupported type"):
+        ormsgpack.unpackb(msgpack.packb(1), type=type)
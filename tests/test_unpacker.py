@@ -0,0 +1,188 @@
+# SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+import datetime
+import gc
+import weakref
+
+import msgpack
+import pytest
+
+import ormsgpack
+
+OBJECTS = (
+    None,
+    True,
+    -1,
+    2**64 - 1,
+    -(2**63),
+    1.5,
+    "a" * 300,
+    b"b" * 70000,
+    [1, [2, [3, {}]]],
+    {"a": [1, 2], "b": {"c": None}},
+    msgpack.ExtType(1, b"x" * 16),
+    msgpack.ExtType(2, b"y" * 300),
+)
+
+
+def test_unpacker_single_chunk() -> None:
+    data = b"".join(msgpack.packb(obj) for obj in OBJECTS)
+    unpacker = ormsgpack.Unpacker(ext_hook=msgpack.ExtType)
+    unpacker.feed(data)
+    assert list(unpacker) == list(OBJECTS)
+    assert list(unpacker) == []
+
+
+def test_unpacker_byte_by_byte() -> None:
+    data = b"".join(msgpack.packb(obj) for obj in OBJECTS)
+    unpacker = ormsgpack.Unpacker(ext_hook=msgpack.ExtType)
+    result = []
+    for i in range(len(data)):
+        unpacker.feed(data[i : i + 1])
+        result.extend(unpacker)
+    assert result == list(OBJECTS)
+
+
+def test_unpacker_incomplete() -> None:
+    unpacker = ormsgpack.Unpacker()
+    unpacker.feed(b"\x93\x01\x02")
+    with pytest.raises(StopIteration):
+        next(unpacker)
+    unpacker.feed(b"\x03")
+    assert next(unpacker) == [1, 2, 3]
+
+
+@pytest.mark.parametrize(
+    "data",
+    (
+        pytest.param(b"abc", id="bytes"),
+        pytest.param(bytearray(b"abc"), id="bytearray"),
+        pytest.param(memoryview(b"abc"), id="memoryview"),
+    ),
+)
+def test_unpacker_feed_types(data: bytes) -> None:
+    unpacker = ormsgpack.Unpacker()
+    unpacker.feed(b"\xc4\x03")
+    unpacker.feed(data)
+    assert list(unpacker) == [b"abc"]
+
+
+def test_unpacker_feed_invalid() -> None:
+    unpacker = ormsgpack.Unpacker()
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        unpacker.feed("abc")  # type: ignore[arg-type]
+
+
+def test_unpacker_invalid_message() -> None:
+    """
+    An invalid message is skipped
+    """
+    unpacker = ormsgpack.Unpacker()
+    unpacker.feed(b"\xa2\xff\xfe" + msgpack.packb(1))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        next(unpacker)
+    assert list(unpacker) == [1]
+
+
+def test_unpacker_ext_hook_none() -> None:
+    unpacker = ormsgpack.Unpacker(ext_hook=None)
+    unpacker.feed(msgpack.packb(msgpack.ExtType(1, b"a")))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        next(unpacker)
+
+
+def test_unpacker_ext_hook_reentrant() -> None:
+    unpacker: ormsgpack.Unpacker
+
+    def ext_hook(tag: int, data: bytes) -> bytes:
+        unpacker.feed(data)
+        return data
+
+    unpacker = ormsgpack.Unpacker(ext_hook=ext_hook)
+    unpacker.feed(msgpack.packb(msgpack.ExtType(1, b"a")))
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        next(unpacker)
+
+
+def test_unpacker_option() -> None:
+    unpacker = ormsgpack.Unpacker(option=ormsgpack.OPT_NON_STR_KEYS)
+    unpacker.feed(msgpack.packb({1: True}))
+    assert next(unpacker) == {1: True}
+
+
+def test_unpacker_option_datetime() -> None:
+    obj = datetime.datetime(2020, 1, 1, tzinfo=datetime.timezone.utc)
+    packed = ormsgpack.packb(obj, option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT)
+    unpacker = ormsgpack.Unpacker(option=ormsgpack.OPT_DATETIME_AS_TIMESTAMP_EXT)
+    unpacker.feed(packed)
+    assert next(unpacker) == obj
+
+
+def test_unpacker_invalid_option() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.Unpacker(option=ormsgpack.OPT_SORT_KEYS)
+
+
+def test_unpacker_invalid_args() -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.Unpacker(None)  # type: ignore[call-arg]
+    with pytest.raises(ormsgpack.MsgpackDecodeError):
+        ormsgpack.Unpacker(default=None)  # type: ignore[call-arg]
+
+
+def test_unpacker_gc_cycle() -> None:
+    class Hook:
+        def __call__(self, tag: int, data: bytes) -> object:
+            return tag
+
+    hook = Hook()
+    unpacker = ormsgpack.Unpacker(ext_hook=hook)
+    hook.unpacker = unpacker  # type: ignore[attr-defined]
+    ref = weakref.ref(hook)
+    del hook, unpacker
+    gc.collect()
+    assert ref() is None
+
+
+def test_unpacker_ext_registry_gc_cycle() -> None:
+    class FromPayload:
+        def __call__(self, payload: bytes) -> object:
+            return payload
+
+    from_payload = FromPayload()
+    registry = ormsgpack.ExtRegistry()
+    registry.register(bytes, 1, bytes, from_payload)
+    unpacker = ormsgpack.Unpacker(ext_registry=registry)
+    from_payload.unpacker = unpacker  # type: ignore[attr-defined]
+    ref = weakref.ref(from_payload)
+    del from_payload, registry, unpacker
+    gc.collect()
+    assert ref() is None
+
+
+def test_unpacker_max_buffer_size() -> None:
+    unpacker = ormsgpack.Unpacker(max_buffer_size=8)
+    unpacker.feed(b"\xc6\xff\xff\xff\xff")
+    with pytest.raises(ormsgpack.MsgpackLimitError, match="max_buffer_size exceeded"):
+        unpacker.feed(b"\x00" * 4)
+    assert list(unpacker) == []
+
+    unpacker = ormsgpack.Unpacker(max_buffer_size=4)
+    unpacker.feed(msgpack.packb([1, 2, 3]))
+    with pytest.raises(ormsgpack.MsgpackLimitError):
+        unpacker.feed(msgpack.packb(4))
+    assert list(unpacker) == [[1, 2, 3]]
+    unpacker.feed(msgpack.packb(4))
+    assert list(unpacker) == [4]
+
+
+def test_unpacker_max_buffer_size_none() -> None:
+    unpacker = ormsgpack.Unpacker(max_buffer_size=None)
+    unpacker.feed(msgpack.packb(b"a" * 1000))
+    assert list(unpacker) == [b"a" * 1000]
+
+
+@pytest.mark.parametrize("value", (-1, 1.0, "1"))
+def test_unpacker_invalid_max_buffer_size(value: object) -> None:
+    with pytest.raises(ormsgpack.MsgpackDecodeError, match="Invalid max_buffer_size"):
+        ormsgpack.Unpacker(max_buffer_size=value)  # type: ignore[arg-type]